@@ -1,4 +1,4 @@
-use std::io::{self, Write};
+use std::io::{self, BufRead, Write};
 
 pub fn read_line(prompt: &str) -> Result<String, io::Error> {
     print!("{}", prompt);
@@ -13,6 +13,40 @@ pub fn read_line(prompt: &str) -> Result<String, io::Error> {
     Ok(String::from(name.trim()))
 }
 
+/// A source of answers to prompts, such as the user through stdin or a script.
+pub trait Prompter {
+    /// Asks for a line of input. Returns the line without surrounding whitespace and fails at the end of the input.
+    ///
+    /// # Arguments
+    /// * `prompt` - Text shown before reading the line.
+    fn prompt(&mut self, prompt: &str) -> Result<String, io::Error>;
+}
+
+/// Prompts the user through stdout and reads the answers from stdin.
+pub struct StdinPrompter;
+
+impl Prompter for StdinPrompter {
+    fn prompt(&mut self, prompt: &str) -> Result<String, io::Error> {
+        print!("{}", prompt);
+        io::stdout().flush()?;
+
+        io::stdin().lock().prompt(prompt)
+    }
+}
+
+/// Reads the answers from any buffered reader, one per line. Prompts are not shown.
+impl<R: BufRead> Prompter for R {
+    fn prompt(&mut self, _: &str) -> Result<String, io::Error> {
+        let mut line = String::new();
+
+        if self.read_line(&mut line)? == 0 {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "No more input"));
+        }
+
+        Ok(String::from(line.trim()))
+    }
+}
+
 pub fn slice2(slice: &[u8]) -> [u8; 2] {
     slice.try_into().expect("The slice should have a length of 2")
 }
@@ -18,16 +18,18 @@ fn main() {
     // week2::readability::main();
     // week2::caesar::main();
     // week2::substitution::main();
-    // week3::sort::main();
-    // week3::plurality::main();
-    // week3::runoff::main();
-    // week3::tideman::main();
+    // week3::sortbench::main();
+    // week3::voting::plurality::main();
+    // week3::voting::runoff::main();
+    // week3::voting::tideman::main();
+    // week3::voting::schulze::main();
     // week4::volume::main();
     // week4::copy::main();
     // week4::filter::main();
     // week4::recover::main();
     week5::inheritance::main();
     // week5::speller::main();
+    // week5::hashbench::main();
     // week6::bank::main();
     // week6::figlet::main();
     // week6::jar::main();
@@ -35,4 +37,5 @@ fn main() {
     // week6::taqueria::main();
     // week6::world_cup::main();
     // week6::dna::main();
+    // week6::dnabench::main();
 }
@@ -1,6 +1,5 @@
 use super::helpers;
 
 pub mod sort;
-pub mod plurality;
-pub mod runoff;
-pub mod tideman;
\ No newline at end of file
+pub mod sortbench;
+pub mod voting;
@@ -1,251 +1,1809 @@
 use std::cmp::Ordering;
 use std::collections::VecDeque;
-use rand::Rng;
-use std::time::{Instant, Duration};
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+use std::ops::Range;
+
+/// Partitions of this length or smaller are sorted with insertion sort by introsort.
+const INSERTION_SORT_THRESHOLD: usize = 16;
+
+/// Number of consecutive wins of the same run after which timsort's merge switches to galloping.
+const MIN_GALLOP: usize = 7;
 
 /// An indexable data type that can be sorted.
+///
+/// Every algorithm also has a `_by` variant taking a function which specifies if an element is smaller
+/// than another, and a `_by_key` variant which orders elements by the key computed by the given function.
+/// Keys are computed again on every comparison.
 pub trait Sortable<T> {
-    fn selection_sort(&mut self);
-    fn bubble_sort(&mut self);
-    fn merge(sortable: &mut Self, start: usize, end: usize);
-    fn merge_sort(&mut self);
+    fn selection_sort_by<F: Fn(&T, &T) -> bool>(&mut self, is_smaller: &F);
+    fn bubble_sort_by<F: Fn(&T, &T) -> bool>(&mut self, is_smaller: &F);
+    fn merge_sort_by<F: Fn(&T, &T) -> bool>(&mut self, is_smaller: &F);
+    fn insertion_sort_by<F: Fn(&T, &T) -> bool>(&mut self, is_smaller: &F);
+    fn heap_sort_by<F: Fn(&T, &T) -> bool>(&mut self, is_smaller: &F);
+    fn tim_sort_by<F: Fn(&T, &T) -> bool>(&mut self, is_smaller: &F);
+    fn shell_sort_by<F: Fn(&T, &T) -> bool>(&mut self, gaps: GapSequence, is_smaller: &F);
+    fn select_nth_by<F: Fn(&T, &T) -> bool>(&mut self, k: usize, is_smaller: &F) -> &T;
+    fn partial_sort_by<F: Fn(&T, &T) -> bool>(&mut self, k: usize, is_smaller: &F) -> Vec<&T>;
+
+    /// Sorts with the chosen algorithm and the specified comparator function. Slices already have an inherent
+    /// `sort_by` which would shadow a method with that name, so this one is called `sort_with`.
+    fn sort_with<F: Fn(&T, &T) -> bool>(&mut self, algorithm: SortAlgorithm, is_smaller: &F);
+
+    fn selection_sort(&mut self) where T: Ord {
+        self.selection_sort_by(&|smaller, greater| smaller < greater);
+    }
+
+    fn bubble_sort(&mut self) where T: Ord {
+        self.bubble_sort_by(&|smaller, greater| smaller < greater);
+    }
+
+    fn merge_sort(&mut self) where T: Ord {
+        self.merge_sort_by(&|smaller, greater| smaller < greater);
+    }
+
+    fn insertion_sort(&mut self) where T: Ord {
+        self.insertion_sort_by(&|smaller, greater| smaller < greater);
+    }
+
+    fn heap_sort(&mut self) where T: Ord {
+        self.heap_sort_by(&|smaller, greater| smaller < greater);
+    }
+
+    fn tim_sort(&mut self) where T: Ord {
+        self.tim_sort_by(&|smaller, greater| smaller < greater);
+    }
+
+    fn shell_sort(&mut self, gaps: GapSequence) where T: Ord {
+        self.shell_sort_by(gaps, &|smaller, greater| smaller < greater);
+    }
+
+    /// Finds the k-th smallest element (starting from 0) using quickselect. Runs in O(n) on average.
+    /// Reorders the elements so that the k-th element is at position k, with smaller elements before it
+    /// and greater elements after it. Panics if k is out of bounds.
+    fn select_nth(&mut self, k: usize) -> &T where T: Ord {
+        self.select_nth_by(k, &|smaller, greater| smaller < greater)
+    }
+
+    /// Finds the k smallest elements using a bounded heap, without sorting the rest of the elements.
+    /// Moves them in order to the start and returns them. If k is bigger than the length every element is returned.
+    fn partial_sort(&mut self, k: usize) -> Vec<&T> where T: Ord {
+        self.partial_sort_by(k, &|smaller, greater| smaller < greater)
+    }
+
+    fn selection_sort_by_key<K: Ord, F: Fn(&T) -> K>(&mut self, key: F) {
+        self.selection_sort_by(&|smaller, greater| key(smaller) < key(greater));
+    }
+
+    fn bubble_sort_by_key<K: Ord, F: Fn(&T) -> K>(&mut self, key: F) {
+        self.bubble_sort_by(&|smaller, greater| key(smaller) < key(greater));
+    }
+
+    fn merge_sort_by_key<K: Ord, F: Fn(&T) -> K>(&mut self, key: F) {
+        self.merge_sort_by(&|smaller, greater| key(smaller) < key(greater));
+    }
+
+    fn insertion_sort_by_key<K: Ord, F: Fn(&T) -> K>(&mut self, key: F) {
+        self.insertion_sort_by(&|smaller, greater| key(smaller) < key(greater));
+    }
+
+    fn heap_sort_by_key<K: Ord, F: Fn(&T) -> K>(&mut self, key: F) {
+        self.heap_sort_by(&|smaller, greater| key(smaller) < key(greater));
+    }
+
+    fn tim_sort_by_key<K: Ord, F: Fn(&T) -> K>(&mut self, key: F) {
+        self.tim_sort_by(&|smaller, greater| key(smaller) < key(greater));
+    }
+
+    fn shell_sort_by_key<K: Ord, F: Fn(&T) -> K>(&mut self, gaps: GapSequence, key: F) {
+        self.shell_sort_by(gaps, &|smaller, greater| key(smaller) < key(greater));
+    }
+
+    /// Sorts with the chosen algorithm. Elements are ordered by the key computed by the given function.
+    fn sort_with_key<K: Ord, F: Fn(&T) -> K>(&mut self, algorithm: SortAlgorithm, key: F) {
+        self.sort_with(algorithm, &|smaller, greater| key(smaller) < key(greater));
+    }
 }
 
-impl<T: Ord + Clone> Sortable<T> for [T] {
-    fn selection_sort(&mut self) {
-        let length = self.len();
+impl<T: Clone> Sortable<T> for [T] {
+    fn selection_sort_by<F: Fn(&T, &T) -> bool>(&mut self, is_smaller: &F) {
+        selection_sort_observed(self, is_smaller, &mut Events::new(&mut ignore_events));
+    }
+
+    fn bubble_sort_by<F: Fn(&T, &T) -> bool>(&mut self, is_smaller: &F) {
+        bubble_sort_observed(self, is_smaller, &mut Events::new(&mut ignore_events));
+    }
 
-        for i in 0..length {
-            let mut min = i;
+    fn merge_sort_by<F: Fn(&T, &T) -> bool>(&mut self, is_smaller: &F) {
+        merge_sort_observed(self, is_smaller, &mut Events::new(&mut ignore_events));
+    }
 
-            for j in i..length {
-                if self[j] < self[min] {
-                    min = j;
-                }
+    fn insertion_sort_by<F: Fn(&T, &T) -> bool>(&mut self, is_smaller: &F) {
+        insertion_sort_by(self, is_smaller);
+    }
+
+    fn heap_sort_by<F: Fn(&T, &T) -> bool>(&mut self, is_smaller: &F) {
+        heap_sort_by(self, is_smaller);
+    }
+
+    fn tim_sort_by<F: Fn(&T, &T) -> bool>(&mut self, is_smaller: &F) {
+        tim_sort_by(self, is_smaller);
+    }
+
+    fn shell_sort_by<F: Fn(&T, &T) -> bool>(&mut self, gaps: GapSequence, is_smaller: &F) {
+        shell_sort_observed(self, gaps, is_smaller, &mut Events::new(&mut ignore_events));
+    }
+
+    fn select_nth_by<F: Fn(&T, &T) -> bool>(&mut self, k: usize, is_smaller: &F) -> &T {
+        assert!(k < self.len(), "Index {} is out of bounds for length {}", k, self.len());
+        let (mut start, mut end) = (0, self.len());
+
+        while end - start > 1 {
+            let pivot_position = start + quicksort_partition(&mut self[start..end], is_smaller, &mut Events::new(&mut ignore_events));
+
+            if k < pivot_position {
+                end = pivot_position;
+            } else if k > pivot_position {
+                start = pivot_position + 1;
+            } else {
+                break;
+            }
+        }
+
+        &self[k]
+    }
+
+    fn partial_sort_by<F: Fn(&T, &T) -> bool>(&mut self, k: usize, is_smaller: &F) -> Vec<&T> {
+        let k = usize::min(k, self.len());
+        let mut on_event = ignore_events;
+        let events = &mut Events::new(&mut on_event);
+
+        // The first k elements form a max heap with the smallest elements found so far.
+        for start in (0..k / 2).rev() {
+            sift_down(self, start, k, is_smaller, events);
+        }
+
+        for i in k..self.len() {
+            if k > 0 && is_smaller(&self[i], &self[0]) {
+                self.swap(0, i);
+                sift_down(self, 0, k, is_smaller, events);
             }
+        }
 
-            self.swap(i, min);
+        for end in (1..k).rev() {
+            self.swap(0, end);
+            sift_down(self, 0, end, is_smaller, events);
         }
+
+        self[..k].iter().collect()
     }
 
-    fn bubble_sort(&mut self) {
-        let mut end = false;
-        let n = self.len() - 1;
+    fn sort_with<F: Fn(&T, &T) -> bool>(&mut self, algorithm: SortAlgorithm, is_smaller: &F) {
+        sort_observed_by(self, algorithm, is_smaller, ignore_events);
+    }
+}
+
+impl<T: Clone> Sortable<T> for VecDeque<T> {
+    fn selection_sort_by<F: Fn(&T, &T) -> bool>(&mut self, is_smaller: &F) {
+        self.make_contiguous().selection_sort_by(is_smaller);
+    }
+
+    fn bubble_sort_by<F: Fn(&T, &T) -> bool>(&mut self, is_smaller: &F) {
+        self.make_contiguous().bubble_sort_by(is_smaller);
+    }
+
+    fn merge_sort_by<F: Fn(&T, &T) -> bool>(&mut self, is_smaller: &F) {
+        self.make_contiguous().merge_sort_by(is_smaller);
+    }
+
+    fn insertion_sort_by<F: Fn(&T, &T) -> bool>(&mut self, is_smaller: &F) {
+        self.make_contiguous().insertion_sort_by(is_smaller);
+    }
+
+    fn heap_sort_by<F: Fn(&T, &T) -> bool>(&mut self, is_smaller: &F) {
+        self.make_contiguous().heap_sort_by(is_smaller);
+    }
+
+    fn tim_sort_by<F: Fn(&T, &T) -> bool>(&mut self, is_smaller: &F) {
+        self.make_contiguous().tim_sort_by(is_smaller);
+    }
+
+    fn shell_sort_by<F: Fn(&T, &T) -> bool>(&mut self, gaps: GapSequence, is_smaller: &F) {
+        self.make_contiguous().shell_sort_by(gaps, is_smaller);
+    }
 
-        for i in 0..n {
-            if end { break };
-            end = true;
+    fn select_nth_by<F: Fn(&T, &T) -> bool>(&mut self, k: usize, is_smaller: &F) -> &T {
+        self.make_contiguous().select_nth_by(k, is_smaller)
+    }
+
+    fn partial_sort_by<F: Fn(&T, &T) -> bool>(&mut self, k: usize, is_smaller: &F) -> Vec<&T> {
+        self.make_contiguous().partial_sort_by(k, is_smaller)
+    }
+
+    fn sort_with<F: Fn(&T, &T) -> bool>(&mut self, algorithm: SortAlgorithm, is_smaller: &F) {
+        self.make_contiguous().sort_with(algorithm, is_smaller);
+    }
+}
+
+/// Gap sequences for shell sort. Every sequence ends with a gap of 1, which is a plain insertion sort.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GapSequence {
+    /// Knuth's sequence 1, 4, 13, 40, ... where each gap is 3 times the last one plus 1.
+    Knuth,
+    /// Ciura's empirically found sequence 1, 4, 10, 23, 57, 132, 301, 701, extended by multiplying by 2.25.
+    Ciura
+}
 
-            for j in (i..n).rev() {
-                if self[j + 1] < self[j] {
-                    self.swap(j, j + 1);
-                    end = false;
+impl GapSequence {
+    /// Ciura's sequence, as found experimentally.
+    const CIURA: [usize; 8] = [1, 4, 10, 23, 57, 132, 301, 701];
+
+    /// Computes the gaps to use on an array of the given length, from biggest to smallest.
+    ///
+    /// # Arguments
+    /// * `length` - The array's length.
+    pub fn gaps(&self, length: usize) -> Vec<usize> {
+        let mut gaps = vec![1];
+
+        match self {
+            GapSequence::Knuth => while gaps[gaps.len() - 1] < length / 3 {
+                gaps.push(gaps[gaps.len() - 1] * 3 + 1);
+            },
+            GapSequence::Ciura => {
+                gaps.extend(Self::CIURA[1..].iter().take_while(|&&gap| gap < length));
+
+                while gaps.len() >= Self::CIURA.len() && gaps[gaps.len() - 1] * 9 / 4 < length {
+                    gaps.push(gaps[gaps.len() - 1] * 9 / 4);
                 }
             }
         }
+
+        gaps.reverse();
+        gaps
     }
+}
 
-    fn merge(sortable: &mut Self, start: usize, end: usize) {
-        if start < end - 1 {
-            let mid = start + (end - start) / 2;
-            Sortable::merge(sortable, start, mid);
-            Sortable::merge(sortable, mid, end);
+/// An event which happened while sorting an array. Positions are indices into the whole array.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortEvent {
+    /// The elements at the given positions were compared.
+    /// Merges report the element taken from their scratch buffer at the position it was copied from.
+    Compare(usize, usize),
+    /// The elements at the given positions were swapped.
+    Swap(usize, usize),
+    /// An element was copied into the given position. Merges move elements this way instead of swapping them.
+    Write(usize)
+}
 
-            let mut empty: VecDeque<usize> = VecDeque::new();
-            let mut queue: VecDeque<T> = VecDeque::new();
-            let mut pointer2 = mid;
+/// The comparison sorting algorithms which can be chosen with `Sortable::sort_with` or observed with `sort_observed`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortAlgorithm {
+    Selection,
+    Bubble,
+    Insertion,
+    Merge,
+    Heap,
+    Tim,
+    Shell(GapSequence),
+    Quick,
+    /// Quicksort with random pivots chosen by an RNG seeded with the given seed.
+    QuickRandom(u64),
+    Intro
+}
 
-            for min_ptr in start..end {
-                match (queue.get(0), pointer2 < end) {
-                    (Some(element), true) => if sortable[pointer2] < *element {
-                        if empty.len() > 0 && min_ptr == *empty.get(0).unwrap() {
-                            empty.pop_front();
-                        } else {
-                            empty.push_back(pointer2);
-                            queue.push_back(sortable[min_ptr].clone());
-                        }
+/// Forwards the events of a sort to a callback, translating positions in a subarray into positions in the whole array.
+struct Events<'a, E: FnMut(SortEvent)> {
+    /// The callback.
+    on_event: &'a mut E,
+    /// Position in the whole array where the current subarray starts.
+    offset: usize
+}
 
-                        sortable.swap(min_ptr, pointer2);
-                        pointer2 += 1;
-                    } else {
-                        let last_empty = empty.get(0);
+impl<'a, E: FnMut(SortEvent)> Events<'a, E> {
+    /// Creates a new event forwarder for a whole array.
+    ///
+    /// # Arguments
+    /// * `on_event` - The callback.
+    fn new(on_event: &'a mut E) -> Self {
+        Self {
+            on_event,
+            offset: 0
+        }
+    }
 
-                        if (last_empty != None && min_ptr == *last_empty.unwrap()) {
-                            empty.pop_front();
-                        } else {
-                            queue.push_back(sortable[min_ptr].clone());
-                        }
+    /// Creates an event forwarder for the subarray starting at the given position.
+    ///
+    /// # Arguments
+    /// * `position` - Position where the subarray starts, relative to the current one.
+    fn at(&mut self, position: usize) -> Events<'_, E> {
+        Events {
+            on_event: &mut *self.on_event,
+            offset: self.offset + position
+        }
+    }
 
-                        sortable[min_ptr] = queue.pop_front().unwrap().clone();
-                        empty.push_back(pointer2);
-                    },
-                    (Some(_), false) => {
-                        let last_empty = empty.get(0);
+    /// Reports a comparison between 2 positions.
+    fn compare(&mut self, i: usize, j: usize) {
+        (self.on_event)(SortEvent::Compare(self.offset + i, self.offset + j));
+    }
 
-                        if (last_empty != None && min_ptr == *last_empty.unwrap()) {
-                            empty.pop_front();
-                        } else {
-                            queue.push_back(sortable[min_ptr].clone());
-                        }
+    /// Reports a write to a position.
+    fn write(&mut self, i: usize) {
+        (self.on_event)(SortEvent::Write(self.offset + i));
+    }
 
-                        sortable[min_ptr] = queue.get(0).unwrap().clone();
-                        queue.pop_front();
-                    },
-                    (None, true) if min_ptr < mid => if sortable[pointer2] < sortable[min_ptr] {
-                        queue.push_back(sortable[min_ptr].clone());
-                        sortable.swap(min_ptr, pointer2);
-                        empty.push_back(pointer2);
-                        pointer2 += 1;
-                    },
-                    (None, _) => ()
-                };
-            }
-        };
+    /// Checks if the element at position i is smaller than the one at position j and reports the comparison.
+    fn is_smaller<T, F: Fn(&T, &T) -> bool>(&mut self, array: &[T], i: usize, j: usize, is_smaller: &F) -> bool {
+        self.compare(i, j);
+        is_smaller(&array[i], &array[j])
     }
 
-    fn merge_sort(&mut self) {
-        Sortable::merge(self, 0, self.len());
+    /// Swaps the elements at 2 different positions and reports the swap.
+    fn swap<T>(&mut self, array: &mut [T], i: usize, j: usize) {
+        if i != j {
+            (self.on_event)(SortEvent::Swap(self.offset + i, self.offset + j));
+            array.swap(i, j);
+        }
     }
 }
 
-pub fn main() {
-    // Loads test data.
-    let mut array1: [i32; 10000] = (0..10000).collect::<Vec<i32>>().try_into().unwrap();
+/// Event callback used by the sorts which are not being observed.
+fn ignore_events(_: SortEvent) {}
+
+/// A type which can be turned into an unsigned integer key preserving its order.
+pub trait RadixKey {
+    fn radix_key(&self) -> u64;
+}
+
+macro_rules! impl_radix_key_unsigned {
+    ($($t:ty),*) => {
+        $(impl RadixKey for $t {
+            fn radix_key(&self) -> u64 {
+                *self as u64
+            }
+        })*
+    };
+}
 
-    //  Clones test data for each algorithm.
-    rand::thread_rng().fill(&mut array1[..]);
-    let mut array2 = array1.clone();
-    let mut array3 = array1.clone();
-    let mut array4 = array1.to_vec();
-    let mut array5 = array1.clone();
+macro_rules! impl_radix_key_signed {
+    ($($t:ty),*) => {
+        $(impl RadixKey for $t {
+            fn radix_key(&self) -> u64 {
+                // Flipping the sign bit maps negative numbers below positive ones.
+                (*self as i64 as u64) ^ (1 << 63)
+            }
+        })*
+    };
+}
 
-    // Benchmarks each algorithm.
-    let mut start = Instant::now();
-    array1.selection_sort();
-    println!("Selection Sort: {}s", start.elapsed().as_secs_f64());
+impl_radix_key_unsigned!(u8, u16, u32, u64, usize);
+impl_radix_key_signed!(i8, i16, i32, i64, isize);
 
-    start = Instant::now();
-    array2.bubble_sort();
-    println!("Bubble Sort: {}s", start.elapsed().as_secs_f64());
+/// An indexable collection of integer keys that can be sorted without comparisons.
+pub trait RadixSortable {
+    fn counting_sort(&mut self);
+    fn radix_sort(&mut self);
+}
 
-    start = Instant::now();
-    array3.merge_sort();
-    println!("Merge Sort 1: {}s", start.elapsed().as_secs_f64());
+impl<T: RadixKey + Clone> RadixSortable for [T] {
+    /// Uses memory proportional to the range between the smallest and largest keys,
+    /// so it should only be used when that range is small.
+    fn counting_sort(&mut self) {
+        let min = match self.iter().map(RadixKey::radix_key).min() {
+            Some(min) => min,
+            None => return
+        };
 
-    start = Instant::now();
-    merge(&mut array4);
-    println!("Merge Sort 2: {}s", start.elapsed().as_secs_f64());
+        counting_sort_by_key(self, |item| (item.radix_key() - min) as usize);
+    }
 
-    start = Instant::now();
-    quicksort(&mut array5);
-    println!("Quicksort: {}s", start.elapsed().as_secs_f64());
+    fn radix_sort(&mut self) {
+        radix_sort_by_key(self, RadixKey::radix_key);
+    }
 }
 
-/// Sorts an array using quicksort.
+/// Sorts an array with the given algorithm, calling a function on every comparison, swap and write.
+/// Useful to visualize a sort step by step or to count the operations it does.
 ///
 /// # Arguments
 /// * `array` - The array to sort.
-pub fn quicksort<T: Ord + Clone>(array: &mut [T]) {
-    quicksort_by(array, &|smaller, greater| smaller < greater);
+/// * `algorithm` - The sorting algorithm.
+/// * `on_event` - Function called on every event of the sort.
+pub fn sort_observed<T: Ord + Clone, E: FnMut(SortEvent)>(array: &mut [T], algorithm: SortAlgorithm, on_event: E) {
+    sort_observed_by(array, algorithm, &|smaller, greater| smaller < greater, on_event);
 }
 
-/// Sorts an array using quicksort. With the specified comparator function.
+/// Sorts an array with the given algorithm and comparator function, calling a function on every comparison, swap and write.
 ///
 /// # Arguments
 /// * `array` - The array to sort.
+/// * `algorithm` - The sorting algorithm.
 /// * `is_smaller` - Function which specifies if the current element is smaller than the other.
-pub fn quicksort_by<T: Clone, F: Fn(&T, &T) -> bool>(array: &mut [T], is_smaller: &F) {
-    let length = array.len();
+/// * `on_event` - Function called on every event of the sort.
+pub fn sort_observed_by<T: Clone, F: Fn(&T, &T) -> bool, E: FnMut(SortEvent)>(array: &mut [T], algorithm: SortAlgorithm, is_smaller: &F, mut on_event: E) {
+    let events = &mut Events::new(&mut on_event);
+    let depth_limit = 2 * (usize::BITS - array.len().leading_zeros()) as usize;
 
-    if length > 1 {
-        let pivot_position = quicksort_partition(array, is_smaller);
-        quicksort_by(&mut array[0..pivot_position], is_smaller);
-        quicksort_by(&mut array[pivot_position + 1..length], is_smaller);
+    match algorithm {
+        SortAlgorithm::Selection => selection_sort_observed(array, is_smaller, events),
+        SortAlgorithm::Bubble => bubble_sort_observed(array, is_smaller, events),
+        SortAlgorithm::Insertion => insertion_sort_observed(array, is_smaller, events),
+        SortAlgorithm::Merge => merge_sort_observed(array, is_smaller, events),
+        SortAlgorithm::Heap => heap_sort_observed(array, is_smaller, events),
+        SortAlgorithm::Tim => tim_sort_observed(array, is_smaller, events),
+        SortAlgorithm::Shell(gaps) => shell_sort_observed(array, gaps, is_smaller, events),
+        SortAlgorithm::Quick => quicksort_observed(array, is_smaller, events),
+        SortAlgorithm::QuickRandom(seed) => quicksort_rand_recursive(array, depth_limit, &mut StdRng::seed_from_u64(seed), is_smaller, events),
+        SortAlgorithm::Intro => intro_sort_recursive(array, depth_limit, is_smaller, events)
     }
 }
 
-/// Partitions a quicksort array into 2 subarrays.
+/// Sorts an array using selection sort.
 ///
 /// # Arguments
 /// * `array` - The array to sort.
 /// * `is_smaller` - Function which specifies if the current element is smaller than the other.
-fn quicksort_partition<T: Clone, F: Fn(&T, &T) -> bool>(array: &mut [T], is_smaller: &F) -> usize {
-    let length: usize = array.len();
+/// * `events` - Receives the sort's events.
+fn selection_sort_observed<T, F: Fn(&T, &T) -> bool, E: FnMut(SortEvent)>(array: &mut [T], is_smaller: &F, events: &mut Events<E>) {
+    let length = array.len();
 
-    if length == 2 {
-        if is_smaller(&array[1], &array[0]) {
-            array.swap(0, 1);
+    for i in 0..length {
+        let mut min = i;
+
+        for j in i + 1..length {
+            if events.is_smaller(array, j, min, is_smaller) {
+                min = j;
+            }
         }
 
-        return 1;
+        events.swap(array, i, min);
     }
+}
 
-    position_pivot(array, is_smaller);
-    let pivot = array[length - 1].clone();
-    let mut pivot_position: usize = 0;
+/// Sorts an array using bubble sort. Stops early once a pass does not swap anything.
+///
+/// # Arguments
+/// * `array` - The array to sort.
+/// * `is_smaller` - Function which specifies if the current element is smaller than the other.
+/// * `events` - Receives the sort's events.
+fn bubble_sort_observed<T, F: Fn(&T, &T) -> bool, E: FnMut(SortEvent)>(array: &mut [T], is_smaller: &F, events: &mut Events<E>) {
+    let mut end = false;
+    let n = array.len().saturating_sub(1);
 
-    for i in 0..length - 1 {
-        if is_smaller(&array[i], &pivot) {
-            array.swap(i, pivot_position);
-            pivot_position += 1;
+    for i in 0..n {
+        if end { break };
+        end = true;
+
+        for j in (i..n).rev() {
+            if events.is_smaller(array, j + 1, j, is_smaller) {
+                events.swap(array, j, j + 1);
+                end = false;
+            }
         }
     }
+}
 
-    array.swap(pivot_position, length - 1);
-    pivot_position
+/// Sorts an array using shell sort: insertion sorts between elements which are a gap apart, for decreasing gaps.
+///
+/// # Arguments
+/// * `array` - The array to sort.
+/// * `gaps` - The gap sequence to use.
+/// * `is_smaller` - Function which specifies if the current element is smaller than the other.
+/// * `events` - Receives the sort's events.
+fn shell_sort_observed<T, F: Fn(&T, &T) -> bool, E: FnMut(SortEvent)>(array: &mut [T], gaps: GapSequence, is_smaller: &F, events: &mut Events<E>) {
+    for gap in gaps.gaps(array.len()) {
+        for i in gap..array.len() {
+            let mut j = i;
+
+            while j >= gap && events.is_smaller(array, j, j - gap, is_smaller) {
+                events.swap(array, j, j - gap);
+                j -= gap;
+            }
+        }
+    }
 }
 
-/// Selects a pivot and positions it at the end of the array.
+/// Sorts an array using a bottom-up merge sort with a single scratch buffer. The sort is stable.
 ///
 /// # Arguments
 /// * `array` - The array to sort.
 /// * `is_smaller` - Function which specifies if the current element is smaller than the other.
-fn position_pivot<T: Clone, F: Fn(&T, &T) -> bool>(array: &mut [T], is_smaller: &F) {
-    let length: usize = array.len();
-    let mid: usize = length / 2;
+/// * `events` - Receives the sort's events.
+fn merge_sort_observed<T: Clone, F: Fn(&T, &T) -> bool, E: FnMut(SortEvent)>(array: &mut [T], is_smaller: &F, events: &mut Events<E>) {
+    let length = array.len();
 
-    let pivot_position = if is_smaller(&array[mid], &array[0]) ^ is_smaller(&array[length - 1], &array[0]) {
-        0
-    } else if is_smaller(&array[0], &array[mid]) ^ is_smaller(&array[length - 1], &array[mid]) {
-        mid
-    } else {
-        length - 1
-    };
+    if is_sorted_observed(array, is_smaller, events) {
+        return;
+    }
+
+    let mut buffer: Vec<T> = Vec::with_capacity(length / 2 + 1);
+    let mut width = 1;
+
+    while width < length {
+        for start in (0..length).step_by(2 * width) {
+            let mid = start + width;
+            let end = usize::min(start + 2 * width, length);
+
+            if mid < end {
+                merge_runs(&mut array[start..end], width, &mut buffer, is_smaller, &mut events.at(start));
+            }
+        }
+
+        width *= 2;
+    }
+}
+
+/// Checks if an array is sorted in ascending order.
+///
+/// # Arguments
+/// * `array` - The array.
+pub fn is_sorted<T: Ord>(array: &[T]) -> bool {
+    is_sorted_by(array, &|smaller, greater| smaller < greater)
+}
+
+/// Checks if an array is sorted according to the specified comparator function.
+///
+/// # Arguments
+/// * `array` - The array.
+/// * `is_smaller` - Function which specifies if the current element is smaller than the other.
+pub fn is_sorted_by<T, F: Fn(&T, &T) -> bool>(array: &[T], is_smaller: &F) -> bool {
+    is_sorted_observed(array, is_smaller, &mut Events::new(&mut ignore_events))
+}
+
+/// Checks if an array is sorted according to the specified comparator function, reporting the comparisons.
+/// Merge sort uses it to return early on sorted arrays.
+///
+/// # Arguments
+/// * `array` - The array.
+/// * `is_smaller` - Function which specifies if the current element is smaller than the other.
+/// * `events` - Receives the comparisons.
+fn is_sorted_observed<T, F: Fn(&T, &T) -> bool, E: FnMut(SortEvent)>(array: &[T], is_smaller: &F, events: &mut Events<E>) -> bool {
+    array.is_empty() || run_end_observed(array, 0, is_smaller, events) == array.len()
+}
+
+/// Splits an array into its maximal ascending runs. Returns the range of each run in order.
+///
+/// # Arguments
+/// * `array` - The array.
+pub fn find_runs<T: Ord>(array: &[T]) -> Vec<Range<usize>> {
+    find_runs_by(array, &|smaller, greater| smaller < greater)
+}
+
+/// Splits an array into its maximal ascending runs according to the specified comparator function.
+/// Returns the range of each run in order.
+///
+/// # Arguments
+/// * `array` - The array.
+/// * `is_smaller` - Function which specifies if the current element is smaller than the other.
+pub fn find_runs_by<T, F: Fn(&T, &T) -> bool>(array: &[T], is_smaller: &F) -> Vec<Range<usize>> {
+    let mut on_event = ignore_events;
+    let events = &mut Events::new(&mut on_event);
+    let mut runs = Vec::new();
+    let mut start = 0;
+
+    while start < array.len() {
+        let end = run_end_observed(array, start, is_smaller, events);
+        runs.push(start..end);
+        start = end;
+    }
+
+    runs
+}
+
+/// Finds where the maximal ascending run starting at a position ends (exclusive), reporting the comparisons.
+/// Timsort uses it to find its natural runs.
+///
+/// # Arguments
+/// * `array` - The array.
+/// * `start` - Position where the run starts, which must be inside the array.
+/// * `is_smaller` - Function which specifies if the current element is smaller than the other.
+/// * `events` - Receives the comparisons.
+fn run_end_observed<T, F: Fn(&T, &T) -> bool, E: FnMut(SortEvent)>(array: &[T], start: usize, is_smaller: &F, events: &mut Events<E>) -> usize {
+    let mut end = start + 1;
+
+    while end < array.len() && !events.is_smaller(array, end, end - 1, is_smaller) {
+        end += 1;
+    }
+
+    end
+}
+
+/// Sorts an array using quicksort.
+///
+/// # Arguments
+/// * `array` - The array to sort.
+pub fn quicksort<T: Ord + Clone>(array: &mut [T]) {
+    quicksort_by(array, &|smaller, greater| smaller < greater);
+}
+
+/// Sorts an array using quicksort. Elements are ordered by the key computed by the given function.
+///
+/// # Arguments
+/// * `array` - The array to sort.
+/// * `key` - Function which computes an element's key.
+pub fn quicksort_by_key<T: Clone, K: Ord, F: Fn(&T) -> K>(array: &mut [T], key: F) {
+    quicksort_by(array, &|smaller, greater| key(smaller) < key(greater));
+}
 
-    array.swap(pivot_position, length - 1);
+/// Sorts an array using quicksort. With the specified comparator function.
+///
+/// # Arguments
+/// * `array` - The array to sort.
+/// * `is_smaller` - Function which specifies if the current element is smaller than the other.
+pub fn quicksort_by<T: Clone, F: Fn(&T, &T) -> bool>(array: &mut [T], is_smaller: &F) {
+    quicksort_observed(array, is_smaller, &mut Events::new(&mut ignore_events));
 }
 
-/// Recursively sorts an array using the merge sort algorithm.
+/// Recursively sorts an array using quicksort.
 ///
 /// # Arguments
-/// * `sortable` - The array to sort.
-fn merge<T: Ord + Clone>(sortable: &mut Vec<T>) {
-    let length = sortable.len();
+/// * `array` - The array to sort.
+/// * `is_smaller` - Function which specifies if the current element is smaller than the other.
+/// * `events` - Receives the sort's events.
+fn quicksort_observed<T: Clone, F: Fn(&T, &T) -> bool, E: FnMut(SortEvent)>(array: &mut [T], is_smaller: &F, events: &mut Events<E>) {
+    let length = array.len();
 
     if length > 1 {
-        let mid = length / 2;
-        let mut half1 = (&sortable[0..mid]).to_vec();
-        let mut half2 = (&sortable[mid..length]).to_vec();
-
-        merge(&mut half1);
-        merge(&mut half2);
-
-        let (mut ptr1, mut ptr2) = (0, 0);
-
-        for min_ptr in 0..length {
-            if ptr1 >= half1.len() {
-                sortable[min_ptr] = half2[ptr2].clone();
-                ptr2 += 1;
-            } else if ptr2 >= half2.len() {
-                sortable[min_ptr] = half1[ptr1].clone();
-                ptr1 += 1;
-            }else if half1[ptr1] < half2[ptr2] {
-                sortable[min_ptr] = half1[ptr1].clone();
-                ptr1 += 1;
-            } else {
-                sortable[min_ptr] = half2[ptr2].clone();
-                ptr2 += 1;
-            }
-        }
-    };
-}
\ No newline at end of file
+        let pivot_position = quicksort_partition(array, is_smaller, events);
+        quicksort_observed(&mut array[0..pivot_position], is_smaller, events);
+        quicksort_observed(&mut array[pivot_position + 1..length], is_smaller, &mut events.at(pivot_position + 1));
+    }
+}
+
+/// Sorts an array using introsort. This is the recommended general purpose sort.
+///
+/// # Arguments
+/// * `array` - The array to sort.
+pub fn intro_sort<T: Ord + Clone>(array: &mut [T]) {
+    intro_sort_by(array, &|smaller, greater| smaller < greater);
+}
+
+/// Sorts an array using introsort. Elements are ordered by the key computed by the given function.
+///
+/// # Arguments
+/// * `array` - The array to sort.
+/// * `key` - Function which computes an element's key.
+pub fn intro_sort_by_key<T: Clone, K: Ord, F: Fn(&T) -> K>(array: &mut [T], key: F) {
+    intro_sort_by(array, &|smaller, greater| key(smaller) < key(greater));
+}
+
+/// Sorts an array using introsort. With the specified comparator function.
+/// Runs quicksort until the recursion gets deeper than 2 * log2(n), where it switches to heap sort,
+/// and uses insertion sort for small partitions.
+///
+/// # Arguments
+/// * `array` - The array to sort.
+/// * `is_smaller` - Function which specifies if the current element is smaller than the other.
+pub fn intro_sort_by<T: Clone, F: Fn(&T, &T) -> bool>(array: &mut [T], is_smaller: &F) {
+    let depth_limit = 2 * (usize::BITS - array.len().leading_zeros()) as usize;
+    intro_sort_recursive(array, depth_limit, is_smaller, &mut Events::new(&mut ignore_events));
+}
+
+/// Recursive step of introsort.
+///
+/// # Arguments
+/// * `array` - The array to sort.
+/// * `depth_left` - Number of quicksort partitions left before switching to heap sort.
+/// * `is_smaller` - Function which specifies if the current element is smaller than the other.
+/// * `events` - Receives the sort's events.
+fn intro_sort_recursive<T: Clone, F: Fn(&T, &T) -> bool, E: FnMut(SortEvent)>(array: &mut [T], depth_left: usize, is_smaller: &F, events: &mut Events<E>) {
+    let length = array.len();
+
+    if length <= INSERTION_SORT_THRESHOLD {
+        insertion_sort_observed(array, is_smaller, events);
+    } else if depth_left == 0 {
+        heap_sort_observed(array, is_smaller, events);
+    } else {
+        let pivot_position = quicksort_partition(array, is_smaller, events);
+        intro_sort_recursive(&mut array[0..pivot_position], depth_left - 1, is_smaller, events);
+        intro_sort_recursive(&mut array[pivot_position + 1..length], depth_left - 1, is_smaller, &mut events.at(pivot_position + 1));
+    }
+}
+
+/// Sorts an array using insertion sort. With the specified comparator function.
+///
+/// # Arguments
+/// * `array` - The array to sort.
+/// * `is_smaller` - Function which specifies if the current element is smaller than the other.
+pub fn insertion_sort_by<T, F: Fn(&T, &T) -> bool>(array: &mut [T], is_smaller: &F) {
+    insertion_sort_observed(array, is_smaller, &mut Events::new(&mut ignore_events));
+}
+
+/// Sorts an array using insertion sort.
+///
+/// # Arguments
+/// * `array` - The array to sort.
+/// * `is_smaller` - Function which specifies if the current element is smaller than the other.
+/// * `events` - Receives the sort's events.
+fn insertion_sort_observed<T, F: Fn(&T, &T) -> bool, E: FnMut(SortEvent)>(array: &mut [T], is_smaller: &F, events: &mut Events<E>) {
+    for i in 1..array.len() {
+        let mut j = i;
+
+        while j > 0 && events.is_smaller(array, j, j - 1, is_smaller) {
+            events.swap(array, j, j - 1);
+            j -= 1;
+        }
+    }
+}
+
+/// Sorts an array using heap sort. With the specified comparator function.
+///
+/// # Arguments
+/// * `array` - The array to sort.
+/// * `is_smaller` - Function which specifies if the current element is smaller than the other.
+pub fn heap_sort_by<T, F: Fn(&T, &T) -> bool>(array: &mut [T], is_smaller: &F) {
+    heap_sort_observed(array, is_smaller, &mut Events::new(&mut ignore_events));
+}
+
+/// Sorts an array using heap sort.
+///
+/// # Arguments
+/// * `array` - The array to sort.
+/// * `is_smaller` - Function which specifies if the current element is smaller than the other.
+/// * `events` - Receives the sort's events.
+fn heap_sort_observed<T, F: Fn(&T, &T) -> bool, E: FnMut(SortEvent)>(array: &mut [T], is_smaller: &F, events: &mut Events<E>) {
+    let length = array.len();
+
+    for start in (0..length / 2).rev() {
+        sift_down(array, start, length, is_smaller, events);
+    }
+
+    for end in (1..length).rev() {
+        events.swap(array, 0, end);
+        sift_down(array, 0, end, is_smaller, events);
+    }
+}
+
+/// Sorts an array using quicksort with pivots chosen at random by an RNG seeded with the given seed.
+/// Falls back to heap sort if the recursion gets deeper than 2 * log2(n).
+///
+/// # Arguments
+/// * `array` - The array to sort.
+/// * `seed` - Seed for the pivot selection RNG.
+pub fn quicksort_rand<T: Ord + Clone>(array: &mut [T], seed: u64) {
+    quicksort_rand_by(array, seed, &|smaller, greater| smaller < greater);
+}
+
+/// Sorts an array using quicksort with random pivots. With the specified comparator function.
+///
+/// # Arguments
+/// * `array` - The array to sort.
+/// * `seed` - Seed for the pivot selection RNG.
+/// * `is_smaller` - Function which specifies if the current element is smaller than the other.
+pub fn quicksort_rand_by<T: Clone, F: Fn(&T, &T) -> bool>(array: &mut [T], seed: u64, is_smaller: &F) {
+    let depth_limit = 2 * (usize::BITS - array.len().leading_zeros()) as usize;
+    quicksort_rand_recursive(array, depth_limit, &mut StdRng::seed_from_u64(seed), is_smaller, &mut Events::new(&mut ignore_events));
+}
+
+/// Recursive step of randomized quicksort.
+///
+/// # Arguments
+/// * `array` - The array to sort.
+/// * `depth_left` - Number of partitions left before switching to heap sort.
+/// * `rng` - RNG used to choose pivots.
+/// * `is_smaller` - Function which specifies if the current element is smaller than the other.
+/// * `events` - Receives the sort's events.
+fn quicksort_rand_recursive<T: Clone, F: Fn(&T, &T) -> bool, R: Rng, E: FnMut(SortEvent)>(array: &mut [T], depth_left: usize, rng: &mut R, is_smaller: &F, events: &mut Events<E>) {
+    let length = array.len();
+
+    if length < 2 {
+        return;
+    }
+
+    if depth_left == 0 {
+        heap_sort_observed(array, is_smaller, events);
+        return;
+    }
+
+    events.swap(array, rng.gen_range(0..length), length - 1);
+    let pivot_position = partition_around_last(array, is_smaller, events);
+    quicksort_rand_recursive(&mut array[0..pivot_position], depth_left - 1, rng, is_smaller, events);
+    quicksort_rand_recursive(&mut array[pivot_position + 1..length], depth_left - 1, rng, is_smaller, &mut events.at(pivot_position + 1));
+}
+
+/// Partitions a quicksort array into 2 subarrays.
+///
+/// # Arguments
+/// * `array` - The array to sort.
+/// * `is_smaller` - Function which specifies if the current element is smaller than the other.
+/// * `events` - Receives the sort's events.
+fn quicksort_partition<T: Clone, F: Fn(&T, &T) -> bool, E: FnMut(SortEvent)>(array: &mut [T], is_smaller: &F, events: &mut Events<E>) -> usize {
+    let length: usize = array.len();
+
+    if length == 2 {
+        if events.is_smaller(array, 1, 0, is_smaller) {
+            events.swap(array, 0, 1);
+        }
+
+        return 1;
+    }
+
+    position_pivot(array, is_smaller, events);
+    partition_around_last(array, is_smaller, events)
+}
+
+/// Partitions an array into 2 subarrays using its last element as the pivot. Returns the pivot's final position.
+///
+/// # Arguments
+/// * `array` - The array to partition.
+/// * `is_smaller` - Function which specifies if the current element is smaller than the other.
+/// * `events` - Receives the sort's events.
+fn partition_around_last<T: Clone, F: Fn(&T, &T) -> bool, E: FnMut(SortEvent)>(array: &mut [T], is_smaller: &F, events: &mut Events<E>) -> usize {
+    let length: usize = array.len();
+    let pivot = array[length - 1].clone();
+    let mut pivot_position: usize = 0;
+
+    for i in 0..length - 1 {
+        events.compare(i, length - 1);
+
+        if is_smaller(&array[i], &pivot) {
+            events.swap(array, i, pivot_position);
+            pivot_position += 1;
+        }
+    }
+
+    events.swap(array, pivot_position, length - 1);
+    pivot_position
+}
+
+/// Selects a pivot and positions it at the end of the array.
+///
+/// # Arguments
+/// * `array` - The array to sort.
+/// * `is_smaller` - Function which specifies if the current element is smaller than the other.
+/// * `events` - Receives the sort's events.
+fn position_pivot<T: Clone, F: Fn(&T, &T) -> bool, E: FnMut(SortEvent)>(array: &mut [T], is_smaller: &F, events: &mut Events<E>) {
+    let length: usize = array.len();
+    let mid: usize = length / 2;
+
+    let pivot_position = if events.is_smaller(array, mid, 0, is_smaller) ^ events.is_smaller(array, length - 1, 0, is_smaller) {
+        0
+    } else if events.is_smaller(array, 0, mid, is_smaller) ^ events.is_smaller(array, length - 1, mid, is_smaller) {
+        mid
+    } else {
+        length - 1
+    };
+
+    events.swap(array, pivot_position, length - 1);
+}
+
+/// Sorts an array using timsort. With the specified comparator function.
+/// Detects the natural runs already present in the data, so partially sorted arrays are sorted in close to linear time.
+/// The sort is stable.
+///
+/// # Arguments
+/// * `array` - The array to sort.
+/// * `is_smaller` - Function which specifies if the current element is smaller than the other.
+pub fn tim_sort_by<T: Clone, F: Fn(&T, &T) -> bool>(array: &mut [T], is_smaller: &F) {
+    tim_sort_observed(array, is_smaller, &mut Events::new(&mut ignore_events));
+}
+
+/// Sorts an array using timsort.
+///
+/// # Arguments
+/// * `array` - The array to sort.
+/// * `is_smaller` - Function which specifies if the current element is smaller than the other.
+/// * `events` - Receives the sort's events.
+fn tim_sort_observed<T: Clone, F: Fn(&T, &T) -> bool, E: FnMut(SortEvent)>(array: &mut [T], is_smaller: &F, events: &mut Events<E>) {
+    let length = array.len();
+    let min_run = tim_sort_min_run(length);
+    let mut buffer: Vec<T> = Vec::with_capacity(length / 2 + 1);
+    let mut runs: Vec<Range<usize>> = Vec::new();
+    let mut start = 0;
+
+    while start < length {
+        let mut end = start + 1;
+
+        // Strictly descending runs are reversed, equal elements would lose their order otherwise.
+        if end < length && events.is_smaller(array, end, end - 1, is_smaller) {
+            while end < length && events.is_smaller(array, end, end - 1, is_smaller) {
+                end += 1;
+            }
+
+            for i in 0..(end - start) / 2 {
+                events.swap(array, start + i, end - 1 - i);
+            }
+        } else {
+            end = run_end_observed(array, start, is_smaller, events);
+        }
+
+        // Short runs are extended with insertion sort.
+        let forced_end = usize::min(start + min_run, length);
+
+        if end < forced_end {
+            insertion_sort_observed(&mut array[start..forced_end], is_smaller, &mut events.at(start));
+            end = forced_end;
+        }
+
+        runs.push(start..end);
+        tim_sort_collapse(array, &mut runs, &mut buffer, is_smaller, false, events);
+        start = end;
+    }
+
+    tim_sort_collapse(array, &mut runs, &mut buffer, is_smaller, true, events);
+}
+
+/// Computes the minimum run length for timsort, so the number of runs is a power of two or slightly less.
+///
+/// # Arguments
+/// * `length` - Length of the array to sort.
+fn tim_sort_min_run(mut length: usize) -> usize {
+    let mut remainder = 0;
+
+    while length >= 64 {
+        remainder |= length & 1;
+        length >>= 1;
+    }
+
+    length + remainder
+}
+
+/// Merges the runs on top of the timsort run stack until the stack invariants hold again,
+/// which keeps merges balanced. When forced, merges every run into one.
+///
+/// # Arguments
+/// * `array` - The array being sorted.
+/// * `runs` - Stack of pending runs.
+/// * `buffer` - Scratch buffer used by the merges.
+/// * `is_smaller` - Function which specifies if the current element is smaller than the other.
+/// * `force` - Whether every run should be merged.
+/// * `events` - Receives the sort's events.
+fn tim_sort_collapse<T: Clone, F: Fn(&T, &T) -> bool, E: FnMut(SortEvent)>(array: &mut [T], runs: &mut Vec<Range<usize>>, buffer: &mut Vec<T>, is_smaller: &F, force: bool, events: &mut Events<E>) {
+    while runs.len() > 1 {
+        let n = runs.len();
+        let broken_invariant = (n >= 3 && runs[n - 3].len() <= runs[n - 2].len() + runs[n - 1].len())
+            || (n >= 4 && runs[n - 4].len() <= runs[n - 3].len() + runs[n - 2].len());
+
+        let i = if broken_invariant && runs[n - 3].len() < runs[n - 1].len() {
+            n - 3
+        } else if broken_invariant || force || runs[n - 2].len() <= runs[n - 1].len() {
+            n - 2
+        } else {
+            break;
+        };
+
+        let (first, second) = (runs[i].clone(), runs.remove(i + 1));
+        gallop_merge(&mut array[first.start..second.end], first.len(), buffer, is_smaller, &mut events.at(first.start));
+        runs[i] = first.start..second.end;
+    }
+}
+
+/// Merges the 2 sorted runs `array[..mid]` and `array[mid..]` like `merge_runs`, but switches to
+/// copying whole blocks found by exponential search whenever one run keeps winning.
+///
+/// # Arguments
+/// * `array` - The array containing both runs.
+/// * `mid` - Position where the second run starts.
+/// * `buffer` - Scratch buffer where the first run is copied to. Reused between merges.
+/// * `is_smaller` - Function which specifies if the current element is smaller than the other.
+/// * `events` - Receives the sort's events.
+fn gallop_merge<T: Clone, F: Fn(&T, &T) -> bool, E: FnMut(SortEvent)>(array: &mut [T], mid: usize, buffer: &mut Vec<T>, is_smaller: &F, events: &mut Events<E>) {
+    // Elements at the start of the first run which are not greater than the second run's first element are already in place.
+    let skip = gallop(mid, |i| !events.is_smaller(array, mid, i, is_smaller));
+    let array = &mut array[skip..];
+    let events = &mut events.at(skip);
+    let mid = mid - skip;
+
+    if mid == 0 {
+        return;
+    }
+
+    // Same for elements at the end of the second run which are not smaller than the first run's last element.
+    let keep = gallop(array.len() - mid, |i| events.is_smaller(array, mid + i, mid - 1, is_smaller));
+    let array = &mut array[..mid + keep];
+
+    buffer.clear();
+    buffer.extend_from_slice(&array[..mid]);
+    let (mut left, mut right, mut position) = (0, mid, 0);
+    let (mut left_wins, mut right_wins) = (0, 0);
+
+    while left < buffer.len() && right < array.len() {
+        events.compare(right, left);
+
+        if is_smaller(&array[right], &buffer[left]) {
+            array[position] = array[right].clone();
+            right += 1;
+            right_wins += 1;
+            left_wins = 0;
+        } else {
+            array[position] = buffer[left].clone();
+            left += 1;
+            left_wins += 1;
+            right_wins = 0;
+        }
+
+        events.write(position);
+        position += 1;
+
+        if left >= buffer.len() || right >= array.len() {
+            break;
+        }
+
+        if left_wins >= MIN_GALLOP {
+            let count = gallop(buffer.len() - left, |i| {
+                events.compare(right, left + i);
+                !is_smaller(&array[right], &buffer[left + i])
+            });
+
+            for i in 0..count {
+                array[position + i] = buffer[left + i].clone();
+                events.write(position + i);
+            }
+
+            left += count;
+            position += count;
+            left_wins = 0;
+        } else if right_wins >= MIN_GALLOP {
+            let count = gallop(array.len() - right, |i| {
+                events.compare(right + i, left);
+                is_smaller(&array[right + i], &buffer[left])
+            });
+
+            for i in 0..count {
+                array[position + i] = array[right + i].clone();
+                events.write(position + i);
+            }
+
+            right += count;
+            position += count;
+            right_wins = 0;
+        }
+    }
+
+    for item in buffer.drain(left..) {
+        array[position] = item;
+        events.write(position);
+        position += 1;
+    }
+}
+
+/// Counts how many positions at the start of a sorted run satisfy a predicate, using exponential search.
+/// The predicate must hold for a prefix of the run and fail for the rest.
+///
+/// # Arguments
+/// * `length` - Length of the sorted run.
+/// * `predicate` - The predicate, which receives positions in the run.
+fn gallop<P: FnMut(usize) -> bool>(length: usize, mut predicate: P) -> usize {
+    let mut end = 1;
+
+    while end <= length && predicate(end - 1) {
+        end *= 2;
+    }
+
+    let mut start = end / 2;
+    let mut end = usize::min(end, length + 1) - 1;
+
+    while start < end {
+        let mid = start + (end - start) / 2;
+
+        if predicate(mid) {
+            start = mid + 1;
+        } else {
+            end = mid;
+        }
+    }
+
+    start
+}
+
+/// Merges the 2 sorted runs `array[..mid]` and `array[mid..]` into a single sorted run.
+/// Equal elements keep their relative order, so the merge is stable.
+///
+/// # Arguments
+/// * `array` - The array containing both runs.
+/// * `mid` - Position where the second run starts.
+/// * `buffer` - Scratch buffer where the first run is copied to. Reused between merges.
+/// * `is_smaller` - Function which specifies if the current element is smaller than the other.
+/// * `events` - Receives the merge's events.
+fn merge_runs<T: Clone, F: Fn(&T, &T) -> bool, E: FnMut(SortEvent)>(array: &mut [T], mid: usize, buffer: &mut Vec<T>, is_smaller: &F, events: &mut Events<E>) {
+    // The runs are already in order.
+    if !events.is_smaller(array, mid, mid - 1, is_smaller) {
+        return;
+    }
+
+    buffer.clear();
+    buffer.extend_from_slice(&array[..mid]);
+    let (mut left, mut right, mut position) = (0, mid, 0);
+
+    while left < buffer.len() && right < array.len() {
+        events.compare(right, left);
+
+        if is_smaller(&array[right], &buffer[left]) {
+            array[position] = array[right].clone();
+            right += 1;
+        } else {
+            array[position] = buffer[left].clone();
+            left += 1;
+        }
+
+        events.write(position);
+        position += 1;
+    }
+
+    for item in buffer.drain(left..) {
+        array[position] = item;
+        events.write(position);
+        position += 1;
+    }
+}
+
+/// Moves the element at the start position down a max heap until the heap property holds again.
+///
+/// # Arguments
+/// * `heap` - The array containing the heap.
+/// * `start` - Position of the element to move down.
+/// * `end` - Position where the heap ends (exclusive).
+/// * `is_smaller` - Function which specifies if the current element is smaller than the other.
+/// * `events` - Receives the sort's events.
+fn sift_down<T, F: Fn(&T, &T) -> bool, E: FnMut(SortEvent)>(heap: &mut [T], start: usize, end: usize, is_smaller: &F, events: &mut Events<E>) {
+    let mut root = start;
+
+    loop {
+        let mut child = 2 * root + 1;
+
+        if child >= end {
+            break;
+        }
+
+        if child + 1 < end && events.is_smaller(heap, child, child + 1, is_smaller) {
+            child += 1;
+        }
+
+        if events.is_smaller(heap, root, child, is_smaller) {
+            events.swap(heap, root, child);
+            root = child;
+        } else {
+            break;
+        }
+    }
+}
+
+/// Sorts an array using a stable counting sort on the keys computed by the given function.
+/// Allocates a counter for every key between 0 and the biggest key.
+///
+/// # Arguments
+/// * `array` - The array to sort.
+/// * `key` - Function which computes an element's key.
+pub fn counting_sort_by_key<T: Clone, F: Fn(&T) -> usize>(array: &mut [T], key: F) {
+    let max = match array.iter().map(&key).max() {
+        Some(max) => max,
+        None => return
+    };
+
+    let mut positions = vec![0_usize; max + 1];
+
+    for item in array.iter() {
+        positions[key(item)] += 1;
+    }
+
+    let mut total = 0;
+
+    for position in positions.iter_mut() {
+        let count = *position;
+        *position = total;
+        total += count;
+    }
+
+    let buffer = array.to_vec();
+
+    for item in buffer.into_iter() {
+        let k = key(&item);
+        array[positions[k]] = item;
+        positions[k] += 1;
+    }
+}
+
+/// Sorts an array of floating point numbers using bucket sort. Runs in O(n) on average when the values are uniformly
+/// distributed. NaN values are moved to the end of the array.
+///
+/// # Arguments
+/// * `array` - The array to sort.
+pub fn bucket_sort(array: &mut [f64]) {
+    if array.is_empty() {
+        return;
+    }
+
+    let finite = array.iter().filter(|value| !value.is_nan());
+    let (min, max) = finite.fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), &value| (min.min(value), max.max(value)));
+    let buckets = array.len();
+    let range = max - min;
+
+    bucket_sort_by(array, buckets, &|value: &f64| {
+        if value.is_nan() {
+            buckets - 1
+        } else if range > 0.0 && range.is_finite() {
+            (((value - min) / range) * (buckets - 1) as f64) as usize
+        } else {
+            0
+        }
+    }, &|smaller: &f64, greater: &f64| smaller < greater || (!smaller.is_nan() && greater.is_nan()));
+}
+
+/// Sorts an array using bucket sort. Elements are distributed into buckets by the given function and every bucket
+/// is sorted with insertion sort. The bucket function must be monotonic: smaller elements may not go to later buckets.
+///
+/// # Arguments
+/// * `array` - The array to sort.
+/// * `buckets` - Number of buckets.
+/// * `bucket` - Function which computes the bucket of an element, from 0 to `buckets - 1`.
+/// * `is_smaller` - Function which specifies if the current element is smaller than the other.
+///
+/// # Panics
+/// If there are no buckets.
+pub fn bucket_sort_by<T: Clone, B: Fn(&T) -> usize, F: Fn(&T, &T) -> bool>(array: &mut [T], buckets: usize, bucket: &B, is_smaller: &F) {
+    assert!(buckets > 0, "Bucket sort needs at least 1 bucket");
+    let mut bucket_list: Vec<Vec<T>> = vec![Vec::new(); buckets];
+
+    for item in array.iter() {
+        bucket_list[usize::min(bucket(item), buckets - 1)].push(item.clone());
+    }
+
+    let mut position = 0;
+
+    for mut items in bucket_list.into_iter() {
+        insertion_sort_by(&mut items, is_smaller);
+
+        for item in items.into_iter() {
+            array[position] = item;
+            position += 1;
+        }
+    }
+}
+
+/// Sorts an array using a least significant digit radix sort, one byte of the key at a time.
+///
+/// # Arguments
+/// * `array` - The array to sort.
+/// * `key` - Function which computes an element's key.
+pub fn radix_sort_by_key<T: Clone, F: Fn(&T) -> u64>(array: &mut [T], key: F) {
+    let mut buffer = array.to_vec();
+
+    for byte in 0..8 {
+        let shift = byte * 8;
+        let digit = |item: &T| ((key(item) >> shift) & 0xff) as usize;
+        let mut positions = [0_usize; 256];
+
+        for item in array.iter() {
+            positions[digit(item)] += 1;
+        }
+
+        // Every key has the same digit so this pass would not move anything.
+        if positions.contains(&array.len()) {
+            continue;
+        }
+
+        let mut total = 0;
+
+        for position in positions.iter_mut() {
+            let count = *position;
+            *position = total;
+            total += count;
+        }
+
+        for item in array.iter() {
+            let d = digit(item);
+            buffer[positions[d]] = item.clone();
+            positions[d] += 1;
+        }
+
+        array.clone_from_slice(&buffer);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use std::cmp::Reverse;
+    use proptest::{prop_assert, prop_assert_eq, proptest};
+    use proptest::arbitrary::any;
+    use proptest::collection::vec;
+
+    /// Lengths of the arrays which every sort is tested with.
+    const LENGTHS: [usize; 8] = [0, 1, 2, 3, 5, 17, 100, 1000];
+
+    /// Generates an array of random numbers, which is always the same for the same seed.
+    ///
+    /// # Arguments
+    /// * `length` - The array's length.
+    /// * `seed` - Seed for the RNG.
+    fn random_array(length: usize, seed: u64) -> Vec<i32> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        (0..length).map(|_| rng.gen()).collect()
+    }
+
+    /// Sorts a copy of an array with the standard library's sort.
+    ///
+    /// # Arguments
+    /// * `array` - The array.
+    fn sorted<T: Ord + Clone>(array: &[T]) -> Vec<T> {
+        let mut sorted = array.to_vec();
+        sorted.sort();
+        sorted
+    }
+
+    #[test]
+    fn insertion_sort_sorts() {
+        for length in LENGTHS {
+            let mut array = random_array(length, 1);
+            let expected = sorted(&array);
+            array.insertion_sort();
+
+            assert_eq!(array, expected);
+        }
+    }
+
+    #[test]
+    fn heap_sort_sorts() {
+        for length in LENGTHS {
+            let mut array = random_array(length, 2);
+            let expected = sorted(&array);
+            array.heap_sort();
+
+            assert_eq!(array, expected);
+        }
+    }
+
+    #[test]
+    fn heap_sort_sorts_repeated_elements() {
+        let mut array: Vec<i32> = random_array(500, 3).iter().map(|item| item % 5).collect();
+        let expected = sorted(&array);
+        array.heap_sort();
+
+        assert_eq!(array, expected);
+    }
+
+    #[test]
+    fn counting_sort_sorts() {
+        for length in LENGTHS {
+            let mut array: Vec<i32> = random_array(length, 4).iter().map(|item| item % 500).collect();
+            let expected = sorted(&array);
+            array.counting_sort();
+
+            assert_eq!(array, expected);
+        }
+    }
+
+    #[test]
+    fn radix_sort_sorts() {
+        for length in LENGTHS {
+            let mut array = random_array(length, 5);
+            let expected = sorted(&array);
+            array.radix_sort();
+
+            assert_eq!(array, expected);
+        }
+    }
+
+    #[test]
+    fn radix_sort_sorts_every_integer_type() {
+        let array = random_array(1000, 6);
+
+        let mut bytes: Vec<u8> = array.iter().map(|&item| item as u8).collect();
+        let expected = sorted(&bytes);
+        bytes.radix_sort();
+        assert_eq!(bytes, expected);
+
+        let mut signed: Vec<i64> = array.iter().map(|&item| item as i64 * 3).collect();
+        let expected = sorted(&signed);
+        signed.radix_sort();
+        assert_eq!(signed, expected);
+    }
+
+    #[test]
+    fn counting_sort_is_stable() {
+        let mut array: Vec<(usize, usize)> = random_array(1000, 7).iter().map(|&item| item as usize % 4).zip(0..).collect();
+        let mut expected = array.clone();
+        expected.sort_by_key(|&(key, _)| key);
+        counting_sort_by_key(&mut array, |&(key, _)| key);
+
+        assert_eq!(array, expected);
+    }
+
+    #[test]
+    fn intro_sort_sorts() {
+        for length in LENGTHS.into_iter().chain([5000]) {
+            let mut array = random_array(length, 8);
+            let expected = sorted(&array);
+            intro_sort(&mut array);
+
+            assert_eq!(array, expected);
+        }
+    }
+
+    #[test]
+    fn intro_sort_sorts_adversarial_inputs() {
+        let ascending: Vec<i32> = (0..5000).collect();
+
+        let mut descending = ascending.clone();
+        descending.reverse();
+        intro_sort(&mut descending);
+        assert_eq!(descending, ascending);
+
+        let mut repeated = vec![7; 5000];
+        intro_sort(&mut repeated);
+        assert_eq!(repeated, vec![7; 5000]);
+    }
+
+    /// Every algorithm which can be chosen with `sort_with`.
+    const ALGORITHMS: [SortAlgorithm; 11] = [
+        SortAlgorithm::Selection,
+        SortAlgorithm::Bubble,
+        SortAlgorithm::Insertion,
+        SortAlgorithm::Merge,
+        SortAlgorithm::Heap,
+        SortAlgorithm::Tim,
+        SortAlgorithm::Shell(GapSequence::Knuth),
+        SortAlgorithm::Shell(GapSequence::Ciura),
+        SortAlgorithm::Quick,
+        SortAlgorithm::QuickRandom(4),
+        SortAlgorithm::Intro
+    ];
+
+    #[test]
+    fn sorts_by_comparator_and_key() {
+        for length in LENGTHS {
+            let array = random_array(length, 9);
+            let mut expected = sorted(&array);
+            expected.reverse();
+
+            let mut selection = array.clone();
+            selection.selection_sort_by(&|smaller, greater| smaller > greater);
+            assert_eq!(selection, expected);
+
+            let mut bubble = array.clone();
+            bubble.bubble_sort_by(&|smaller, greater| smaller > greater);
+            assert_eq!(bubble, expected);
+
+            let mut heap = array.clone();
+            heap.heap_sort_by_key(|&item| Reverse(item));
+            assert_eq!(heap, expected);
+
+            let mut insertion = array.clone();
+            insertion.insertion_sort_by_key(|&item| -(item as i64));
+            assert_eq!(insertion, expected);
+
+            let mut quick = array.clone();
+            quicksort_by_key(&mut quick, |&item| -(item as i64));
+            assert_eq!(quick, expected);
+
+            let mut intro = array.clone();
+            intro_sort_by_key(&mut intro, |&item| Reverse(item));
+            assert_eq!(intro, expected);
+        }
+    }
+
+    #[test]
+    fn sort_with_dispatches_to_every_algorithm() {
+        for algorithm in ALGORITHMS {
+            for length in LENGTHS {
+                let array = random_array(length, 10);
+                let mut expected = sorted(&array);
+
+                let mut ascending = array.clone();
+                ascending.sort_with(algorithm, &|smaller, greater| smaller < greater);
+                assert!(is_sorted(&ascending), "{:?}", algorithm);
+                assert_eq!(ascending, expected, "{:?}", algorithm);
+
+                let mut deque: VecDeque<i32> = array.iter().copied().collect();
+                deque.rotate_left(length / 3);
+                deque.sort_with_key(algorithm, |&item| Reverse(item));
+                assert!(is_sorted_by(deque.make_contiguous(), &|smaller, greater| smaller > greater), "{:?}", algorithm);
+                expected.reverse();
+                assert!(deque.iter().eq(expected.iter()), "{:?}", algorithm);
+            }
+        }
+    }
+
+    #[test]
+    fn select_nth_finds_every_position() {
+        for length in [1, 2, 3, 5, 17, 100] {
+            let array = random_array(length, 11);
+            let repeated: Vec<i32> = array.iter().map(|item| item % 3).collect();
+
+            for array in [array, repeated] {
+                let expected = sorted(&array);
+
+                for k in 0..length {
+                    let mut selected = array.clone();
+                    assert_eq!(*selected.select_nth(k), expected[k]);
+                    assert!(selected[..k].iter().all(|item| *item <= expected[k]));
+                    assert!(selected[k + 1..].iter().all(|item| *item >= expected[k]));
+                }
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn select_nth_panics_out_of_bounds() {
+        random_array(5, 12).select_nth(5);
+    }
+
+    #[test]
+    fn merge_sort_sorts() {
+        for length in LENGTHS.into_iter().chain([1024, 1025]) {
+            let mut array = random_array(length, 13);
+            let expected = sorted(&array);
+            array.merge_sort();
+
+            assert_eq!(array, expected);
+        }
+    }
+
+    #[test]
+    fn merge_sort_is_stable() {
+        let mut array: Vec<(i32, usize)> = random_array(1000, 14).iter().map(|item| item % 4).zip(0..).collect();
+        let mut expected = array.clone();
+        expected.sort_by_key(|&(key, _)| key);
+        array.merge_sort_by_key(|&(key, _)| key);
+
+        assert_eq!(array, expected);
+    }
+
+    proptest! {
+        #[test]
+        fn merge_sort_matches_slice_sort(mut array in vec(any::<i32>(), 0..2000)) {
+            let expected = sorted(&array);
+            array.merge_sort();
+
+            prop_assert!(is_sorted(&array));
+            prop_assert_eq!(array, expected);
+        }
+
+        #[test]
+        fn merge_sort_matches_stable_slice_sort(keys in vec(0..8_u8, 0..2000)) {
+            let mut array: Vec<(u8, usize)> = keys.into_iter().zip(0..).collect();
+            let mut expected = array.clone();
+            expected.sort_by_key(|&(key, _)| key);
+            array.merge_sort_by_key(|&(key, _)| key);
+
+            prop_assert_eq!(array, expected);
+        }
+    }
+
+    #[test]
+    fn is_sorted_checks_order() {
+        assert!(is_sorted::<i32>(&[]));
+        assert!(is_sorted(&[4]));
+        assert!(is_sorted(&[1, 1, 2, 5]));
+        assert!(!is_sorted(&[1, 2, 1]));
+        assert!(is_sorted_by(&[3, 2, 2, 0], &|smaller, greater| smaller > greater));
+    }
+
+    #[test]
+    fn find_runs_splits_ascending_runs() {
+        assert!(find_runs::<i32>(&[]).is_empty());
+        assert_eq!(find_runs(&[7]), vec![0..1]);
+        assert_eq!(find_runs(&[1, 2, 2, 1, 5, 0]), vec![0..3, 3..5, 5..6]);
+        assert_eq!(find_runs_by(&[3, 2, 2, 4, 1], &|smaller, greater| smaller > greater), vec![0..3, 3..5]);
+    }
+
+    #[test]
+    fn find_runs_covers_the_array() {
+        let array = random_array(300, 15);
+        let runs = find_runs(&array);
+
+        assert_eq!(runs.first().map(|run| run.start), Some(0));
+        assert_eq!(runs.last().map(|run| run.end), Some(array.len()));
+        assert!(runs.windows(2).all(|pair| pair[0].end == pair[1].start && array[pair[1].start] < array[pair[1].start - 1]));
+        assert!(runs.iter().all(|run| is_sorted(&array[run.clone()])));
+    }
+
+    #[test]
+    fn tim_sort_sorts() {
+        for length in LENGTHS.into_iter().chain([63, 64, 65, 5000, 20000]) {
+            let mut array = random_array(length, 16);
+            let expected = sorted(&array);
+            array.tim_sort();
+
+            assert_eq!(array, expected);
+        }
+    }
+
+    #[test]
+    fn tim_sort_sorts_structured_inputs() {
+        for length in [100, 1000, 20000] {
+            // Ascending with a few swapped elements.
+            let mut nearly_sorted: Vec<i32> = (0..length as i32).collect();
+
+            for i in 0..length / 10 {
+                nearly_sorted.swap(i * 7 % length, i * 13 % length);
+            }
+
+            // Alternating ascending and descending runs.
+            let zigzag: Vec<i32> = (0..length as i32).map(|item| if item % 200 < 100 { item } else { -item }).collect();
+
+            for mut array in [nearly_sorted, zigzag] {
+                let expected = sorted(&array);
+                array.tim_sort();
+
+                assert_eq!(array, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn tim_sort_is_stable() {
+        let random: Vec<(i32, usize)> = random_array(5000, 17).iter().map(|item| item % 4).zip(0..).collect();
+        // Long runs of equal keys, which are merged by galloping.
+        let blocks: Vec<(i32, usize)> = (0..5000).map(|item| ((item / 50 % 3) as i32, item)).collect();
+
+        for mut array in [random, blocks] {
+            let mut expected = array.clone();
+            expected.sort_by_key(|&(key, _)| key);
+            array.tim_sort_by_key(|&(key, _)| key);
+
+            assert_eq!(array, expected);
+        }
+    }
+
+    #[test]
+    fn quicksort_rand_sorts() {
+        for length in LENGTHS.into_iter().chain([5000]) {
+            let mut array = random_array(length, 18);
+            let expected = sorted(&array);
+            quicksort_rand(&mut array, 3);
+
+            assert_eq!(array, expected);
+        }
+    }
+
+    #[test]
+    fn quicksort_rand_sorts_repeated_elements() {
+        // Every partition is as unbalanced as possible, so the depth guard switches to heap sort.
+        let mut array = vec![1; 5000];
+        quicksort_rand(&mut array, 3);
+
+        assert_eq!(array, vec![1; 5000]);
+    }
+
+    #[test]
+    fn quicksort_rand_is_deterministic() {
+        let mut events = [Vec::new(), Vec::new()];
+
+        for run_events in events.iter_mut() {
+            sort_observed(&mut random_array(100, 19), SortAlgorithm::QuickRandom(5), |event| run_events.push(event));
+        }
+
+        assert_eq!(events[0], events[1]);
+    }
+
+    #[test]
+    fn sorts_wrapped_deques() {
+        for length in LENGTHS {
+            let array = random_array(length, 20);
+            let expected = sorted(&array);
+            let mut deque: VecDeque<i32> = array.into_iter().collect();
+            deque.rotate_left(length / 3);
+            deque.tim_sort();
+
+            assert!(deque.iter().eq(expected.iter()));
+        }
+    }
+
+    #[test]
+    fn sort_observed_reports_valid_events() {
+        for algorithm in ALGORITHMS {
+            for length in LENGTHS {
+                let array = random_array(length, 21);
+                let expected = sorted(&array);
+                let mut sorted_array = array.clone();
+                let mut events = Vec::new();
+                sort_observed(&mut sorted_array, algorithm, |event| events.push(event));
+                assert_eq!(sorted_array, expected, "{:?}", algorithm);
+
+                if length > 1 {
+                    assert!(events.iter().any(|event| matches!(event, SortEvent::Compare(..))), "{:?}", algorithm);
+                }
+
+                // Replaying the swaps sorts the array, unless the algorithm also writes.
+                let mut replayed = array.clone();
+                let mut swaps_only = true;
+
+                for event in events.iter() {
+                    match *event {
+                        SortEvent::Compare(i, j) => assert!(i < length && j < length, "{:?}", algorithm),
+                        SortEvent::Swap(i, j) => replayed.swap(i, j),
+                        SortEvent::Write(i) => {
+                            assert!(i < length, "{:?}", algorithm);
+                            swaps_only = false;
+                        }
+                    }
+                }
+
+                if swaps_only {
+                    assert_eq!(replayed, expected, "{:?}", algorithm);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn sort_observed_reports_every_comparison() {
+        for algorithm in ALGORITHMS {
+            let comparisons = Cell::new(0);
+            let mut reported = 0;
+
+            sort_observed_by(&mut random_array(1000, 22), algorithm, &|smaller: &i32, greater: &i32| {
+                comparisons.set(comparisons.get() + 1);
+                smaller < greater
+            }, |event| if let SortEvent::Compare(..) = event {
+                reported += 1;
+            });
+
+            assert_eq!(comparisons.get(), reported, "{:?}", algorithm);
+        }
+    }
+
+    #[test]
+    fn partial_sort_finds_smallest_elements() {
+        for length in LENGTHS {
+            let array = random_array(length, 23);
+            let expected = sorted(&array);
+
+            for k in [0, 1, 2, 5, length / 2, length, length + 3] {
+                let mut partial = array.clone();
+                let smallest: Vec<i32> = partial.partial_sort(k).into_iter().copied().collect();
+
+                assert_eq!(smallest, expected[..k.min(length)]);
+                assert_eq!(sorted(&partial), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn gap_sequences() {
+        assert_eq!(GapSequence::Knuth.gaps(1), vec![1]);
+        assert_eq!(GapSequence::Knuth.gaps(100), vec![40, 13, 4, 1]);
+        assert_eq!(GapSequence::Ciura.gaps(1), vec![1]);
+        assert_eq!(GapSequence::Ciura.gaps(100), vec![57, 23, 10, 4, 1]);
+        assert_eq!(GapSequence::Ciura.gaps(1578)[..2], [1577, 701]);
+        assert_eq!(GapSequence::Ciura.gaps(100000)[..3], [90927, 40412, 17961]);
+    }
+
+    #[test]
+    fn shell_sort_sorts() {
+        for gaps in [GapSequence::Knuth, GapSequence::Ciura] {
+            for length in LENGTHS.into_iter().chain([5000]) {
+                let mut array = random_array(length, 24);
+                let expected = sorted(&array);
+                array.shell_sort(gaps);
+
+                assert_eq!(array, expected, "{:?}", gaps);
+            }
+        }
+    }
+
+    #[test]
+    fn bucket_sort_sorts() {
+        for length in LENGTHS.into_iter().chain([5000]) {
+            let mut rng = StdRng::seed_from_u64(25);
+            let mut array: Vec<f64> = (0..length).map(|_| rng.gen::<f64>() * 100.0 - 50.0).collect();
+            let mut expected = array.clone();
+            expected.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            bucket_sort(&mut array);
+
+            assert_eq!(array, expected);
+
+            let mut equal = vec![3.0; length];
+            bucket_sort(&mut equal);
+            assert_eq!(equal, vec![3.0; length]);
+        }
+    }
+
+    #[test]
+    fn bucket_sort_moves_nan_to_the_end() {
+        let mut array = [f64::NAN, 2.0, f64::INFINITY, -1.0, f64::NAN, 0.5];
+        bucket_sort(&mut array);
+
+        assert_eq!(array[..4], [-1.0, 0.5, 2.0, f64::INFINITY]);
+        assert!(array[4].is_nan() && array[5].is_nan());
+    }
+
+    #[test]
+    #[should_panic]
+    fn bucket_sort_by_panics_without_buckets() {
+        bucket_sort_by(&mut [2, 1], 0, &|_| 0, &|smaller, greater| smaller < greater);
+    }
+}
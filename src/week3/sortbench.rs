@@ -0,0 +1,258 @@
+use std::env;
+use std::time::Instant;
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+use super::sort::{self, GapSequence, RadixSortable, SortAlgorithm, SortEvent, Sortable};
+
+/// Sizes benchmarked when `--sizes` is not given.
+const DEFAULT_SIZES: [usize; 1] = [10000];
+
+/// Distribution of the benchmarked input data.
+#[derive(Debug, Clone, Copy)]
+enum Distribution {
+    /// Uniformly random values.
+    Uniform,
+    /// Random values with only a few (n / 10) distinct keys.
+    FewUnique,
+    /// Already sorted values.
+    Sorted,
+    /// Values sorted in descending order.
+    Reversed,
+    /// Sorted values with 1% of the elements swapped at random.
+    NearlySorted,
+    /// Ascending then descending values.
+    OrganPipe
+}
+
+impl Distribution {
+    const ALL: [Distribution; 6] = [Self::Uniform, Self::FewUnique, Self::Sorted, Self::Reversed, Self::NearlySorted, Self::OrganPipe];
+
+    /// Parses a distribution from its command line name.
+    ///
+    /// # Arguments
+    /// * `name` - Name of the distribution.
+    fn parse(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|dist| dist.name() == name)
+    }
+
+    /// Name of the distribution, as accepted by `--dist`.
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Uniform => "uniform",
+            Self::FewUnique => "few-unique",
+            Self::Sorted => "sorted",
+            Self::Reversed => "reversed",
+            Self::NearlySorted => "nearly-sorted",
+            Self::OrganPipe => "organ-pipe"
+        }
+    }
+
+    /// Generates input data following this distribution.
+    ///
+    /// # Arguments
+    /// * `size` - Number of elements.
+    /// * `rng` - Random number generator.
+    fn generate(&self, size: usize, rng: &mut StdRng) -> Vec<i32> {
+        let length = size as i32;
+
+        match self {
+            Self::Uniform => (0..size).map(|_| rng.gen()).collect(),
+            Self::FewUnique => (0..size).map(|_| rng.gen_range(0..i32::max(length / 10, 1))).collect(),
+            Self::Sorted => (0..length).collect(),
+            Self::Reversed => (0..length).rev().collect(),
+            Self::NearlySorted => {
+                let mut data: Vec<i32> = (0..length).collect();
+
+                for _ in 0..size / 200 {
+                    let (i, j) = (rng.gen_range(0..size), rng.gen_range(0..size));
+                    data.swap(i, j);
+                }
+
+                data
+            },
+            Self::OrganPipe => (0..length / 2).chain((0..length - length / 2).rev()).collect()
+        }
+    }
+}
+
+/// Widest range of values which counting sort is benchmarked on, it allocates a counter for every value in the range.
+const COUNTING_SORT_MAX_RANGE: i64 = 1 << 24;
+
+/// An algorithm which can be benchmarked.
+#[derive(Debug, Clone, Copy)]
+enum Benchmark {
+    /// A comparison sort, whose operations can be counted.
+    Comparison(SortAlgorithm),
+    /// Counting sort, which is skipped when the range of the values is too wide.
+    Counting,
+    /// LSD radix sort.
+    Radix,
+    /// Bucket sort on the values converted to floating point numbers.
+    Bucket
+}
+
+impl Benchmark {
+    /// Parses an algorithm from its command line name.
+    ///
+    /// # Arguments
+    /// * `name` - Name of the algorithm.
+    /// * `seed` - Seed used by randomized algorithms.
+    fn parse(name: &str, seed: u64) -> Option<Self> {
+        let algorithm = match name {
+            "selection" => SortAlgorithm::Selection,
+            "bubble" => SortAlgorithm::Bubble,
+            "insertion" => SortAlgorithm::Insertion,
+            "merge" => SortAlgorithm::Merge,
+            "heap" => SortAlgorithm::Heap,
+            "tim" => SortAlgorithm::Tim,
+            "shell-knuth" => SortAlgorithm::Shell(GapSequence::Knuth),
+            "shell-ciura" => SortAlgorithm::Shell(GapSequence::Ciura),
+            "quick" => SortAlgorithm::Quick,
+            "quick-random" => SortAlgorithm::QuickRandom(seed),
+            "intro" => SortAlgorithm::Intro,
+            "counting" => return Some(Self::Counting),
+            "radix" => return Some(Self::Radix),
+            "bucket" => return Some(Self::Bucket),
+            _ => return None
+        };
+
+        Some(Self::Comparison(algorithm))
+    }
+
+    /// Sorts a copy of the data without observing the sort. Returns the elapsed seconds, or `None` if the algorithm
+    /// can not sort the data.
+    ///
+    /// # Arguments
+    /// * `data` - The data to sort.
+    fn time(&self, data: &[i32]) -> Option<f64> {
+        let mut array = data.to_vec();
+
+        let seconds = match self {
+            Self::Comparison(algorithm) => timed(&mut array, |array| array.sort_with(*algorithm, &|smaller, greater| smaller < greater)),
+            Self::Counting => {
+                let range = match (data.iter().min(), data.iter().max()) {
+                    (Some(&min), Some(&max)) => max as i64 - min as i64,
+                    _ => 0
+                };
+
+                if range > COUNTING_SORT_MAX_RANGE {
+                    return None;
+                }
+
+                timed(&mut array, |array| array.counting_sort())
+            },
+            Self::Radix => timed(&mut array, |array| array.radix_sort()),
+            Self::Bucket => {
+                let mut floats: Vec<f64> = data.iter().map(|&value| value as f64).collect();
+                let seconds = timed(&mut floats, sort::bucket_sort);
+                assert!(sort::is_sorted_by(&floats, &|smaller, greater| smaller < greater), "{:?} did not sort the array.", self);
+
+                return Some(seconds);
+            }
+        };
+
+        assert!(sort::is_sorted(&array), "{:?} did not sort the array.", self);
+        Some(seconds)
+    }
+
+    /// Sorts a copy of the data observing the sort. Returns the number of comparisons, swaps and writes, or `None`
+    /// for the algorithms which do not compare elements.
+    ///
+    /// # Arguments
+    /// * `data` - The data to sort.
+    fn count(&self, data: &[i32]) -> Option<(u64, u64, u64)> {
+        let algorithm = match self {
+            Self::Comparison(algorithm) => *algorithm,
+            _ => return None
+        };
+
+        let mut array = data.to_vec();
+        let (mut comparisons, mut swaps, mut writes) = (0u64, 0u64, 0u64);
+
+        sort::sort_observed(&mut array, algorithm, |event| match event {
+            SortEvent::Compare(_, _) => comparisons += 1,
+            SortEvent::Swap(_, _) => swaps += 1,
+            SortEvent::Write(_) => writes += 1
+        });
+
+        Some((comparisons, swaps, writes))
+    }
+}
+
+/// Sorts an array with the given function. Returns the elapsed seconds.
+///
+/// # Arguments
+/// * `array` - The array to sort.
+/// * `sort` - The sorting function.
+fn timed<T, F: FnOnce(&mut [T])>(array: &mut [T], sort: F) -> f64 {
+    let start = Instant::now();
+    sort(array);
+
+    start.elapsed().as_secs_f64()
+}
+
+/// Algorithms benchmarked when `--algorithms` is not given.
+const DEFAULT_ALGORITHMS: [&str; 14] = [
+    "selection", "bubble", "insertion", "merge", "heap", "tim", "shell-knuth", "shell-ciura", "quick", "quick-random", "intro",
+    "counting", "radix", "bucket"
+];
+
+/// Benchmarks the sorting algorithms of the sort module.
+///
+/// Usage: `sortbench [--sizes 1000,10000] [--dist uniform,sorted] [--seed 42] [--algorithms merge,intro]`
+///
+/// Prints one CSV row per algorithm, distribution and size with the elapsed time of an unobserved sort. The
+/// comparisons, swaps and writes are counted on a separate run, so counting them does not slow down the timed one,
+/// and are left empty for the sorts which do not compare elements. Counting sort is skipped on data whose values are
+/// too far apart.
+pub fn main() {
+    let mut sizes = DEFAULT_SIZES.to_vec();
+    let mut distributions = Distribution::ALL.to_vec();
+    let mut seed: u64 = rand::thread_rng().gen();
+    let mut algorithm_names: Vec<String> = DEFAULT_ALGORITHMS.iter().map(|name| name.to_string()).collect();
+    let mut args = env::args().skip(1);
+
+    // Parses the command line flags.
+    while let Some(flag) = args.next() {
+        let value = args.next().unwrap_or_else(|| panic!("Missing value for {flag}."));
+        let values = value.split(',').map(str::trim);
+
+        match flag.as_str() {
+            "--sizes" => sizes = values.map(|size| size.parse().expect("Sizes must be non-negative integers.")).collect(),
+            "--dist" => distributions = values.map(|name| Distribution::parse(name).unwrap_or_else(|| panic!("Unknown distribution: {name}."))).collect(),
+            "--seed" => seed = value.parse().expect("Seed must be a non-negative integer."),
+            "--algorithms" => algorithm_names = values.map(str::to_string).collect(),
+            _ => panic!("Unknown flag: {flag}.")
+        }
+    }
+
+    let algorithms: Vec<(&str, Benchmark)> = algorithm_names.iter()
+        .map(|name| (name.as_str(), Benchmark::parse(name, seed).unwrap_or_else(|| panic!("Unknown algorithm: {name}."))))
+        .collect();
+
+    println!("algorithm,distribution,size,seed,seconds,comparisons,swaps,writes");
+
+    for &size in sizes.iter() {
+        for dist in distributions.iter() {
+            // Every algorithm sorts the same data.
+            let data = dist.generate(size, &mut StdRng::seed_from_u64(seed));
+
+            for &(name, benchmark) in algorithms.iter() {
+                let seconds = match benchmark.time(&data) {
+                    Some(seconds) => seconds,
+                    None => {
+                        eprintln!("Skipping {name} on {} data of size {size}, the values are too far apart.", dist.name());
+                        continue;
+                    }
+                };
+
+                let counts = match benchmark.count(&data) {
+                    Some((comparisons, swaps, writes)) => format!("{comparisons},{swaps},{writes}"),
+                    None => String::from(",,")
+                };
+
+                println!("{name},{},{size},{seed},{seconds},{counts}", dist.name());
+            }
+        }
+    }
+}
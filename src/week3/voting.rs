@@ -0,0 +1,9 @@
+use super::{helpers, sort};
+
+pub mod ballots;
+pub mod election;
+pub mod plurality;
+pub mod runoff;
+pub mod tideman;
+pub mod schulze;
+pub mod tui;
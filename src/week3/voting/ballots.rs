@@ -0,0 +1,607 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::{self, Debug, Formatter};
+use std::fs::File;
+use std::io;
+use std::path::Path;
+use csv::ReaderBuilder;
+use serde::{Deserialize, Serialize};
+use super::helpers::Prompter;
+
+/// A candidate registered in an election.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Candidate {
+    /// The candidate's id: its position in the order candidates were registered.
+    pub id: usize,
+    /// The candidate's name, as it was registered.
+    pub name: String
+}
+
+/// Optional information about a candidate.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CandidateMetadata {
+    /// The candidate's party.
+    pub party: Option<String>,
+    /// The district the candidate runs in.
+    pub district: Option<String>
+}
+
+/// A candidate read from a candidate file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CandidateEntry {
+    /// The candidate's name.
+    pub name: String,
+    /// The candidate's metadata.
+    pub metadata: CandidateMetadata
+}
+
+/// A row of a candidate file. Party and district may be missing or empty.
+#[derive(Deserialize)]
+struct CandidateRow {
+    /// The candidate's name.
+    name: String,
+    /// The candidate's party.
+    #[serde(default)]
+    party: Option<String>,
+    /// The district the candidate runs in.
+    #[serde(default)]
+    district: Option<String>
+}
+
+impl From<CandidateRow> for CandidateEntry {
+    fn from(row: CandidateRow) -> Self {
+        let non_empty = |value: Option<String>| value
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty());
+
+        CandidateEntry {
+            name: row.name.trim().to_string(),
+            metadata: CandidateMetadata {
+                party: non_empty(row.party),
+                district: non_empty(row.district)
+            }
+        }
+    }
+}
+
+/// The candidates registered in an election. Names are case insensitive.
+#[derive(Debug, Clone, Default)]
+pub struct Candidates {
+    /// The candidates' names, in the order they were registered.
+    names: Vec<String>,
+    /// The candidates' metadata, in the order they were registered.
+    metadata: Vec<CandidateMetadata>,
+    /// A hashmap which allows indexing by lowercase candidate name.
+    ids: HashMap<String, usize>
+}
+
+impl Candidates {
+    /// Creates an empty candidate registry.
+    pub fn new() -> Self {
+        Candidates {
+            names: Vec::new(),
+            metadata: Vec::new(),
+            ids: HashMap::new()
+        }
+    }
+
+    /// Registers a candidate. Returns the candidate's id, or `None` if a candidate with that name already exists.
+    ///
+    /// # Arguments
+    /// * `name` - The candidate's name.
+    pub fn add(&mut self, name: String) -> Option<usize> {
+        let key = name.to_lowercase();
+
+        if self.ids.contains_key(&key) {
+            return None;
+        }
+
+        let id = self.names.len();
+        self.ids.insert(key, id);
+        self.names.push(name);
+        self.metadata.push(CandidateMetadata::default());
+
+        Some(id)
+    }
+
+    /// Sets a candidate's metadata. Returns the candidate's id, or `None` if the candidate does not exist.
+    ///
+    /// # Arguments
+    /// * `name` - The candidate's name.
+    /// * `metadata` - The candidate's metadata.
+    pub fn set_metadata(&mut self, name: &str, metadata: CandidateMetadata) -> Option<usize> {
+        let id = self.id(name)?;
+        self.metadata[id] = metadata;
+
+        Some(id)
+    }
+
+    /// Gets a candidate's metadata.
+    ///
+    /// # Arguments
+    /// * `id` - The candidate's id.
+    pub fn metadata(&self, id: usize) -> &CandidateMetadata {
+        &self.metadata[id]
+    }
+
+    /// Gets a candidate's id by name.
+    ///
+    /// # Arguments
+    /// * `name` - The candidate's name.
+    pub fn id(&self, name: &str) -> Option<usize> {
+        self.ids.get(&name.to_lowercase()).copied()
+    }
+
+    /// Checks if a candidate exists.
+    ///
+    /// # Arguments
+    /// * `name` - The candidate's name.
+    pub fn contains(&self, name: &str) -> bool {
+        self.id(name).is_some()
+    }
+
+    /// Gets a candidate by id.
+    ///
+    /// # Arguments
+    /// * `id` - The candidate's id.
+    pub fn candidate(&self, id: usize) -> Candidate {
+        Candidate {
+            id,
+            name: self.names[id].clone()
+        }
+    }
+
+    /// Gets a candidate's name, as it was registered.
+    ///
+    /// # Arguments
+    /// * `id` - The candidate's id.
+    pub fn name(&self, id: usize) -> &str {
+        &self.names[id]
+    }
+
+    /// The candidates' names, in the order they were registered.
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    /// Number of candidates.
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    /// Checks if there are no candidates.
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+}
+
+/// Reasons why a ballot is not valid.
+#[derive(Clone, PartialEq, Eq)]
+pub enum BallotError {
+    /// The ballot does not rank any candidate.
+    EmptyBallot,
+    /// The ballot ranks a candidate which does not exist.
+    UnknownCandidate(String),
+    /// The ballot ranks a candidate more than once.
+    DuplicateRank(String),
+    /// The ballot does not rank the given candidates.
+    MissingRanks(Vec<String>)
+}
+
+impl Debug for BallotError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            BallotError::EmptyBallot => write!(f, "The ballot is empty"),
+            BallotError::UnknownCandidate(name) => write!(f, "The candidate \"{}\" does not exist", name),
+            BallotError::DuplicateRank(name) => write!(f, "The candidate \"{}\" was ranked more than once", name),
+            BallotError::MissingRanks(names) => write!(f, "The ballot does not rank {}", names.join(", "))
+        }
+    }
+}
+
+/// A voter's ballot. Contains the names of the candidates the voter voted for, from most to least preferred.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(transparent)]
+pub struct Ballot {
+    /// The candidates' names, from most to least preferred.
+    ranks: Vec<String>
+}
+
+impl Ballot {
+    /// Creates a ballot with the given ranking.
+    ///
+    /// # Arguments
+    /// * `ranks` - The candidates' names, from most to least preferred.
+    pub fn new(ranks: Vec<String>) -> Self {
+        Ballot {
+            ranks
+        }
+    }
+
+    /// The candidates' names, from most to least preferred.
+    pub fn ranks(&self) -> &[String] {
+        &self.ranks
+    }
+
+    /// Validates a ballot which must rank every candidate exactly once.
+    /// Returns the candidates' ids, from most to least preferred.
+    ///
+    /// # Arguments
+    /// * `candidates` - The election's candidates.
+    pub fn validate(&self, candidates: &Candidates) -> Result<Vec<usize>, BallotError> {
+        let ids = self.validate_partial(candidates)?;
+
+        if ids.len() < candidates.len() {
+            let missing = (0..candidates.len())
+                .filter(|id| !ids.contains(id))
+                .map(|id| candidates.name(id).to_string())
+                .collect();
+
+            return Err(BallotError::MissingRanks(missing));
+        }
+
+        Ok(ids)
+    }
+
+    /// Validates a ballot which ranks at least one candidate, each at most once. Candidates may be left unranked.
+    /// Returns the ranked candidates' ids, from most to least preferred.
+    ///
+    /// # Arguments
+    /// * `candidates` - The election's candidates.
+    pub fn validate_partial(&self, candidates: &Candidates) -> Result<Vec<usize>, BallotError> {
+        if self.ranks.is_empty() {
+            return Err(BallotError::EmptyBallot);
+        }
+
+        let mut ranked: HashSet<usize> = HashSet::new();
+        let mut ids: Vec<usize> = Vec::with_capacity(self.ranks.len());
+
+        for name in self.ranks.iter() {
+            let id = candidates.id(name).ok_or_else(|| BallotError::UnknownCandidate(name.clone()))?;
+
+            if !ranked.insert(id) {
+                return Err(BallotError::DuplicateRank(name.clone()));
+            }
+
+            ids.push(id);
+        }
+
+        Ok(ids)
+    }
+
+    /// Validates the ballot's first choice. Returns the first choice's id. The remaining ranks are ignored.
+    ///
+    /// # Arguments
+    /// * `candidates` - The election's candidates.
+    pub fn validate_first(&self, candidates: &Candidates) -> Result<usize, BallotError> {
+        let name = self.ranks.first().ok_or(BallotError::EmptyBallot)?;
+
+        candidates.id(name).ok_or_else(|| BallotError::UnknownCandidate(name.clone()))
+    }
+}
+
+impl From<Vec<String>> for Ballot {
+    fn from(ranks: Vec<String>) -> Self {
+        Ballot::new(ranks)
+    }
+}
+
+/// Errors which may happen while reading a ballot or candidate file.
+pub enum BallotFileError {
+    /// The file could not be opened.
+    IoError(io::Error),
+    /// The file is not a valid CSV file.
+    CsvError(csv::Error),
+    /// The file is not a valid JSON file.
+    JsonError(serde_json::Error)
+}
+
+impl Debug for BallotFileError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            BallotFileError::IoError(err) => write!(f, "Could not open file: {}", err),
+            BallotFileError::CsvError(err) => write!(f, "Malformed CSV file: {}", err),
+            BallotFileError::JsonError(err) => write!(f, "Malformed JSON file: {}", err)
+        }
+    }
+}
+
+/// Reads ballots from a file.
+/// Files with a `.json` extension must contain an array of ballots, each being an array of candidate names.
+/// Any other file is read as a CSV file without headers, with one ballot per row.
+///
+/// # Arguments
+/// * `path` - Path to the ballot file.
+pub fn read_ballots(path: &str) -> Result<Vec<Ballot>, BallotFileError> {
+    let file = File::open(path).map_err(BallotFileError::IoError)?;
+    let is_json = Path::new(path).extension().is_some_and(|extension| extension.eq_ignore_ascii_case("json"));
+
+    if is_json {
+        return serde_json::from_reader(io::BufReader::new(file)).map_err(BallotFileError::JsonError);
+    }
+
+    ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(file)
+        .records()
+        .map(|record| {
+            let record = record.map_err(BallotFileError::CsvError)?;
+
+            Ok(Ballot::new(record.iter()
+                .map(str::trim)
+                .filter(|name| !name.is_empty())
+                .map(String::from)
+                .collect()))
+        })
+        .collect()
+}
+
+/// Reads candidates and their metadata from a file.
+/// Files with a `.json` extension must contain an array of objects with a `name` and optionally a `party` and a `district`.
+/// Any other file is read as a CSV file with a `name,party,district` header, where party and district may be left empty.
+///
+/// # Arguments
+/// * `path` - Path to the candidate file.
+pub fn read_candidates(path: &str) -> Result<Vec<CandidateEntry>, BallotFileError> {
+    let file = File::open(path).map_err(BallotFileError::IoError)?;
+    let is_json = Path::new(path).extension().is_some_and(|extension| extension.eq_ignore_ascii_case("json"));
+
+    if is_json {
+        let rows: Vec<CandidateRow> = serde_json::from_reader(io::BufReader::new(file)).map_err(BallotFileError::JsonError)?;
+        return Ok(rows.into_iter().map(CandidateEntry::from).collect());
+    }
+
+    ReaderBuilder::new()
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_reader(file)
+        .deserialize::<CandidateRow>()
+        .map(|row| row.map(CandidateEntry::from).map_err(BallotFileError::CsvError))
+        .collect()
+}
+
+/// Removes the `--candidates <file>` flag from the command line args and reads the candidate file, if the flag was given.
+/// Returns the remaining args and the candidates in the file.
+///
+/// # Arguments
+/// * `args` - The command line args, without the program's name.
+pub fn take_candidates_flag(args: &[String]) -> (Vec<String>, Vec<CandidateEntry>) {
+    let (rest, path) = take_flag(args, "--candidates");
+
+    let entries = match path {
+        Some(path) => read_candidates(&path).unwrap_or_else(|err| panic!("{:?}", err)),
+        None => Vec::new()
+    };
+
+    (rest, entries)
+}
+
+/// Reads a ballot which ranks every candidate, asking for one rank at a time.
+/// Asks for the whole ballot again until it is valid.
+///
+/// # Arguments
+/// * `candidates` - The election's candidates.
+/// * `prompter` - Source of the voter's answers.
+pub fn read_ballot<P: Prompter>(candidates: &Candidates, prompter: &mut P) -> Result<Ballot, io::Error> {
+    loop {
+        let ballot = Ballot::new((0..candidates.len())
+            .map(|i| prompter.prompt(&format!("Rank {}: ", i + 1)))
+            .collect::<Result<_, _>>()?);
+
+        match ballot.validate(candidates) {
+            Ok(_) => return Ok(ballot),
+            Err(err) => println!("{:?}, please vote again", err)
+        }
+    }
+}
+
+/// Reads a ballot which ranks some of the candidates, asking for one rank at a time until the voter leaves a rank blank
+/// or every candidate is ranked. Asks for the whole ballot again until it is valid.
+///
+/// # Arguments
+/// * `candidates` - The election's candidates.
+/// * `prompter` - Source of the voter's answers.
+pub fn read_partial_ballot<P: Prompter>(candidates: &Candidates, prompter: &mut P) -> Result<Ballot, io::Error> {
+    loop {
+        let mut ranks = Vec::new();
+
+        while ranks.len() < candidates.len() {
+            let name = prompter.prompt(&format!("Rank {} (leave blank to finish): ", ranks.len() + 1))?;
+
+            if name.is_empty() {
+                break;
+            }
+
+            ranks.push(name);
+        }
+
+        let ballot = Ballot::new(ranks);
+
+        match ballot.validate_partial(candidates) {
+            Ok(_) => return Ok(ballot),
+            Err(err) => println!("{:?}, please vote again", err)
+        }
+    }
+}
+
+/// Reads the number of voters in an election. Asks again until the answer is a number.
+///
+/// # Arguments
+/// * `prompter` - Source of the answers.
+pub fn read_number_of_voters<P: Prompter>(prompter: &mut P) -> Result<i32, io::Error> {
+    loop {
+        match prompter.prompt("Number of voters: ")?.parse::<i32>() {
+            Ok(n) => return Ok(n),
+            _ => eprintln!("The number of voters should be and integer")
+        };
+    }
+}
+
+/// Removes the `--ballots <file>` flag from the command line args.
+/// Returns the remaining args and the ballot file's path, if the flag was given.
+///
+/// # Arguments
+/// * `args` - The command line args, without the program's name.
+pub fn take_ballots_flag(args: &[String]) -> (Vec<String>, Option<String>) {
+    take_flag(args, "--ballots")
+}
+
+/// Removes a flag and its value from the command line args.
+/// Returns the remaining args and the flag's value, if the flag was given.
+///
+/// # Arguments
+/// * `args` - The command line args, without the program's name.
+/// * `flag` - The flag, for example `--ballots`.
+pub fn take_flag(args: &[String], flag: &str) -> (Vec<String>, Option<String>) {
+    match args.iter().position(|arg| arg == flag) {
+        Some(i) => {
+            let value = args.get(i + 1).unwrap_or_else(|| panic!("Missing value after {}", flag)).clone();
+            let rest = args[..i].iter().chain(args[i + 2..].iter()).cloned().collect();
+
+            (rest, Some(value))
+        },
+        None => (args.to_vec(), None)
+    }
+}
+
+/// Removes a flag without value, such as `--json`, from the command line args.
+/// Returns the remaining args and whether the flag was given.
+///
+/// # Arguments
+/// * `args` - The command line args, without the program's name.
+/// * `flag` - The flag.
+pub fn take_switch(args: &[String], flag: &str) -> (Vec<String>, bool) {
+    let rest: Vec<String> = args.iter().filter(|arg| *arg != flag).cloned().collect();
+    let found = rest.len() != args.len();
+
+    (rest, found)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Creates a ballot ranking the given candidates.
+    ///
+    /// # Arguments
+    /// * `names` - The candidates' names, from most to least preferred.
+    fn ballot(names: &[&str]) -> Ballot {
+        Ballot::new(names.iter().map(|name| name.to_string()).collect())
+    }
+
+    /// Registers Alice, Bob and Carol.
+    fn candidates() -> Candidates {
+        let mut candidates = Candidates::new();
+
+        for name in ["Alice", "Bob", "Carol"] {
+            candidates.add(name.to_string());
+        }
+
+        candidates
+    }
+
+    #[test]
+    fn candidate_names_are_case_insensitive() {
+        let mut candidates = Candidates::new();
+
+        assert_eq!(candidates.add(String::from("Alice")), Some(0));
+        assert_eq!(candidates.add(String::from("ALICE")), None);
+        assert_eq!(candidates.id("alice"), Some(0));
+        assert_eq!(candidates.name(0), "Alice");
+    }
+
+    #[test]
+    fn validates_complete_ballots() {
+        let candidates = candidates();
+
+        assert_eq!(ballot(&["bob", "ALICE", "carol"]).validate(&candidates), Ok(vec![1, 0, 2]));
+        assert_eq!(ballot(&["bob", "bob", "carol"]).validate(&candidates), Err(BallotError::DuplicateRank(String::from("bob"))));
+        assert_eq!(ballot(&["bob", "dave"]).validate(&candidates), Err(BallotError::UnknownCandidate(String::from("dave"))));
+        assert_eq!(ballot(&["bob"]).validate(&candidates), Err(BallotError::MissingRanks(vec![String::from("Alice"), String::from("Carol")])));
+    }
+
+    #[test]
+    fn validates_first_choices() {
+        let candidates = candidates();
+
+        assert_eq!(ballot(&[]).validate_first(&candidates), Err(BallotError::EmptyBallot));
+        assert_eq!(ballot(&["carol", "zz"]).validate_first(&candidates), Ok(2));
+    }
+
+    #[test]
+    fn deserializes_ballots() {
+        let ballots: Vec<Ballot> = serde_json::from_str(r#"[["a", "b"], ["c"]]"#).unwrap();
+
+        assert_eq!(ballots, vec![ballot(&["a", "b"]), ballot(&["c"])]);
+    }
+
+    #[test]
+    fn reads_number_of_voters_from_prompter() {
+        assert_eq!(read_number_of_voters(&mut "x\n2\n".as_bytes()).unwrap(), 2);
+        assert!(read_number_of_voters(&mut "x\n".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn asks_again_for_invalid_ballots() {
+        let candidates = candidates();
+        let ballot = read_ballot(&candidates, &mut "alice\nalice\nbob\ncarol\nbob\nalice\n".as_bytes()).unwrap();
+
+        assert_eq!(ballot.ranks(), ["carol", "bob", "alice"]);
+    }
+
+    /// Writes a candidate file to the temporary directory. Returns its path.
+    ///
+    /// # Arguments
+    /// * `name` - The file's name.
+    /// * `contents` - The file's contents.
+    fn write_candidate_file(name: &str, contents: &str) -> String {
+        let path = std::env::temp_dir().join(format!("{}-{}", std::process::id(), name));
+        std::fs::write(&path, contents).unwrap();
+
+        path.to_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn reads_candidate_metadata_from_csv() {
+        let path = write_candidate_file("candidates.csv", "name,party,district\nAlice,Green,North\nBob,,South\nCharlie,Green,\n");
+        let entries = read_candidates(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(entries.iter().map(|entry| entry.name.as_str()).collect::<Vec<_>>(), ["Alice", "Bob", "Charlie"]);
+        assert_eq!(entries[0].metadata, CandidateMetadata { party: Some(String::from("Green")), district: Some(String::from("North")) });
+        assert_eq!(entries[1].metadata, CandidateMetadata { party: None, district: Some(String::from("South")) });
+        assert_eq!(entries[2].metadata.district, None);
+    }
+
+    #[test]
+    fn reads_candidate_metadata_from_json() {
+        let path = write_candidate_file("candidates.json", r#"[{"name": "Alice", "party": "Green"}, {"name": "Bob"}, {"name": "Charlie", "party": "Green", "district": "East"}]"#);
+        let entries = read_candidates(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(entries[1].metadata, CandidateMetadata::default());
+        assert_eq!(entries[2].metadata.district.as_deref(), Some("East"));
+    }
+
+    #[test]
+    fn validates_partial_ballots() {
+        let candidates = candidates();
+
+        assert_eq!(ballot(&["carol"]).validate_partial(&candidates), Ok(vec![2]));
+        assert_eq!(ballot(&[]).validate_partial(&candidates), Err(BallotError::EmptyBallot));
+        assert_eq!(ballot(&["bob", "BOB"]).validate_partial(&candidates), Err(BallotError::DuplicateRank(String::from("BOB"))));
+    }
+
+    #[test]
+    fn reads_partial_ballots_until_blank_rank() {
+        let candidates = candidates();
+
+        // The empty ballot and the ballot ranking Alice twice are asked for again.
+        let ballot = read_partial_ballot(&candidates, &mut "\nalice\nalice\n\nbob\n\n".as_bytes()).unwrap();
+        assert_eq!(ballot.ranks(), ["bob"]);
+
+        // Stops asking once every candidate is ranked.
+        let ballot = read_partial_ballot(&candidates, &mut "alice\nbob\ncarol\n".as_bytes()).unwrap();
+        assert_eq!(ballot.ranks().len(), 3);
+    }
+}
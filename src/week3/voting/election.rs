@@ -0,0 +1,447 @@
+use std::fmt::{self, Debug, Formatter};
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+use serde::Serialize;
+use super::ballots::{self, Ballot, BallotError, CandidateEntry, CandidateMetadata, Candidates};
+
+/// Errors which may happen in an election.
+#[derive(Clone)]
+pub enum ElectionError {
+    /// The given candidate does not exist.
+    CandidateNotFoundError(String),
+    /// Attempted to register an existing candidate.
+    CandidateAlreadyExistsError(String),
+    /// A ballot is not valid for the election.
+    InvalidBallotError(BallotError),
+    /// The election has no candidates.
+    NoCandidatesError,
+    /// A graph lock created a cycle.
+    LockCreatedCycleError,
+    /// No candidate beats every other. Contains the tied candidates' names.
+    TieError(Vec<String>)
+}
+
+impl Debug for ElectionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ElectionError::CandidateNotFoundError(name) => write!(f, "The candidate \"{}\" was not found", name),
+            ElectionError::CandidateAlreadyExistsError(name) => write!(f, "Can't add candidate \"{}\" because it already exists", name),
+            ElectionError::InvalidBallotError(err) => write!(f, "Invalid ballot: {:?}", err),
+            ElectionError::NoCandidatesError => write!(f, "The election has no candidates"),
+            ElectionError::LockCreatedCycleError => write!(f, "The lock created a cycle in the graph"),
+            ElectionError::TieError(names) => write!(f, "The election is tied between {}", names.join(", "))
+        }
+    }
+}
+
+/// The result of an election.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ElectionResult {
+    /// A single candidate won the election.
+    Winner(String),
+    /// The election ended in a tie between the given candidates.
+    Tie(Vec<String>)
+}
+
+impl ElectionResult {
+    /// Message announcing the result.
+    pub fn announcement(&self) -> String {
+        match self {
+            ElectionResult::Winner(name) => format!("Winner is {}", name),
+            ElectionResult::Tie(names) => format!("Tie between {}!", names.join(", "))
+        }
+    }
+}
+
+/// A candidate's votes in a round of counting.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Tally {
+    /// The candidate's name.
+    pub candidate: String,
+    /// The candidate's number of votes.
+    pub votes: u32
+}
+
+/// The votes counted in a round of an election.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RoundReport {
+    /// Every remaining candidate's votes, in the order they were registered.
+    pub tallies: Vec<Tally>,
+    /// Candidates eliminated at the end of the round.
+    pub eliminated: Vec<String>
+}
+
+/// The votes of every candidate in a party.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PartyTally {
+    /// The party's name.
+    pub party: String,
+    /// The sum of the party's candidates' votes.
+    pub votes: u32
+}
+
+/// Party of the candidates which don't have one.
+const INDEPENDENT: &str = "Independent";
+
+/// Groups the candidates' votes by party. Candidates without a party are grouped as independents.
+/// Returns the parties in the order their first candidate was registered, or nothing if no candidate has a party.
+///
+/// # Arguments
+/// * `candidates` - The election's candidates.
+/// * `votes` - Each candidate's votes, indexed by candidate id.
+pub fn party_tallies(candidates: &Candidates, votes: &[u32]) -> Vec<PartyTally> {
+    let mut tallies: Vec<PartyTally> = Vec::new();
+
+    if (0..candidates.len()).all(|id| candidates.metadata(id).party.is_none()) {
+        return tallies;
+    }
+
+    for (id, &candidate_votes) in votes.iter().enumerate() {
+        let party = candidates.metadata(id).party.as_deref().unwrap_or(INDEPENDENT);
+
+        match tallies.iter_mut().find(|tally| tally.party == party) {
+            Some(tally) => tally.votes += candidate_votes,
+            None => tallies.push(PartyTally { party: party.to_string(), votes: candidate_votes })
+        }
+    }
+
+    tallies
+}
+
+/// Counts each candidate's first choice votes.
+///
+/// # Arguments
+/// * `rankings` - Each voter's ranking, as candidate ids.
+/// * `candidates` - Number of candidates in the election.
+pub fn first_choice_votes(rankings: &[Vec<usize>], candidates: usize) -> Vec<u32> {
+    let mut votes = vec![0; candidates];
+
+    for ranking in rankings {
+        if let Some(&first) = ranking.first() {
+            votes[first] += 1;
+        }
+    }
+
+    votes
+}
+
+/// A head to head matchup between two candidates.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PairReport {
+    /// The candidate preferred by more voters.
+    pub winner: String,
+    /// The other candidate.
+    pub loser: String,
+    /// How many more voters prefer the winner over the loser.
+    pub margin: u32,
+    /// Whether the pair was locked into the election's graph.
+    pub locked: bool
+}
+
+/// A report of an election's results, which can be serialized to be consumed by other tools.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ElectionReport {
+    /// The voting system, for example `plurality`.
+    pub method: String,
+    /// The candidates' names, in the order they were registered.
+    pub candidates: Vec<String>,
+    /// Number of valid ballots.
+    pub ballots: usize,
+    /// The rounds of counting, for voting systems which count votes in rounds.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub rounds: Vec<RoundReport>,
+    /// The head to head matchups, for voting systems which compare pairs of candidates.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub pairs: Vec<PairReport>,
+    /// First choice votes grouped by party, when the candidates have parties.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub parties: Vec<PartyTally>,
+    /// The result of the election.
+    pub result: ElectionResult
+}
+
+impl ElectionReport {
+    /// Creates a report without rounds, pairs nor parties.
+    ///
+    /// # Arguments
+    /// * `method` - The voting system.
+    /// * `candidates` - The candidates' names, in the order they were registered.
+    /// * `ballots` - Number of valid ballots.
+    /// * `result` - The result of the election.
+    pub fn new(method: &str, candidates: &[String], ballots: usize, result: ElectionResult) -> Self {
+        ElectionReport {
+            method: method.to_string(),
+            candidates: candidates.to_vec(),
+            ballots,
+            rounds: Vec::new(),
+            pairs: Vec::new(),
+            parties: Vec::new(),
+            result
+        }
+    }
+
+    /// Serializes the report as pretty printed JSON.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("A report is always serializable")
+    }
+
+    /// Draws bar charts of the votes in each round and of the margin of each head to head matchup.
+    pub fn render_chart(&self) -> String {
+        let mut chart = String::new();
+
+        for (i, round) in self.rounds.iter().enumerate() {
+            if self.rounds.len() > 1 {
+                chart.push_str(&format!("Round {}\n", i + 1));
+            }
+
+            chart.push_str(&render_chart(&round.tallies));
+        }
+
+        if !self.pairs.is_empty() {
+            let rows: Vec<(String, u32, String)> = self.pairs
+                .iter()
+                .map(|pair| {
+                    let locked = if pair.locked { "" } else { ", skipped" };
+                    (format!("{} over {}", pair.winner, pair.loser), pair.margin, format!("+{}{}", pair.margin, locked))
+                })
+                .collect();
+
+            let scale = self.pairs.iter().map(|pair| pair.margin).max().unwrap_or(0);
+
+            chart.push_str("Margins\n");
+            chart.push_str(&render_bars(&rows, scale));
+        }
+
+        chart
+    }
+}
+
+/// Width, in characters, of the longest bar in a chart.
+const CHART_WIDTH: usize = 40;
+
+/// Draws one horizontal bar per row, proportional to the row's value.
+///
+/// # Arguments
+/// * `rows` - Each bar's label, value and the text shown after the bar.
+/// * `scale` - The value of a bar which is `CHART_WIDTH` characters long.
+fn render_bars(rows: &[(String, u32, String)], scale: u32) -> String {
+    let label_width = rows.iter().map(|(label, _, _)| label.chars().count()).max().unwrap_or(0);
+
+    rows.iter()
+        .map(|(label, value, text)| {
+            let length = if scale == 0 { 0 } else { *value as usize * CHART_WIDTH / scale as usize };
+
+            format!("{:<label_width$} | {:<CHART_WIDTH$} {}\n", label, "#".repeat(length), text)
+        })
+        .collect()
+}
+
+/// Draws a bar chart of the candidates' votes. Each bar is proportional to the candidate's share of the votes.
+///
+/// # Arguments
+/// * `tallies` - The candidates' votes.
+pub fn render_chart(tallies: &[Tally]) -> String {
+    let total: u32 = tallies.iter().map(|tally| tally.votes).sum();
+
+    let rows: Vec<(String, u32, String)> = tallies
+        .iter()
+        .map(|tally| {
+            let share = if total == 0 { 0.0 } else { tally.votes as f64 * 100.0 / total as f64 };
+            (tally.candidate.clone(), tally.votes, format!("{} ({:.1}%)", tally.votes, share))
+        })
+        .collect();
+
+    render_bars(&rows, total)
+}
+
+/// Strategy used to pick a single winner when an election is tied.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TieBreak {
+    /// Picks one of the tied candidates at random, with an RNG seeded with the given seed.
+    Random(u64),
+    /// Picks the tied candidate whose name comes first alphabetically, ignoring case.
+    Alphabetical,
+    /// Picks the tied candidate which was registered first.
+    EarliestRegistered,
+    /// Does not break the tie. Every tied candidate is reported.
+    ReportAll
+}
+
+impl TieBreak {
+    /// Parses a tie-breaking strategy from its command line name: `random:<seed>`, `alphabetical`, `earliest` or `all`.
+    ///
+    /// # Arguments
+    /// * `name` - The strategy's name.
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "alphabetical" => Some(TieBreak::Alphabetical),
+            "earliest" => Some(TieBreak::EarliestRegistered),
+            "all" => Some(TieBreak::ReportAll),
+            _ => name.strip_prefix("random:")?.parse().ok().map(TieBreak::Random)
+        }
+    }
+
+    /// Breaks a tie between the given candidates.
+    ///
+    /// # Arguments
+    /// * `tied` - The tied candidates' names, in the order they were registered.
+    pub fn resolve(&self, mut tied: Vec<String>) -> ElectionResult {
+        if tied.len() == 1 {
+            return ElectionResult::Winner(tied.remove(0));
+        }
+
+        let winner = match self {
+            TieBreak::Random(seed) => StdRng::seed_from_u64(*seed).gen_range(0..tied.len()),
+            TieBreak::Alphabetical => (0..tied.len()).min_by_key(|&i| tied[i].to_lowercase()).unwrap(),
+            TieBreak::EarliestRegistered => 0,
+            TieBreak::ReportAll => return ElectionResult::Tie(tied)
+        };
+
+        ElectionResult::Winner(tied.swap_remove(winner))
+    }
+}
+
+/// A voting system. Candidates are registered first, then ballots are cast and finally the result is computed.
+pub trait Election {
+    /// Error returned when a candidate or ballot is rejected.
+    type Error: Debug;
+
+    /// Adds a candidate to the election.
+    ///
+    /// # Arguments
+    /// * `name` - The candidate's name.
+    fn add_candidate(&mut self, name: String) -> Result<(), Self::Error>;
+
+    /// Sets the metadata of a registered candidate.
+    ///
+    /// # Arguments
+    /// * `name` - The candidate's name.
+    /// * `metadata` - The candidate's metadata.
+    fn set_candidate_metadata(&mut self, name: &str, metadata: CandidateMetadata) -> Result<(), Self::Error>;
+
+    /// Casts a voter's ballot. Invalid ballots are rejected without changing the election.
+    ///
+    /// # Arguments
+    /// * `ballot` - The voter's ballot.
+    fn cast_ballot(&mut self, ballot: Ballot) -> Result<(), Self::Error>;
+
+    /// Computes the result of the election with the ballots cast so far.
+    /// Ties list the tied candidates in the order they were registered.
+    fn result(&mut self) -> Result<ElectionResult, Self::Error>;
+
+    /// Computes the result of the election, breaking ties with the given strategy.
+    ///
+    /// # Arguments
+    /// * `tie_break` - The tie-breaking strategy.
+    fn result_with_tie_break(&mut self, tie_break: TieBreak) -> Result<ElectionResult, Self::Error> {
+        Ok(match self.result()? {
+            ElectionResult::Tie(tied) => tie_break.resolve(tied),
+            result => result
+        })
+    }
+
+    /// Builds a report of the election's results, breaking ties with the given strategy.
+    ///
+    /// # Arguments
+    /// * `tie_break` - The tie-breaking strategy.
+    fn report(&mut self, tie_break: TieBreak) -> Result<ElectionReport, Self::Error>;
+
+    /// Adds every candidate, in order.
+    ///
+    /// # Arguments
+    /// * `names` - The candidates' names.
+    fn add_candidates<I: IntoIterator<Item = String>>(&mut self, names: I) -> Result<(), Self::Error> {
+        names.into_iter().try_for_each(|name| self.add_candidate(name))
+    }
+
+    /// Adds every candidate read from a candidate file, in order, with its metadata.
+    ///
+    /// # Arguments
+    /// * `entries` - The candidates.
+    fn add_candidate_entries<I: IntoIterator<Item = CandidateEntry>>(&mut self, entries: I) -> Result<(), Self::Error> {
+        entries.into_iter().try_for_each(|entry| {
+            self.add_candidate(entry.name.clone())?;
+            self.set_candidate_metadata(&entry.name, entry.metadata)
+        })
+    }
+}
+
+/// Removes the `--tie-break <strategy>` flag from the command line args.
+/// Returns the remaining args and the strategy, which reports every tied candidate if the flag was not given.
+///
+/// # Arguments
+/// * `args` - The command line args, without the program's name.
+pub fn take_tie_break_flag(args: &[String]) -> (Vec<String>, TieBreak) {
+    let (rest, name) = ballots::take_flag(args, "--tie-break");
+
+    let tie_break = match name {
+        Some(name) => TieBreak::parse(&name).unwrap_or_else(|| panic!("Unknown tie break \"{}\", expected random:<seed>, alphabetical, earliest or all", name)),
+        None => TieBreak::ReportAll
+    };
+
+    (rest, tie_break)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn breaks_ties() {
+        let tied = vec![String::from("zed"), String::from("Amy"), String::from("bob")];
+
+        assert_eq!(TieBreak::Alphabetical.resolve(tied.clone()), ElectionResult::Winner(String::from("Amy")));
+        assert_eq!(TieBreak::EarliestRegistered.resolve(tied.clone()), ElectionResult::Winner(String::from("zed")));
+        assert_eq!(TieBreak::ReportAll.resolve(tied.clone()), ElectionResult::Tie(tied.clone()));
+        assert_eq!(TieBreak::ReportAll.resolve(vec![String::from("Amy")]), ElectionResult::Winner(String::from("Amy")));
+    }
+
+    #[test]
+    fn random_tie_break_is_deterministic() {
+        let tied = vec![String::from("zed"), String::from("Amy"), String::from("bob")];
+        let result = TieBreak::Random(5).resolve(tied.clone());
+
+        assert!(matches!(&result, ElectionResult::Winner(name) if tied.contains(name)));
+
+        for _ in 0..5 {
+            assert_eq!(TieBreak::Random(5).resolve(tied.clone()), result);
+        }
+    }
+
+    #[test]
+    fn parses_tie_breaks() {
+        assert_eq!(TieBreak::parse("random:42"), Some(TieBreak::Random(42)));
+        assert_eq!(TieBreak::parse("alphabetical"), Some(TieBreak::Alphabetical));
+        assert_eq!(TieBreak::parse("earliest"), Some(TieBreak::EarliestRegistered));
+        assert_eq!(TieBreak::parse("all"), Some(TieBreak::ReportAll));
+        assert_eq!(TieBreak::parse("random:x"), None);
+        assert_eq!(TieBreak::parse("bogus"), None);
+    }
+
+    #[test]
+    fn groups_votes_by_party() {
+        let mut candidates = Candidates::new();
+
+        for (name, party) in [("Alice", Some("Green")), ("Bob", None), ("Charlie", Some("Green")), ("Dana", Some("Blue"))] {
+            candidates.add(name.to_string());
+            candidates.set_metadata(name, CandidateMetadata { party: party.map(String::from), district: None });
+        }
+
+        let tallies = party_tallies(&candidates, &[3, 1, 2, 4]);
+
+        assert_eq!(tallies, vec![
+            PartyTally { party: String::from("Green"), votes: 5 },
+            PartyTally { party: String::from(INDEPENDENT), votes: 1 },
+            PartyTally { party: String::from("Blue"), votes: 4 }
+        ]);
+    }
+
+    #[test]
+    fn skips_party_tallies_without_parties() {
+        let mut candidates = Candidates::new();
+        candidates.add(String::from("Alice"));
+
+        assert!(party_tallies(&candidates, &[3]).is_empty());
+    }
+}
@@ -0,0 +1,283 @@
+use std::env;
+use std::io;
+
+use super::ballots;
+use super::helpers::{Prompter, StdinPrompter};
+use super::election::{self, Election, ElectionError, ElectionReport, ElectionResult, RoundReport, Tally, TieBreak};
+use ballots::{Ballot, CandidateMetadata, Candidates};
+
+/// Table which holds each candidate's number of votes.
+pub struct CandidateTable {
+    /// Each candidate's number of votes, indexed by candidate id.
+    votes: Vec<u32>,
+    /// The election's candidates.
+    candidates: Candidates
+}
+
+impl CandidateTable {
+    /// Creates a new candidate table containing the given candidates.
+    ///
+    /// # Arguments
+    /// * `candidates` - The election's candidates.
+    pub fn new(candidates: &[String]) -> CandidateTable {
+        let mut registry = Candidates::new();
+
+        for candidate in candidates {
+            registry.add(candidate.clone());
+        }
+
+        CandidateTable {
+            votes: vec![0; registry.len()],
+            candidates: registry
+        }
+    }
+
+    /// Votes for the given candidate.
+    ///
+    /// # Arguments
+    /// * `name` - The candidate's name. Case insensitive.
+    pub fn vote(&mut self, name: &str) -> Result<(), ElectionError> {
+        match self.candidates.id(name) {
+            Some(id) => {
+                self.votes[id] += 1;
+                Ok(())
+            },
+            None => Err(ElectionError::CandidateNotFoundError(name.to_string()))
+        }
+    }
+
+    /// Number of votes of the given candidate.
+    ///
+    /// # Arguments
+    /// * `name` - The candidate's name. Case insensitive.
+    pub fn votes(&self, name: &str) -> Option<u32> {
+        self.candidates.id(name).map(|id| self.votes[id])
+    }
+
+    /// Finds the winners of the election: every candidate tied for the most votes, in the order they were registered.
+    /// Returns a tuple with each winner's name and number of votes. Empty if there are no candidates.
+    pub fn winner(&self) -> Vec<(&str, u32)> {
+        let max_votes = match self.votes.iter().max() {
+            Some(&votes) => votes,
+            None => return Vec::new()
+        };
+
+        (0..self.candidates.len())
+            .filter(|&id| self.votes[id] == max_votes)
+            .map(|id| (self.candidates.name(id), max_votes))
+            .collect()
+    }
+}
+
+impl Election for CandidateTable {
+    type Error = ElectionError;
+
+    fn add_candidate(&mut self, name: String) -> Result<(), ElectionError> {
+        match self.candidates.add(name.clone()) {
+            Some(_) => {
+                self.votes.push(0);
+                Ok(())
+            },
+            None => Err(ElectionError::CandidateAlreadyExistsError(name))
+        }
+    }
+
+    fn set_candidate_metadata(&mut self, name: &str, metadata: CandidateMetadata) -> Result<(), ElectionError> {
+        match self.candidates.set_metadata(name, metadata) {
+            Some(_) => Ok(()),
+            None => Err(ElectionError::CandidateNotFoundError(name.to_string()))
+        }
+    }
+
+    /// Only the ballot's first choice is counted.
+    fn cast_ballot(&mut self, ballot: Ballot) -> Result<(), ElectionError> {
+        let id = ballot.validate_first(&self.candidates).map_err(ElectionError::InvalidBallotError)?;
+
+        self.votes[id] += 1;
+        Ok(())
+    }
+
+    fn result(&mut self) -> Result<ElectionResult, ElectionError> {
+        let mut winners: Vec<String> = self.winner().into_iter().map(|(name, _)| name.to_string()).collect();
+
+        if winners.is_empty() {
+            Err(ElectionError::NoCandidatesError)
+        } else if winners.len() == 1 {
+            Ok(ElectionResult::Winner(winners.remove(0)))
+        } else {
+            Ok(ElectionResult::Tie(winners))
+        }
+    }
+
+    fn report(&mut self, tie_break: TieBreak) -> Result<ElectionReport, ElectionError> {
+        let result = self.result_with_tie_break(tie_break)?;
+        let ballots = self.votes.iter().sum::<u32>() as usize;
+        let mut report = ElectionReport::new("plurality", self.candidates.names(), ballots, result);
+
+        report.rounds.push(RoundReport {
+            tallies: self.candidates
+                .names()
+                .iter()
+                .zip(self.votes.iter())
+                .map(|(name, &votes)| Tally { candidate: name.clone(), votes })
+                .collect(),
+            eliminated: Vec::new()
+        });
+
+        report.parties = election::party_tallies(&self.candidates, &self.votes);
+
+        Ok(report)
+    }
+}
+
+pub fn main() {
+    // Reads candidates from command line args.
+    let args: Vec<String> = env::args().skip(1).collect();
+    let (args, tie_break) = election::take_tie_break_flag(&args);
+    let (args, json) = ballots::take_switch(&args, "--json");
+    let (args, chart) = ballots::take_switch(&args, "--chart");
+    let (args, entries) = ballots::take_candidates_flag(&args);
+    let (candidates, ballot_file) = ballots::take_ballots_flag(&args);
+
+    if candidates.len() + entries.len() < 2 {
+        panic!("Usage:\n ./plurality [--candidates <file>] [--ballots <file>] [--tie-break <strategy>] [--json] [--chart] <candidate1> <candidate2> <...> <candidateN>\nMinimun number of candidates is 2");
+    }
+
+    // Creates candidate table.
+    let mut table: CandidateTable = CandidateTable::new(&candidates);
+
+    if let Err(err) = table.add_candidate_entries(entries) {
+        panic!("{:?}", err);
+    }
+
+    match ballot_file {
+        // Counts the votes in the ballot file.
+        Some(path) => vote_ballots(&mut table, ballots::read_ballots(&path).unwrap()),
+        None => {
+            // Reads number of voters.
+            let number_of_voters = ballots::read_number_of_voters(&mut StdinPrompter).unwrap();
+
+            // Get votes for each voter.
+            vote(&mut table, number_of_voters, &mut StdinPrompter).unwrap();
+        }
+    }
+
+    if json {
+        println!("{}", table.report(tie_break).unwrap().to_json());
+        return;
+    }
+
+    // Prints every winner, unless the tie was broken.
+    let winners = match table.result_with_tie_break(tie_break).unwrap() {
+        ElectionResult::Winner(name) => vec![name],
+        ElectionResult::Tie(names) => names
+    };
+
+    println!("\n{}", if winners.len() == 1 { "Winner:" } else { "Winners:" });
+
+    for name in winners {
+        println!("{} ({} votes)", name, table.votes(&name).unwrap());
+    }
+
+    if chart {
+        print!("\n{}", table.report(tie_break).unwrap().render_chart());
+    }
+}
+
+/// Votes the given number of times.
+///
+/// # Arguments
+/// * `election` - The election. Votes for candidates which are not in the election are not allowed.
+/// * `number_of_voters` - Number of voters in the election.
+/// * `prompter` - Source of the voters' answers.
+pub fn vote<E: Election, P: Prompter>(election: &mut E, number_of_voters: i32, prompter: &mut P) -> Result<(), io::Error> {
+    for _ in 0..number_of_voters {
+        let candidate = prompter.prompt("Vote: ")?;
+
+        if election.cast_ballot(Ballot::new(vec![candidate])).is_err() {
+            eprintln!("Invalid Vote");
+        }
+    }
+
+    Ok(())
+}
+
+/// Votes once for each ballot. Only the first choice in each ballot is counted.
+///
+/// # Arguments
+/// * `election` - The election. Votes for candidates which are not in the election are not allowed.
+/// * `ballots` - The voters' ballots.
+fn vote_ballots<E: Election>(election: &mut E, ballots: Vec<Ballot>) {
+    for (i, ballot) in ballots.into_iter().enumerate() {
+        if election.cast_ballot(ballot).is_err() {
+            eprintln!("Invalid Vote in ballot {}", i + 1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Creates a ballot ranking the given candidates.
+    ///
+    /// # Arguments
+    /// * `names` - The candidates' names, from most to least preferred.
+    fn ballot(names: &[&str]) -> Ballot {
+        Ballot::new(names.iter().map(|name| name.to_string()).collect())
+    }
+
+    #[test]
+    fn counts_first_choices() {
+        let mut table = CandidateTable::new(&[]);
+        assert!(table.result().is_err());
+
+        table.add_candidates(["Alice", "Bob", "Charlie"].map(String::from)).unwrap();
+        assert!(table.add_candidate(String::from("Alice")).is_err());
+
+        table.cast_ballot(ballot(&["alice"])).unwrap();
+        table.cast_ballot(ballot(&["bob", "alice"])).unwrap();
+        table.cast_ballot(ballot(&["Bob"])).unwrap();
+
+        assert_eq!(table.votes("BOB"), Some(2));
+        assert_eq!(table.result().unwrap(), ElectionResult::Winner(String::from("Bob")));
+    }
+
+    #[test]
+    fn rejects_invalid_ballots() {
+        let mut table = CandidateTable::new(&[String::from("Alice")]);
+
+        assert!(table.cast_ballot(ballot(&["zed"])).is_err());
+        assert!(table.cast_ballot(ballot(&[])).is_err());
+        assert_eq!(table.votes("Alice"), Some(0));
+    }
+
+    #[test]
+    fn breaks_ties_with_strategy() {
+        let mut table = CandidateTable::new(&[String::from("z"), String::from("a")]);
+        table.cast_ballot(ballot(&["z"])).unwrap();
+        table.cast_ballot(ballot(&["a"])).unwrap();
+
+        assert_eq!(table.result_with_tie_break(TieBreak::Alphabetical).unwrap(), ElectionResult::Winner(String::from("a")));
+        assert_eq!(table.result_with_tie_break(TieBreak::EarliestRegistered).unwrap(), ElectionResult::Winner(String::from("z")));
+    }
+
+    #[test]
+    fn votes_from_prompter() {
+        let mut table = CandidateTable::new(&[String::from("a"), String::from("b")]);
+        vote(&mut table, 3, &mut "a\nb\nb\n".as_bytes()).unwrap();
+
+        assert_eq!(table.result().unwrap(), ElectionResult::Winner(String::from("b")));
+        assert!(vote(&mut table, 2, &mut "a\n".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn reports_every_tied_winner() {
+        let mut table = CandidateTable::new(&["a", "b", "c"].map(String::from));
+        table.cast_ballot(ballot(&["a"])).unwrap();
+        table.cast_ballot(ballot(&["b", "a"])).unwrap();
+
+        assert_eq!(table.winner(), vec![("a", 1), ("b", 1)]);
+        assert_eq!(table.result().unwrap(), ElectionResult::Tie(vec![String::from("a"), String::from("b")]));
+    }
+}
@@ -0,0 +1,492 @@
+use std::env;
+use std::fmt::{self, Display, Formatter};
+use std::io;
+use serde::Serialize;
+use super::ballots;
+use super::tui;
+use super::helpers::{Prompter, StdinPrompter};
+use super::election::{self, Election, ElectionError, ElectionReport, ElectionResult, RoundReport, Tally, TieBreak};
+use ballots::{Ballot, CandidateMetadata, Candidates};
+use std::collections::HashMap;
+
+/// The result of a round of a runoff election.
+enum RunoffTabulationResult {
+    /// The candidate with the given id won the election.
+    Win(usize),
+    /// The candidates tied for the fewest votes, given by id, are eliminated. The election continues with another round.
+    Elimination(Vec<usize>),
+    /// The remaining candidates are tied.
+    Tie
+}
+
+/// Votes moved from an eliminated candidate to the next remaining candidate on the voters' ballots.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Transfer {
+    /// The eliminated candidate.
+    pub from: String,
+    /// The candidate which receives the votes.
+    pub to: String,
+    /// Number of votes moved.
+    pub votes: u32
+}
+
+/// A round of counting in a runoff election.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AuditRound {
+    /// The round's number, starting at 1.
+    pub number: usize,
+    /// Every remaining candidate's votes, in the order they were registered.
+    pub tallies: Vec<Tally>,
+    /// Candidates eliminated at the end of the round, in the order they were registered.
+    pub eliminated: Vec<String>,
+    /// Where the eliminated candidates' votes go in the next round.
+    pub transfers: Vec<Transfer>
+}
+
+/// Record of every round of a runoff election, from the first count to the result.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AuditLog {
+    /// The rounds of counting, in order.
+    pub rounds: Vec<AuditRound>,
+    /// The result of the election, without breaking ties.
+    pub result: ElectionResult
+}
+
+impl AuditLog {
+    /// Serializes the log as pretty printed JSON.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("An audit log is always serializable")
+    }
+}
+
+impl Display for AuditLog {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for round in self.rounds.iter() {
+            writeln!(f, "Round {}", round.number)?;
+
+            for tally in round.tallies.iter() {
+                writeln!(f, "  {}: {}", tally.candidate, tally.votes)?;
+            }
+
+            if !round.eliminated.is_empty() {
+                writeln!(f, "  Eliminated: {}", round.eliminated.join(", "))?;
+            }
+
+            for transfer in round.transfers.iter() {
+                writeln!(f, "  {} -> {}: {}", transfer.from, transfer.to, transfer.votes)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// A runoff election. Voters rank some or all of the candidates and the candidate with the fewest votes is eliminated
+/// each round until one candidate has a majority. Ballots whose ranked candidates have all been eliminated are not counted.
+pub struct RunoffElection {
+    /// The election's candidates.
+    candidates: Candidates,
+    /// Each voter's ranking, as candidate ids.
+    votes: Vec<Vec<usize>>
+}
+
+impl RunoffElection {
+    /// Creates a new runoff election without candidates.
+    pub fn new() -> Self {
+        RunoffElection {
+            candidates: Candidates::new(),
+            votes: Vec::new()
+        }
+    }
+
+    /// Checks if a candidate exists.
+    ///
+    /// # Arguments
+    /// * `name` - The candidate's name. Case insensitive.
+    pub fn contains(&self, name: &str) -> bool {
+        self.candidates.contains(name)
+    }
+
+    /// Number of candidates in the election.
+    pub fn len(&self) -> usize {
+        self.candidates.len()
+    }
+
+    /// The election's candidates.
+    pub fn candidates(&self) -> &Candidates {
+        &self.candidates
+    }
+
+    /// Tabulates rounds until a candidate wins or the remaining candidates tie.
+    /// Returns a log of every round and the result.
+    pub fn audit(&self) -> Result<AuditLog, ElectionError> {
+        if self.candidates.is_empty() {
+            return Err(ElectionError::NoCandidatesError);
+        }
+
+        let name = |id: usize| self.candidates.name(id).to_string();
+        let mut eliminated = vec![false; self.len()];
+        let mut rounds: Vec<AuditRound> = Vec::new();
+
+        loop {
+            let (votes, tabulation) = tabulate(&self.votes, &eliminated);
+            let number = rounds.len() + 1;
+
+            let tallies = (0..self.len())
+                .filter(|&id| !eliminated[id])
+                .map(|id| Tally { candidate: name(id), votes: votes[id] })
+                .collect();
+
+            match tabulation {
+                RunoffTabulationResult::Win(id) => {
+                    rounds.push(AuditRound { number, tallies, eliminated: Vec::new(), transfers: Vec::new() });
+                    return Ok(AuditLog { rounds, result: ElectionResult::Winner(name(id)) });
+                },
+                RunoffTabulationResult::Elimination(ids) => {
+                    for &id in ids.iter() {
+                        eliminated[id] = true;
+                    }
+
+                    let transfers = self.transfers(&eliminated, &ids);
+                    rounds.push(AuditRound { number, tallies, eliminated: ids.into_iter().map(name).collect(), transfers });
+                },
+                RunoffTabulationResult::Tie => {
+                    let mut remaining: Vec<String> = (0..self.len()).filter(|&id| !eliminated[id]).map(name).collect();
+                    rounds.push(AuditRound { number, tallies, eliminated: Vec::new(), transfers: Vec::new() });
+
+                    let result = if remaining.len() == 1 {
+                        ElectionResult::Winner(remaining.remove(0))
+                    } else {
+                        ElectionResult::Tie(remaining)
+                    };
+
+                    return Ok(AuditLog { rounds, result });
+                }
+            }
+        }
+    }
+
+    /// Counts where the votes of the candidates eliminated this round go in the next round.
+    ///
+    /// # Arguments
+    /// * `eliminated` - Whether each candidate has been eliminated, including this round's candidates.
+    /// * `ids` - The ids of the candidates eliminated this round.
+    fn transfers(&self, eliminated: &[bool], ids: &[usize]) -> Vec<Transfer> {
+        let mut counts: HashMap<(usize, usize), u32> = HashMap::new();
+
+        for ranking in self.votes.iter() {
+            // The ballot's choice this round, which is either still running or has just been eliminated.
+            let current = ranking.iter().find(|id| !eliminated[**id] || ids.contains(id));
+            let next = ranking.iter().find(|id| !eliminated[**id]);
+
+            if let (Some(&from), Some(&to)) = (current, next) {
+                if from != to {
+                    *counts.entry((from, to)).or_insert(0) += 1;
+                }
+            }
+        }
+
+        let mut transfers = Vec::new();
+
+        for &from in ids {
+            for to in 0..self.len() {
+                if let Some(&votes) = counts.get(&(from, to)) {
+                    transfers.push(Transfer { from: self.candidates.name(from).to_string(), to: self.candidates.name(to).to_string(), votes });
+                }
+            }
+        }
+
+        transfers
+    }
+}
+
+impl Election for RunoffElection {
+    type Error = ElectionError;
+
+    fn add_candidate(&mut self, name: String) -> Result<(), ElectionError> {
+        match self.candidates.add(name.clone()) {
+            Some(_) => Ok(()),
+            None => Err(ElectionError::CandidateAlreadyExistsError(name))
+        }
+    }
+
+    fn set_candidate_metadata(&mut self, name: &str, metadata: CandidateMetadata) -> Result<(), ElectionError> {
+        match self.candidates.set_metadata(name, metadata) {
+            Some(_) => Ok(()),
+            None => Err(ElectionError::CandidateNotFoundError(name.to_string()))
+        }
+    }
+
+    /// The ballot may leave candidates unranked, but must rank at least one.
+    fn cast_ballot(&mut self, ballot: Ballot) -> Result<(), ElectionError> {
+        let ids = ballot.validate_partial(&self.candidates).map_err(ElectionError::InvalidBallotError)?;
+
+        self.votes.push(ids);
+        Ok(())
+    }
+
+    fn result(&mut self) -> Result<ElectionResult, ElectionError> {
+        self.audit().map(|log| log.result)
+    }
+
+    fn report(&mut self, tie_break: TieBreak) -> Result<ElectionReport, ElectionError> {
+        let log = self.audit()?;
+        let result = self.result_with_tie_break(tie_break)?;
+        let mut report = ElectionReport::new("runoff", self.candidates.names(), self.votes.len(), result);
+
+        report.parties = election::party_tallies(&self.candidates, &election::first_choice_votes(&self.votes, self.len()));
+
+        report.rounds = log.rounds
+            .into_iter()
+            .map(|round| RoundReport { tallies: round.tallies, eliminated: round.eliminated })
+            .collect();
+        Ok(report)
+    }
+}
+
+pub fn main() {
+    // Reads candidates from command line args.
+    let args: Vec<String> = env::args().skip(1).collect();
+    let (args, tie_break) = election::take_tie_break_flag(&args);
+    let (args, json) = ballots::take_switch(&args, "--json");
+    let (args, chart) = ballots::take_switch(&args, "--chart");
+    let (args, audit) = ballots::take_switch(&args, "--audit");
+    let (args, use_tui) = ballots::take_switch(&args, "--tui");
+    let (args, entries) = ballots::take_candidates_flag(&args);
+    let (names, ballot_file) = ballots::take_ballots_flag(&args);
+
+    if names.len() + entries.len() < 2 {
+        panic!("Usage:\n ./runoff [--candidates <file>] [--ballots <file>] [--tie-break <strategy>] [--json] [--chart] [--audit] [--tui] <candidate1> <candidate2> <...> <candidateN>\nMinimun number of candidates is 2");
+    }
+
+    // Creates the election from the candidates.
+    let mut election = RunoffElection::new();
+
+    if let Err(err) = election.add_candidates(names).and_then(|_| election.add_candidate_entries(entries)) {
+        panic!("{:?}", err);
+    }
+
+    // Reads votes from the ballot file or from stdin.
+    match ballot_file {
+        Some(path) => vote_ballots(&mut election, ballots::read_ballots(&path).unwrap()),
+        None => {
+            // Reads number of voters in the election.
+            let number_of_voters = ballots::read_number_of_voters(&mut StdinPrompter).unwrap();
+
+            if use_tui {
+                let candidates = election.candidates().clone();
+                tui::vote(&mut election, &candidates, true, number_of_voters).unwrap()
+            } else {
+                vote(number_of_voters, &mut election, &mut StdinPrompter).unwrap()
+            }
+        }
+    };
+
+    // Tabulates results
+    if json && audit {
+        println!("{}", election.audit().unwrap().to_json());
+    } else if json {
+        println!("{}", election.report(tie_break).unwrap().to_json());
+    } else {
+        if audit {
+            print!("{}", election.audit().unwrap());
+        }
+
+        if chart {
+            print!("{}", election.report(tie_break).unwrap().render_chart());
+        }
+
+        println!("{}", election.result_with_tie_break(tie_break).unwrap().announcement());
+    }
+}
+
+/// Votes the given number of times.
+///
+/// # Arguments
+/// * `number_of_voters` - Number of voters in the election.
+/// * `election` - The election. Votes for candidates which are not in the election are not allowed.
+/// * `prompter` - Source of the voters' answers.
+pub fn vote<P: Prompter>(number_of_voters: i32, election: &mut RunoffElection, prompter: &mut P) -> Result<(), io::Error> {
+    for _ in 0..number_of_voters {
+        let ballot = ballots::read_partial_ballot(election.candidates(), prompter)?;
+        println!();
+
+        if let Err(err) = election.cast_ballot(ballot) {
+            eprintln!("{:?}", err);
+        }
+    }
+
+    Ok(())
+}
+
+/// Casts every ballot. Invalid ballots are discarded.
+///
+/// # Arguments
+/// * `election` - The election. Votes for candidates which are not in the election are not allowed.
+/// * `ballots` - The voters' ballots.
+fn vote_ballots<E: Election>(election: &mut E, ballots: Vec<Ballot>) {
+    for (i, ballot) in ballots.into_iter().enumerate() {
+        if let Err(err) = election.cast_ballot(ballot) {
+            eprintln!("Invalid ballot {}: {:?}", i + 1, err);
+        }
+    }
+}
+
+/// Tabulates the results of a runoff election round.
+/// Returns every candidate's votes, indexed by candidate id, and the result of the round.
+///
+/// # Arguments
+/// * `votes` - Each voter's ranking, as candidate ids.
+/// * `eliminated` - Whether each candidate has been eliminated.
+fn tabulate(votes: &[Vec<usize>], eliminated: &[bool]) -> (Vec<u32>, RunoffTabulationResult) {
+    let mut counts = vec![0; eliminated.len()];
+    // Ballots which still rank a candidate that has not been eliminated.
+    let mut active_ballots = 0;
+
+    for ranking in votes {
+        if let Some(&id) = ranking.iter().find(|id| !eliminated[**id]) {
+            counts[id] += 1;
+            active_ballots += 1;
+        }
+    }
+
+    let remaining: Vec<usize> = (0..eliminated.len()).filter(|&id| !eliminated[id]).collect();
+    let min = remaining.iter().map(|&id| counts[id]).min().unwrap_or(0);
+    let max = remaining.iter().map(|&id| counts[id]).max().unwrap_or(0);
+
+    let result = if max * 2 > active_ballots {
+        RunoffTabulationResult::Win(remaining.into_iter().find(|&id| counts[id] == max).unwrap())
+    } else if min == max {
+        RunoffTabulationResult::Tie
+    } else {
+        RunoffTabulationResult::Elimination(remaining.into_iter().filter(|&id| counts[id] == min).collect())
+    };
+
+    (counts, result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Creates a ballot ranking the given candidates.
+    ///
+    /// # Arguments
+    /// * `names` - The candidates' names, from most to least preferred.
+    fn ballot(names: &[&str]) -> Ballot {
+        Ballot::new(names.iter().map(|name| name.to_string()).collect())
+    }
+
+    #[test]
+    fn eliminates_until_majority() {
+        let mut election = RunoffElection::new();
+        election.add_candidates(["Alice", "Bob", "Charlie"].map(String::from)).unwrap();
+        assert!(election.add_candidate(String::from("alice")).is_err());
+
+        for ranks in [["alice", "bob", "charlie"], ["bob", "alice", "charlie"], ["charlie", "alice", "bob"], ["alice", "charlie", "bob"], ["bob", "charlie", "alice"]] {
+            election.cast_ballot(ballot(&ranks)).unwrap();
+        }
+
+        // Charlie is eliminated and his voter's second choice gives Alice the majority.
+        assert_eq!(election.result().unwrap(), ElectionResult::Winner(String::from("Alice")));
+        assert_eq!(election.result().unwrap(), ElectionResult::Winner(String::from("Alice")));
+    }
+
+    #[test]
+    fn ties_without_ballots() {
+        let mut election = RunoffElection::new();
+        election.add_candidates(["Alice", "Bob", "Charlie"].map(String::from)).unwrap();
+
+        assert_eq!(election.result().unwrap(), ElectionResult::Tie(["Alice", "Bob", "Charlie"].map(String::from).to_vec()));
+    }
+
+    #[test]
+    fn rejects_invalid_ballots() {
+        let mut election = RunoffElection::new();
+        election.add_candidates(["Alice", "Bob", "Charlie"].map(String::from)).unwrap();
+
+        assert!(election.cast_ballot(ballot(&["alice", "bob", "bob"])).is_err());
+        assert!(election.cast_ballot(ballot(&["alice", "bob", "dave"])).is_err());
+    }
+
+    #[test]
+    fn ties_when_every_remaining_candidate_is_tied() {
+        let mut election = RunoffElection::new();
+        election.add_candidates(["x", "y", "z"].map(String::from)).unwrap();
+
+        for ranks in [["x", "y", "z"], ["x", "y", "z"], ["y", "x", "z"], ["y", "x", "z"]] {
+            election.cast_ballot(ballot(&ranks)).unwrap();
+        }
+
+        assert_eq!(election.result().unwrap(), ElectionResult::Tie(vec![String::from("x"), String::from("y")]));
+        assert_eq!(election.result_with_tie_break(TieBreak::Alphabetical).unwrap(), ElectionResult::Winner(String::from("x")));
+    }
+
+    #[test]
+    fn eliminates_every_candidate_tied_for_last() {
+        let mut election = RunoffElection::new();
+        election.add_candidates(["x", "y", "z"].map(String::from)).unwrap();
+
+        for ranks in [["x", "y", "z"], ["x", "y", "z"], ["y", "x", "z"], ["z", "x", "y"]] {
+            election.cast_ballot(ballot(&ranks)).unwrap();
+        }
+
+        assert_eq!(election.result().unwrap(), ElectionResult::Winner(String::from("x")));
+    }
+
+    #[test]
+    fn votes_from_prompter() {
+        let mut election = RunoffElection::new();
+        election.add_candidates(["a", "b"].map(String::from)).unwrap();
+
+        // The first voter's ballot ranks a twice, so it is asked for again.
+        vote(2, &mut election, &mut "a\na\na\nb\nb\na\n".as_bytes()).unwrap();
+
+        assert_eq!(election.result().unwrap(), ElectionResult::Tie(vec![String::from("a"), String::from("b")]));
+    }
+
+    #[test]
+    fn audits_every_round() {
+        let mut election = RunoffElection::new();
+        election.add_candidates(["Alice", "Bob", "Charlie"].map(String::from)).unwrap();
+
+        for ranks in [["alice", "bob", "charlie"], ["alice", "charlie", "bob"], ["bob", "alice", "charlie"], ["bob", "charlie", "alice"], ["charlie", "alice", "bob"]] {
+            election.cast_ballot(ballot(&ranks)).unwrap();
+        }
+
+        let log = election.audit().unwrap();
+
+        assert_eq!(log.rounds.len(), 2);
+        assert_eq!(log.rounds[0].number, 1);
+        assert_eq!(log.rounds[0].tallies.iter().map(|tally| tally.votes).collect::<Vec<_>>(), vec![2, 2, 1]);
+        assert_eq!(log.rounds[0].eliminated, vec![String::from("Charlie")]);
+        assert_eq!(log.rounds[0].transfers, vec![Transfer { from: String::from("Charlie"), to: String::from("Alice"), votes: 1 }]);
+        assert_eq!(log.rounds[1].tallies.iter().map(|tally| tally.votes).collect::<Vec<_>>(), vec![3, 2]);
+        assert_eq!(log.result, ElectionResult::Winner(String::from("Alice")));
+        assert_eq!(election.report(TieBreak::ReportAll).unwrap().rounds.len(), 2);
+    }
+
+    #[test]
+    fn audit_fails_without_candidates() {
+        assert!(RunoffElection::new().audit().is_err());
+    }
+
+    #[test]
+    fn exhausted_ballots_are_not_counted() {
+        let mut election = RunoffElection::new();
+        election.add_candidates(["A", "B", "C"].map(String::from)).unwrap();
+        assert!(matches!(election.cast_ballot(ballot(&[])), Err(ElectionError::InvalidBallotError(ballots::BallotError::EmptyBallot))));
+
+        // C is eliminated and its only ballot does not rank anyone else, so A and B tie with 2 of 4 counted votes.
+        for ranks in [&["A"][..], &["A", "B"], &["B"], &["B", "A"], &["C"]] {
+            election.cast_ballot(ballot(ranks)).unwrap();
+        }
+
+        let log = election.audit().unwrap();
+        assert_eq!(log.rounds[1].tallies.iter().map(|tally| tally.votes).collect::<Vec<_>>(), vec![2, 2]);
+        assert_eq!(log.result, ElectionResult::Tie(vec![String::from("A"), String::from("B")]));
+
+        election.cast_ballot(ballot(&["A"])).unwrap();
+        assert_eq!(election.result().unwrap(), ElectionResult::Winner(String::from("A")));
+    }
+}
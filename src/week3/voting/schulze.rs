@@ -0,0 +1,317 @@
+use std::env;
+use std::io;
+use super::ballots;
+use super::tui;
+use super::helpers::{Prompter, StdinPrompter};
+use super::election::{self, Election, ElectionError, ElectionReport, ElectionResult, Tally, TieBreak};
+use ballots::{Ballot, CandidateMetadata, Candidates};
+
+/// An election which elects the Condorcet winner, the candidate preferred over every other by a majority of voters.
+/// If there is no Condorcet winner, the winners are found with the Schulze beatpath method.
+pub struct SchulzeElection {
+    /// The election's candidates.
+    candidates: Candidates,
+    /// Each voter's ranking, as candidate indexes.
+    votes: Vec<Vec<usize>>
+}
+
+impl SchulzeElection {
+    /// Creates a new election without candidates.
+    pub fn new() -> Self {
+        SchulzeElection {
+            candidates: Candidates::new(),
+            votes: Vec::new()
+        }
+    }
+
+    /// Checks if a candidate exists.
+    ///
+    /// # Arguments
+    /// * `name` - The candidate's name. Case insensitive.
+    pub fn contains(&self, name: &str) -> bool {
+        self.candidates.contains(name)
+    }
+
+    /// Number of candidates in the election.
+    pub fn len(&self) -> usize {
+        self.candidates.len()
+    }
+
+    /// The election's candidates.
+    pub fn candidates(&self) -> &Candidates {
+        &self.candidates
+    }
+
+    /// Calculates the pairwise preference matrix. The element at `[i][j]` is the number of voters which prefer
+    /// candidate `i` over candidate `j`.
+    pub fn preferences(&self) -> Vec<Vec<u32>> {
+        let mut preferences = vec![vec![0; self.len()]; self.len()];
+
+        for ranking in self.votes.iter() {
+            for (i, &winner) in ranking.iter().enumerate() {
+                for &loser in ranking[i + 1..].iter() {
+                    preferences[winner][loser] += 1;
+                }
+            }
+        }
+
+        preferences
+    }
+
+    /// Finds the Condorcet winner: the candidate which beats every other candidate in a head to head matchup.
+    pub fn condorcet_winner(&self) -> Option<&str> {
+        let preferences = self.preferences();
+
+        (0..self.len())
+            .find(|&i| (0..self.len()).all(|j| i == j || preferences[i][j] > preferences[j][i]))
+            .map(|i| self.candidates.name(i))
+    }
+
+    /// Calculates the strength of the strongest path between every pair of candidates. A path's strength is the
+    /// strength of its weakest link and a link from `i` to `j` exists when more voters prefer `i` over `j`.
+    pub fn strongest_paths(&self) -> Vec<Vec<u32>> {
+        let preferences = self.preferences();
+        let length = self.len();
+        let mut paths = vec![vec![0; length]; length];
+
+        for i in 0..length {
+            for j in 0..length {
+                if i != j && preferences[i][j] > preferences[j][i] {
+                    paths[i][j] = preferences[i][j];
+                }
+            }
+        }
+
+        for k in 0..length {
+            for i in 0..length {
+                for j in 0..length {
+                    if i != j && i != k && j != k {
+                        paths[i][j] = u32::max(paths[i][j], u32::min(paths[i][k], paths[k][j]));
+                    }
+                }
+            }
+        }
+
+        paths
+    }
+
+    /// Finds the Schulze winners: the candidates whose strongest path to every other candidate is at least as strong
+    /// as the strongest path back. Several candidates may win if their paths are equally strong.
+    pub fn schulze_winners(&self) -> Vec<&str> {
+        let paths = self.strongest_paths();
+
+        (0..self.len())
+            .filter(|&i| (0..self.len()).all(|j| paths[i][j] >= paths[j][i]))
+            .map(|i| self.candidates.name(i))
+            .collect()
+    }
+}
+
+impl Election for SchulzeElection {
+    type Error = ElectionError;
+
+    fn add_candidate(&mut self, name: String) -> Result<(), ElectionError> {
+        match self.candidates.add(name.clone()) {
+            Some(_) => Ok(()),
+            None => Err(ElectionError::CandidateAlreadyExistsError(name))
+        }
+    }
+
+    fn set_candidate_metadata(&mut self, name: &str, metadata: CandidateMetadata) -> Result<(), ElectionError> {
+        match self.candidates.set_metadata(name, metadata) {
+            Some(_) => Ok(()),
+            None => Err(ElectionError::CandidateNotFoundError(name.to_string()))
+        }
+    }
+
+    /// The ballot must rank every candidate exactly once.
+    fn cast_ballot(&mut self, ballot: Ballot) -> Result<(), ElectionError> {
+        let ranking = ballot.validate(&self.candidates).map_err(ElectionError::InvalidBallotError)?;
+
+        self.votes.push(ranking);
+        Ok(())
+    }
+
+    fn result(&mut self) -> Result<ElectionResult, ElectionError> {
+        if self.candidates.is_empty() {
+            return Err(ElectionError::NoCandidatesError);
+        }
+
+        if let Some(winner) = self.condorcet_winner() {
+            return Ok(ElectionResult::Winner(winner.to_string()));
+        }
+
+        let mut winners: Vec<String> = self.schulze_winners().into_iter().map(String::from).collect();
+
+        Ok(if winners.len() == 1 {
+            ElectionResult::Winner(winners.remove(0))
+        } else {
+            ElectionResult::Tie(winners)
+        })
+    }
+
+    fn report(&mut self, tie_break: TieBreak) -> Result<ElectionReport, ElectionError> {
+        let result = self.result_with_tie_break(tie_break)?;
+        let mut report = ElectionReport::new("schulze", self.candidates.names(), self.votes.len(), result);
+
+        report.parties = election::party_tallies(&self.candidates, &election::first_choice_votes(&self.votes, self.len()));
+        Ok(report)
+    }
+}
+
+pub fn main() {
+    // Reads candidates from command line args.
+    let args: Vec<String> = env::args().skip(1).collect();
+    let (args, tie_break) = election::take_tie_break_flag(&args);
+    let (args, json) = ballots::take_switch(&args, "--json");
+    let (args, chart) = ballots::take_switch(&args, "--chart");
+    let (args, use_tui) = ballots::take_switch(&args, "--tui");
+    let (args, entries) = ballots::take_candidates_flag(&args);
+    let (candidates, ballot_file) = ballots::take_ballots_flag(&args);
+
+    if candidates.len() + entries.len() < 2 {
+        panic!("Usage:\n ./schulze [--candidates <file>] [--ballots <file>] [--tie-break <strategy>] [--json] [--chart] [--tui] <candidate1> <candidate2> <...> <candidateN>\nMinimun number of candidates is 2");
+    }
+
+    // Creates the election from the candidates.
+    let mut election = SchulzeElection::new();
+
+    if let Err(err) = election.add_candidates(candidates).and_then(|_| election.add_candidate_entries(entries)) {
+        panic!("{:?}", err);
+    }
+
+    match ballot_file {
+        // Casts the ballots in the ballot file, skipping invalid ones.
+        Some(path) => {
+            for (i, ballot) in ballots::read_ballots(&path).unwrap().into_iter().enumerate() {
+                if let Err(err) = election.cast_ballot(ballot) {
+                    eprintln!("Ballot {}: {:?}", i + 1, err);
+                }
+            }
+        },
+        None => {
+            // Reads number of voters.
+            let number_of_voters = ballots::read_number_of_voters(&mut StdinPrompter).unwrap();
+
+            if use_tui {
+                let candidates = election.candidates().clone();
+                tui::vote(&mut election, &candidates, false, number_of_voters).unwrap();
+            } else {
+                vote(&mut election, number_of_voters, &mut StdinPrompter).unwrap();
+            }
+        }
+    }
+
+    if json {
+        println!("{}", election.report(tie_break).unwrap().to_json());
+        return;
+    }
+
+    // Draws every candidate's first choice votes.
+    if chart {
+        let first_choices = election::first_choice_votes(&election.votes, election.len());
+
+        let tallies: Vec<Tally> = election.candidates
+            .names()
+            .iter()
+            .zip(first_choices)
+            .map(|(name, votes)| Tally { candidate: name.clone(), votes })
+            .collect();
+
+        println!("First choices\n{}", election::render_chart(&tallies));
+    }
+
+    match election.condorcet_winner() {
+        Some(winner) => println!("Condorcet winner is {}", winner),
+        None => println!("There is no Condorcet winner, using the Schulze method")
+    }
+
+    println!("{}", election.result_with_tie_break(tie_break).unwrap().announcement());
+}
+
+/// Votes the specified number of times.
+///
+/// # Arguments
+/// * `election` - The election.
+/// * `voters` - Number of voters in the election. 1 vote for each voter.
+/// * `prompter` - Source of the voters' answers.
+pub fn vote<P: Prompter>(election: &mut SchulzeElection, voters: i32, prompter: &mut P) -> Result<(), io::Error> {
+    for _ in 0..voters {
+        let ballot = ballots::read_ballot(election.candidates(), prompter)?;
+
+        if let Err(err) = election.cast_ballot(ballot) {
+            eprintln!("{:?}", err);
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Creates a ballot ranking the given candidates.
+    ///
+    /// # Arguments
+    /// * `names` - The candidates' names, from most to least preferred.
+    fn ballot(names: &[&str]) -> Ballot {
+        Ballot::new(names.iter().map(|name| name.to_string()).collect())
+    }
+
+    #[test]
+    fn finds_winner_without_condorcet_winner() {
+        // Example from the Schulze method's Wikipedia article.
+        let mut election = SchulzeElection::new();
+        election.add_candidates(["A", "B", "C", "D", "E"].map(String::from)).unwrap();
+
+        for (voters, ranks) in [(5, "ACBED"), (5, "ADECB"), (8, "BEDAC"), (3, "CABED"), (7, "CAEBD"), (2, "CBADE"), (7, "DCEBA"), (8, "EBADC")] {
+            for _ in 0..voters {
+                election.cast_ballot(Ballot::new(ranks.chars().map(String::from).collect())).unwrap();
+            }
+        }
+
+        let paths = election.strongest_paths();
+
+        assert_eq!(election.preferences()[0][1], 20);
+        assert_eq!(election.condorcet_winner(), None);
+        assert_eq!(paths[0], vec![0, 28, 28, 30, 24]);
+        assert_eq!(paths[4], vec![25, 28, 28, 31, 0]);
+        assert_eq!(election.result().unwrap(), ElectionResult::Winner(String::from("E")));
+    }
+
+    #[test]
+    fn condorcet_winner_wins() {
+        let mut election = SchulzeElection::new();
+        election.add_candidates(["x", "y", "z"].map(String::from)).unwrap();
+        election.cast_ballot(ballot(&["y", "x", "z"])).unwrap();
+
+        assert_eq!(election.condorcet_winner(), Some("y"));
+        assert_eq!(election.result().unwrap(), ElectionResult::Winner(String::from("y")));
+    }
+
+    #[test]
+    fn ties_without_ballots() {
+        let mut election = SchulzeElection::new();
+        election.add_candidates(["x", "y"].map(String::from)).unwrap();
+
+        assert_eq!(election.result().unwrap(), ElectionResult::Tie(vec![String::from("x"), String::from("y")]));
+    }
+
+    #[test]
+    fn rejects_incomplete_ballots() {
+        let mut election = SchulzeElection::new();
+        election.add_candidates(["a", "b", "c"].map(String::from)).unwrap();
+
+        assert!(election.cast_ballot(ballot(&["a"])).is_err());
+        assert!(election.cast_ballot(ballot(&["a", "b", "a"])).is_err());
+    }
+
+    #[test]
+    fn fails_when_input_ends_before_a_valid_ballot() {
+        let mut election = SchulzeElection::new();
+        election.add_candidates(["a", "b"].map(String::from)).unwrap();
+
+        assert!(vote(&mut election, 1, &mut "a\na\n".as_bytes()).is_err());
+    }
+}
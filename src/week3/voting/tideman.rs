@@ -0,0 +1,654 @@
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::fmt::{self, Debug, Display, Formatter};
+use std::env;
+use std::io;
+use super::{ballots, sort, tui};
+use super::helpers::{Prompter, StdinPrompter};
+use super::election::{self, Election, ElectionError, ElectionReport, ElectionResult, PairReport, TieBreak};
+use ballots::{Ballot, Candidate, CandidateMetadata, Candidates};
+
+/// A node in a tideman graph.
+pub struct TidemanNode {
+    /// The node's candidate.
+    pub candidate: Candidate,
+    /// The node's edges.
+    pub links: Vec<usize>
+}
+
+impl TidemanNode {
+    /// Creates a new tideman node containing the given candidate.
+    ///
+    /// # Arguments
+    /// * `candidate` - The node's candidate.
+    pub fn new(candidate: Candidate) -> Self {
+        TidemanNode {
+            candidate,
+            links: Vec::new()
+        }
+    }
+
+    /// Adds an edge from the this node to the specified node.
+    ///
+    /// # Arguments
+    /// * `node_id` - The other node's id.
+    pub fn link(&mut self, node_id: usize) {
+        self.links.push(node_id);
+    }
+}
+
+/// A pair of candidates facing each other in a tideman election.
+#[derive(Debug, Clone)]
+pub struct TidemanPair {
+    /// The index of the winning candidate.
+    pub winner_id: usize,
+    /// The index of the losing candidate.
+    pub loser_id: usize,
+    /// Weight of the matchup or how impactful it is for the overall election.
+    pub weight: i32
+}
+
+impl TidemanPair {
+    /// Creates a new tideman pair with the supplied data.
+    ///
+    /// # Arguments
+    /// * `winner_id` - The index of the winning candidate.
+    /// * `loser_id` - The index of the losing candidate.
+    /// * `weight` - Weight of the matchup or how impactful it is for the overall election.
+    pub fn new(winner_id: usize, loser_id: usize, weight: i32) -> Self {
+        TidemanPair {
+            winner_id,
+            loser_id,
+            weight
+        }
+    }
+}
+
+/// Head to head margins between every pair of candidates in a tideman election.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Margins {
+    /// The candidates' names, in the order they were added. The matrix's rows and columns follow this order.
+    pub candidates: Vec<String>,
+    /// The element at `[i][j]` is how many more voters prefer candidate `i` over candidate `j`.
+    /// It is negative if more voters prefer candidate `j`.
+    pub matrix: Vec<Vec<i32>>
+}
+
+impl Margins {
+    /// Gets the margin of a candidate over another.
+    ///
+    /// # Arguments
+    /// * `winner` - The first candidate's name. Case insensitive.
+    /// * `loser` - The second candidate's name. Case insensitive.
+    pub fn get(&self, winner: &str, loser: &str) -> Option<i32> {
+        let position = |name: &str| self.candidates.iter().position(|candidate| candidate.eq_ignore_ascii_case(name));
+
+        Some(self.matrix[position(winner)?][position(loser)?])
+    }
+}
+
+impl Display for Margins {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let width = self.candidates.iter().map(|name| name.len()).max().unwrap_or(0).max(4);
+
+        write!(f, "{:width$}", "")?;
+
+        for candidate in self.candidates.iter() {
+            write!(f, " {:>width$}", candidate)?;
+        }
+
+        for (candidate, row) in self.candidates.iter().zip(self.matrix.iter()) {
+            write!(f, "\n{:width$}", candidate)?;
+
+            for margin in row {
+                write!(f, " {:>width$}", margin)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// A graph used to calculate the result of a tideman election.
+pub struct TidemanGraph {
+    /// The graph's nodes.
+    nodes: Vec<TidemanNode>,
+    /// The candidates' names, which allow indexing by candidate name.
+    registry: Candidates,
+    /// Number of votes for each candidate.
+    votes: Vec<Vec<usize>>,
+    /// Pairs of candidates facing each other in a tideman election.
+    pairs: Vec<TidemanPair>
+}
+
+impl TidemanGraph {
+    /// Creates a new empty tideman graph.
+    pub fn new() -> Self {
+        TidemanGraph {
+            nodes: Vec::new(),
+            registry: Candidates::new(),
+            votes: Vec::new(),
+            pairs: Vec::new()
+        }
+    }
+
+    /// Gets a candidate's id by name.
+    ///
+    /// # Arguments
+    /// * `candidate` - The candidate's name.
+    pub fn get_candidate_id(&self, candidate: &str) -> Result<usize, ElectionError> {
+        self.registry
+            .id(candidate)
+            .ok_or_else(|| ElectionError::CandidateNotFoundError(candidate.to_string()))
+    }
+
+    /// Checks if a candidate exists.
+    ///
+    /// # Arguments
+    /// * `candidate` - The candidate's name.
+    pub fn contains(&self, candidate: &str) -> bool {
+        self.registry.contains(candidate)
+    }
+
+    /// Adds a candidate to the election.
+    ///
+    /// # Arguments
+    /// * `name` - The candidate's name.
+    pub fn add_candidate(&mut self, name: String) -> Result<(), ElectionError> {
+        match self.registry.add(name.clone()) {
+            None => Err(ElectionError::CandidateAlreadyExistsError(name)),
+            Some(id) => {
+                self.nodes.push(TidemanNode::new(self.registry.candidate(id)));
+                Ok(())
+            }
+        }
+    }
+
+    /// Checks if the graph has any cycle starting from the specified node.
+    ///
+    /// # Arguments
+    /// * `node_id` - The node's index.
+    pub fn has_cycles_from(&self, node_id: usize) -> bool {
+        let mut path: HashSet<usize> = HashSet::new();
+        let mut visited: HashSet<usize> = HashSet::new();
+
+        self.has_cycles_dfs(node_id, &mut path, &mut visited)
+    }
+
+    /// Checks if the graph has cycles using the DFS algorithm to traverse the graph.
+    /// Only reaching a node in the current path is a cycle, nodes reached through several paths are not.
+    ///
+    /// # Arguments
+    /// * `node_id` - The node's index.
+    /// * `path` - Nodes in the path from the starting node to the current one.
+    /// * `visited` - Nodes which were already fully explored.
+    fn has_cycles_dfs(&self, node_id: usize, path: &mut HashSet<usize>, visited: &mut HashSet<usize>) -> bool {
+        if !path.insert(node_id) {
+            return true;
+        }
+
+        if visited.insert(node_id) {
+            for link_id in self.nodes[node_id].links.iter() {
+                if self.has_cycles_dfs(*link_id, path, visited) {
+                    return true;
+                }
+            }
+        }
+
+        path.remove(&node_id);
+        false
+    }
+
+    /// Locks a the tideman pair having the specified winner and loser candidates if the lock does not create a cycle.
+    ///
+    /// # Arguments
+    /// * `winner_id` - The pair's winner's index.
+    /// * `loser_id` - The pair's loser's index.
+    fn lock(&mut self, winner_id: usize, loser_id: usize) -> Result<(), ElectionError> {
+        let candidates: Vec<Result<usize, ElectionError>> = [winner_id, loser_id]
+            .iter()
+            .map(|candidate_id| {
+                match self.nodes.get(*candidate_id) {
+                    Some(_) => Ok(*candidate_id),
+                    _ => Err(ElectionError::CandidateNotFoundError(candidate_id.to_string()))
+                }
+            })
+            .collect();
+
+        let ids = (candidates[0].clone()?, candidates[1].clone()?);
+        self.nodes[ids.0].link(ids.1);
+
+        if self.has_cycles_from(ids.0) {
+            self.nodes[ids.0].links.pop();
+            Err(ElectionError::LockCreatedCycleError)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Number of candidates in the graph.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Calculates the head to head margins between every pair of candidates, before the pairs are sorted.
+    pub fn margins(&self) -> Margins {
+        let mut matrix: Vec<Vec<i32>> = self.nodes
+            .iter()
+            .map(|_| self.nodes
+                .iter()
+                .map(|_| 0)
+                .collect()
+            )
+            .collect();
+
+        for v in self.votes.iter() {
+            // Candidates left off a ballot are less preferred than every ranked candidate.
+            let unranked: Vec<usize> = (0..self.nodes.len()).filter(|id| !v.contains(id)).collect();
+
+            for i in 0..v.len() {
+                for &j in v[i + 1..].iter().chain(unranked.iter()) {
+                    matrix[v[i]][j] += 1;
+                    matrix[j][v[i]] -= 1;
+                }
+            }
+        }
+
+        Margins {
+            candidates: self.registry.names().to_vec(),
+            matrix
+        }
+    }
+
+    /// Tabulates the election's results.
+    pub fn tabulate(&mut self) {
+        self.pairs.clear();
+
+        let pairs = self.margins().matrix;
+        let number_of_candidates = self.nodes.len();
+
+        // Tied matchups have no winner, so they are not locked.
+        for i in 1..number_of_candidates {
+            for j in 0..i {
+                let pair = match pairs[i][j].cmp(&0) {
+                    Ordering::Less => TidemanPair::new(j, i, -pairs[i][j]),
+                    Ordering::Greater => TidemanPair::new(i, j, pairs[i][j]),
+                    Ordering::Equal => continue
+                };
+
+                self.pairs.push(pair);
+            }
+        }
+
+        sort::quicksort_by(&mut self.pairs[..], &|bigger, smaller| smaller.weight < bigger.weight);
+    }
+
+    /// Locks tideman pairs in the election depending on their weight in order to find a winner.
+    pub fn lock_pairs(&mut self) {
+        for node in self.nodes.iter_mut() {
+            node.links.clear();
+        }
+
+        // Pairs which would create a cycle are skipped.
+        for pair in self.pairs.clone() {
+            let _ = self.lock(pair.winner_id, pair.loser_id);
+        }
+    }
+
+    /// The election's candidates, in the order they were added.
+    pub fn candidates(&self) -> impl Iterator<Item = &Candidate> {
+        self.nodes.iter().map(|node| &node.candidate)
+    }
+
+    /// Pairs of candidates facing each other, sorted from strongest to weakest victory. Empty until the election is tabulated.
+    pub fn pairs(&self) -> &[TidemanPair] {
+        &self.pairs
+    }
+
+    /// Pairs which were locked into the graph, sorted from strongest to weakest victory.
+    pub fn locked_pairs(&self) -> Vec<&TidemanPair> {
+        self.pairs
+            .iter()
+            .filter(|pair| self.nodes[pair.winner_id].links.contains(&pair.loser_id))
+            .collect()
+    }
+
+    /// Calculates the complete ranking of the candidates implied by the locked graph, from first to last place.
+    /// Every candidate is ranked after all candidates locked over them. Candidates which are not ordered by the graph
+    /// keep the order in which they were added.
+    pub fn ranking(&self) -> Vec<&Candidate> {
+        let mut incoming: Vec<usize> = vec![0; self.len()];
+
+        for node in self.nodes.iter() {
+            for &link in node.links.iter() {
+                incoming[link] += 1;
+            }
+        }
+
+        let mut ranking: Vec<&Candidate> = Vec::with_capacity(self.len());
+        let mut ranked: Vec<bool> = vec![false; self.len()];
+
+        // The graph is acyclic, so there is always an unranked candidate without unranked candidates above it.
+        while let Some(id) = (0..self.len()).find(|&id| !ranked[id] && incoming[id] == 0) {
+            ranked[id] = true;
+            ranking.push(&self.nodes[id].candidate);
+
+            for &link in self.nodes[id].links.iter() {
+                incoming[link] -= 1;
+            }
+        }
+
+        ranking
+    }
+
+    /// Calculates the election's winner, which is the source of the locked graph: the only candidate without any
+    /// candidate locked over them. Fails if there are several sources, listing them in the order they were added.
+    pub fn get_winner(&self) -> Result<Candidate, ElectionError> {
+        let mut is_source: Vec<bool> = vec![true; self.len()];
+
+        for candidate in self.nodes.iter() {
+            for &win in candidate.links.iter() {
+                is_source[win] = false;
+            }
+        }
+
+        let sources: Vec<&Candidate> = self.nodes
+            .iter()
+            .zip(is_source)
+            .filter(|(_, is_source)| *is_source)
+            .map(|(node, _)| &node.candidate)
+            .collect();
+
+        match sources[..] {
+            [] => Err(ElectionError::NoCandidatesError),
+            [winner] => Ok(winner.clone()),
+            _ => Err(ElectionError::TieError(sources.iter().map(|candidate| candidate.name.clone()).collect()))
+        }
+    }
+}
+
+impl Election for TidemanGraph {
+    type Error = ElectionError;
+
+    fn add_candidate(&mut self, name: String) -> Result<(), ElectionError> {
+        TidemanGraph::add_candidate(self, name)
+    }
+
+    fn set_candidate_metadata(&mut self, name: &str, metadata: CandidateMetadata) -> Result<(), ElectionError> {
+        match self.registry.set_metadata(name, metadata) {
+            Some(_) => Ok(()),
+            None => Err(ElectionError::CandidateNotFoundError(name.to_string()))
+        }
+    }
+
+    /// The ballot may leave candidates unranked, but must rank at least one.
+    fn cast_ballot(&mut self, ballot: Ballot) -> Result<(), ElectionError> {
+        let ids = ballot.validate_partial(&self.registry).map_err(ElectionError::InvalidBallotError)?;
+
+        self.votes.push(ids);
+        Ok(())
+    }
+
+    fn result(&mut self) -> Result<ElectionResult, ElectionError> {
+        self.tabulate();
+        self.lock_pairs();
+
+        match self.get_winner() {
+            Ok(winner) => Ok(ElectionResult::Winner(winner.name)),
+            Err(ElectionError::TieError(names)) => Ok(ElectionResult::Tie(names)),
+            Err(err) => Err(err)
+        }
+    }
+
+    fn report(&mut self, tie_break: TieBreak) -> Result<ElectionReport, ElectionError> {
+        let result = self.result_with_tie_break(tie_break)?;
+        let mut report = ElectionReport::new("tideman", self.registry.names(), self.votes.len(), result);
+
+        report.pairs = self.pairs
+            .iter()
+            .map(|pair| PairReport {
+                winner: self.nodes[pair.winner_id].candidate.name.clone(),
+                loser: self.nodes[pair.loser_id].candidate.name.clone(),
+                margin: pair.weight as u32,
+                locked: self.nodes[pair.winner_id].links.contains(&pair.loser_id)
+            })
+            .collect();
+
+        report.parties = election::party_tallies(&self.registry, &election::first_choice_votes(&self.votes, self.len()));
+        Ok(report)
+    }
+}
+
+pub fn main() {
+    // Reads candidates from command line args.
+    let args: Vec<String> = env::args().skip(1).collect();
+    let (args, tie_break) = election::take_tie_break_flag(&args);
+    let (args, json) = ballots::take_switch(&args, "--json");
+    let (args, chart) = ballots::take_switch(&args, "--chart");
+    let (args, verbose) = ballots::take_switch(&args, "--verbose");
+    let (args, use_tui) = ballots::take_switch(&args, "--tui");
+    let (args, entries) = ballots::take_candidates_flag(&args);
+    let (candidates, ballot_file) = ballots::take_ballots_flag(&args);
+
+    if candidates.len() + entries.len() < 2 {
+        panic!("Usage:\n ./tideman [--candidates <file>] [--ballots <file>] [--tie-break <strategy>] [--json] [--chart] [--verbose] [--tui] <candidate1> <candidate2> <...> <candidateN>\nMinimun number of candidates is 2");
+    }
+
+    // Creates a tideman graph from candidates.
+    let mut graph = TidemanGraph::new();
+
+    if let Err(err) = graph.add_candidates(candidates).and_then(|_| graph.add_candidate_entries(entries)) {
+        panic!("{:?}", err);
+    }
+
+    match ballot_file {
+        // Adds the ballots in the ballot file, skipping invalid ones.
+        Some(path) => {
+            for (i, ballot) in ballots::read_ballots(&path).unwrap().into_iter().enumerate() {
+                if let Err(err) = graph.cast_ballot(ballot) {
+                    eprintln!("Ballot {}: {:?}", i + 1, err);
+                }
+            }
+        },
+        None => {
+            // Reads number of voters.
+            let number_of_voters = ballots::read_number_of_voters(&mut StdinPrompter).unwrap();
+
+            if use_tui {
+                let candidates = graph.registry.clone();
+                tui::vote(&mut graph, &candidates, true, number_of_voters).unwrap();
+            } else {
+                vote(&mut graph, number_of_voters, &mut StdinPrompter).unwrap();
+            }
+        }
+    }
+
+    if json {
+        println!("{}", graph.report(tie_break).unwrap().to_json());
+        return;
+    }
+
+    // Prints how many more voters prefer each candidate (rows) over each other candidate (columns).
+    if verbose {
+        println!("Margins:\n{}\n", graph.margins());
+    }
+
+    // Draws each matchup's margin.
+    if chart {
+        println!("{}", graph.report(tie_break).unwrap().render_chart());
+    }
+
+    // Tabulates results and finds winner.
+    match graph.result_with_tie_break(tie_break).unwrap() {
+        ElectionResult::Winner(name) => println!("The winner is {}", name),
+        result => println!("{}", result.announcement())
+    }
+
+    // Prints every candidate's place.
+    println!("\nRanking:");
+
+    for (i, candidate) in graph.ranking().iter().enumerate() {
+        println!("{}. {}", i + 1, candidate.name);
+    }
+}
+
+/// Votes the specified number of times.
+///
+/// # Arguments
+/// * `graph` - The election's graph.
+/// * `voters` - Number of voters in the election. 1 vote for each voter.
+/// * `prompter` - Source of the voters' answers.
+pub fn vote<P: Prompter>(graph: &mut TidemanGraph, voters: i32, prompter: &mut P) -> Result<(), io::Error> {
+    for _ in 0..voters {
+        let ballot = ballots::read_partial_ballot(&graph.registry, prompter)?;
+
+        if let Err(err) = graph.cast_ballot(ballot) {
+            eprintln!("{:?}", err);
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Creates a ballot ranking the given candidates.
+    ///
+    /// # Arguments
+    /// * `names` - The candidates' names, from most to least preferred.
+    fn ballot(names: &[&str]) -> Ballot {
+        Ballot::new(names.iter().map(|name| name.to_string()).collect())
+    }
+
+    /// Names of the candidates in the graph's ranking.
+    ///
+    /// # Arguments
+    /// * `graph` - The tabulated graph.
+    fn ranking(graph: &TidemanGraph) -> Vec<&str> {
+        graph.ranking().iter().map(|candidate| candidate.name.as_str()).collect()
+    }
+
+    #[test]
+    fn ranks_every_candidate() {
+        let mut graph = TidemanGraph::new();
+        graph.add_candidates(["a", "b", "c", "d"].map(String::from)).unwrap();
+
+        for _ in 0..3 {
+            graph.cast_ballot(ballot(&["c", "a", "b", "d"])).unwrap();
+        }
+
+        for _ in 0..2 {
+            graph.cast_ballot(ballot(&["a", "b", "d", "c"])).unwrap();
+        }
+
+        graph.cast_ballot(ballot(&["b", "d", "a", "c"])).unwrap();
+        let result = graph.result().unwrap();
+
+        assert_eq!(result, ElectionResult::Tie(vec![String::from("a"), String::from("c")]));
+        assert_eq!(ranking(&graph), vec!["a", "b", "c", "d"]);
+        assert_eq!(graph.pairs().len(), 3);
+        assert_eq!(graph.locked_pairs().len(), 3);
+
+        // Tabulating again gives the same pairs.
+        assert_eq!(graph.result().unwrap(), result);
+        assert_eq!(graph.pairs().len(), 3);
+    }
+
+    #[test]
+    fn ranks_a_single_ballot() {
+        let mut graph = TidemanGraph::new();
+        graph.add_candidates(["a", "b", "c"].map(String::from)).unwrap();
+        graph.cast_ballot(ballot(&["b", "c", "a"])).unwrap();
+
+        assert_eq!(graph.result().unwrap(), ElectionResult::Winner(String::from("b")));
+        assert_eq!(graph.locked_pairs().len(), 3);
+        assert_eq!(ranking(&graph), vec!["b", "c", "a"]);
+    }
+
+    #[test]
+    fn fails_without_candidates() {
+        let mut graph = TidemanGraph::new();
+
+        assert!(matches!(graph.get_winner(), Err(ElectionError::NoCandidatesError)));
+        assert!(graph.result().is_err());
+    }
+
+    #[test]
+    fn single_candidate_wins() {
+        let mut graph = TidemanGraph::new();
+        graph.add_candidate(String::from("solo")).unwrap();
+
+        assert_eq!(graph.result().unwrap(), ElectionResult::Winner(String::from("solo")));
+    }
+
+    #[test]
+    fn reports_every_source_as_tied() {
+        let mut graph = TidemanGraph::new();
+        graph.add_candidates(["a", "b", "c"].map(String::from)).unwrap();
+        // Every pair of candidates is tied, so no pair is locked.
+        graph.cast_ballot(ballot(&["a", "c", "b"])).unwrap();
+        graph.cast_ballot(ballot(&["b", "c", "a"])).unwrap();
+        graph.tabulate();
+        graph.lock_pairs();
+
+        match graph.get_winner() {
+            Err(ElectionError::TieError(names)) => assert_eq!(names, ["a", "b", "c"].map(String::from)),
+            other => panic!("Expected a tie, got {:?}", other.map(|candidate| candidate.name))
+        }
+    }
+
+    #[test]
+    fn skips_pairs_which_create_cycles() {
+        let mut graph = TidemanGraph::new();
+        graph.add_candidates(["a", "b", "c"].map(String::from)).unwrap();
+
+        // a beats b by 3, b beats c by 3 and c beats a by 1.
+        for ranks in [["a", "b", "c"], ["a", "b", "c"], ["a", "b", "c"], ["b", "c", "a"], ["b", "c", "a"], ["c", "a", "b"], ["c", "a", "b"]] {
+            graph.cast_ballot(ballot(&ranks)).unwrap();
+        }
+
+        assert_eq!(graph.result().unwrap(), ElectionResult::Winner(String::from("a")));
+        assert_eq!(graph.locked_pairs().len(), 2);
+    }
+
+    #[test]
+    fn votes_from_prompter() {
+        let mut graph = TidemanGraph::new();
+        graph.add_candidates(["a", "b", "c"].map(String::from)).unwrap();
+        vote(&mut graph, 1, &mut "c\nb\na\n".as_bytes()).unwrap();
+
+        assert_eq!(graph.result().unwrap(), ElectionResult::Winner(String::from("c")));
+    }
+
+    #[test]
+    fn computes_margins() {
+        let mut graph = TidemanGraph::new();
+        assert!(graph.margins().matrix.is_empty());
+
+        graph.add_candidates(["A", "B"].map(String::from)).unwrap();
+        graph.cast_ballot(ballot(&["b", "a"])).unwrap();
+        let margins = graph.margins();
+
+        assert_eq!(margins.get("a", "B"), Some(-1));
+        assert_eq!(margins.get("b", "a"), Some(1));
+        assert_eq!(margins.get("c", "a"), None);
+
+        graph.tabulate();
+        assert_eq!(graph.pairs().len(), 1);
+    }
+
+    #[test]
+    fn ranked_candidates_beat_unranked_ones() {
+        let mut graph = TidemanGraph::new();
+        graph.add_candidates(["A", "B", "C"].map(String::from)).unwrap();
+        graph.cast_ballot(ballot(&["C"])).unwrap();
+        graph.cast_ballot(ballot(&["B", "A"])).unwrap();
+        graph.cast_ballot(ballot(&["C", "A"])).unwrap();
+
+        assert_eq!(graph.margins().get("C", "B"), Some(1));
+        assert_eq!(graph.margins().get("A", "B"), Some(0));
+        assert_eq!(graph.result().unwrap(), ElectionResult::Winner(String::from("C")));
+    }
+}
@@ -0,0 +1,280 @@
+use std::io::{self, Write};
+use crossterm::{cursor, execute, queue, terminal};
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use crossterm::style::Print;
+use super::ballots::{Ballot, BallotError, Candidates};
+use super::election::Election;
+
+/// Keys accepted by the ballot screen.
+const HELP: &str = "Up/Down: move  Space: rank/unrank  Backspace: undo  Enter: submit  Esc: quit";
+
+/// A ballot being filled in the terminal UI.
+pub struct BallotEntry<'a> {
+    /// The election's candidates.
+    candidates: &'a Candidates,
+    /// The highlighted candidate's id.
+    cursor: usize,
+    /// The ranked candidates' ids, from most to least preferred.
+    ranks: Vec<usize>,
+    /// Whether candidates may be left unranked.
+    partial: bool,
+    /// Why the ballot was rejected the last time it was submitted.
+    error: Option<BallotError>
+}
+
+impl<'a> BallotEntry<'a> {
+    /// Creates an empty ballot with the first candidate highlighted.
+    ///
+    /// # Arguments
+    /// * `candidates` - The election's candidates.
+    /// * `partial` - Whether candidates may be left unranked.
+    pub fn new(candidates: &'a Candidates, partial: bool) -> Self {
+        BallotEntry {
+            candidates,
+            cursor: 0,
+            ranks: Vec::new(),
+            partial,
+            error: None
+        }
+    }
+
+    /// Highlights the previous candidate, wrapping around to the last one.
+    pub fn up(&mut self) {
+        self.cursor = (self.cursor + self.candidates.len() - 1) % self.candidates.len();
+    }
+
+    /// Highlights the next candidate, wrapping around to the first one.
+    pub fn down(&mut self) {
+        self.cursor = (self.cursor + 1) % self.candidates.len();
+    }
+
+    /// Ranks the highlighted candidate below every ranked candidate, or removes it from the ranking if it was already ranked.
+    pub fn toggle(&mut self) {
+        match self.rank_of(self.cursor) {
+            Some(i) => {
+                self.ranks.remove(i);
+            },
+            None => self.ranks.push(self.cursor)
+        }
+
+        self.error = None;
+    }
+
+    /// Removes the least preferred candidate from the ranking.
+    pub fn undo(&mut self) {
+        self.ranks.pop();
+        self.error = None;
+    }
+
+    /// Position of a candidate in the ranking, starting at 0.
+    ///
+    /// # Arguments
+    /// * `id` - The candidate's id.
+    pub fn rank_of(&self, id: usize) -> Option<usize> {
+        self.ranks.iter().position(|&ranked| ranked == id)
+    }
+
+    /// Validates the ballot. Returns the ballot if it is valid, otherwise keeps the error to be shown to the voter.
+    pub fn submit(&mut self) -> Option<Ballot> {
+        let ballot = Ballot::new(self.ranks.iter().map(|&id| self.candidates.name(id).to_string()).collect());
+
+        let validation = if self.partial {
+            ballot.validate_partial(self.candidates)
+        } else {
+            ballot.validate(self.candidates)
+        };
+
+        match validation {
+            Ok(_) => Some(ballot),
+            Err(err) => {
+                self.error = Some(err);
+                None
+            }
+        }
+    }
+
+    /// The lines of text shown on screen.
+    ///
+    /// # Arguments
+    /// * `title` - Text shown above the candidates.
+    pub fn lines(&self, title: &str) -> Vec<String> {
+        let mut lines = vec![title.to_string(), HELP.to_string(), String::new()];
+
+        for (id, name) in self.candidates.names().iter().enumerate() {
+            let pointer = if id == self.cursor { '>' } else { ' ' };
+
+            let rank = match self.rank_of(id) {
+                Some(i) => format!("{:>2}.", i + 1),
+                None => String::from("   ")
+            };
+
+            lines.push(format!("{} {} {}", pointer, rank, name));
+        }
+
+        let ranking: Vec<&str> = self.ranks.iter().map(|&id| self.candidates.name(id)).collect();
+
+        lines.push(String::new());
+        lines.push(format!("Ranking: {}", ranking.join(", ")));
+
+        if let Some(err) = &self.error {
+            lines.push(format!("{:?}", err));
+        }
+
+        lines
+    }
+}
+
+/// Puts the terminal in raw mode on an alternate screen. The terminal is restored when dropped, even if reading a ballot fails.
+struct RawScreen;
+
+impl RawScreen {
+    /// Switches to the alternate screen and enables raw mode.
+    fn enter() -> Result<Self, io::Error> {
+        terminal::enable_raw_mode()?;
+
+        if let Err(err) = execute!(io::stdout(), terminal::EnterAlternateScreen, cursor::Hide) {
+            terminal::disable_raw_mode()?;
+            return Err(err);
+        }
+
+        Ok(RawScreen)
+    }
+}
+
+impl Drop for RawScreen {
+    fn drop(&mut self) {
+        let _ = execute!(io::stdout(), cursor::Show, terminal::LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+/// Draws the ballot, replacing whatever was on screen.
+///
+/// # Arguments
+/// * `out` - The terminal.
+/// * `lines` - The lines of text to draw.
+fn draw<W: Write>(out: &mut W, lines: &[String]) -> Result<(), io::Error> {
+    queue!(out, terminal::Clear(terminal::ClearType::All))?;
+
+    for (row, line) in lines.iter().enumerate() {
+        queue!(out, cursor::MoveTo(0, row as u16), Print(line))?;
+    }
+
+    out.flush()
+}
+
+/// Reads a ballot in a full screen terminal UI.
+/// The voter picks candidates from a list and invalid ballots are explained without leaving the screen.
+/// Fails with `ErrorKind::Interrupted` if the voter quits with Esc or Ctrl+C.
+///
+/// # Arguments
+/// * `candidates` - The election's candidates.
+/// * `partial` - Whether candidates may be left unranked. Otherwise the ballot must rank every candidate.
+/// * `title` - Text shown above the candidates, for example the voter's number.
+pub fn read_ballot(candidates: &Candidates, partial: bool, title: &str) -> Result<Ballot, io::Error> {
+    let mut entry = BallotEntry::new(candidates, partial);
+    let mut stdout = io::stdout();
+    let _screen = RawScreen::enter()?;
+
+    loop {
+        draw(&mut stdout, &entry.lines(title))?;
+
+        let key = match event::read()? {
+            Event::Key(key) if key.kind == KeyEventKind::Press => key,
+            _ => continue
+        };
+
+        let cancelled = key.code == KeyCode::Esc
+            || (key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL));
+
+        if cancelled {
+            return Err(io::Error::new(io::ErrorKind::Interrupted, "Ballot entry cancelled"));
+        }
+
+        match key.code {
+            KeyCode::Up => entry.up(),
+            KeyCode::Down => entry.down(),
+            KeyCode::Char(' ') => entry.toggle(),
+            KeyCode::Backspace => entry.undo(),
+            KeyCode::Enter => if let Some(ballot) = entry.submit() {
+                return Ok(ballot);
+            },
+            _ => {}
+        }
+    }
+}
+
+/// Votes the given number of times, reading each ballot in the terminal UI.
+///
+/// # Arguments
+/// * `election` - The election.
+/// * `candidates` - The election's candidates.
+/// * `partial` - Whether candidates may be left unranked.
+/// * `voters` - Number of voters in the election. 1 vote for each voter.
+pub fn vote<E: Election>(election: &mut E, candidates: &Candidates, partial: bool, voters: i32) -> Result<(), io::Error> {
+    for i in 0..voters {
+        let ballot = read_ballot(candidates, partial, &format!("Voter {} of {}", i + 1, voters))?;
+
+        if let Err(err) = election.cast_ballot(ballot) {
+            eprintln!("{:?}", err);
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Registers Alice, Bob and Charlie.
+    fn candidates() -> Candidates {
+        let mut candidates = Candidates::new();
+
+        for name in ["Alice", "Bob", "Charlie"] {
+            candidates.add(name.to_string());
+        }
+
+        candidates
+    }
+
+    #[test]
+    fn ranks_highlighted_candidates() {
+        let candidates = candidates();
+        let mut entry = BallotEntry::new(&candidates, false);
+
+        // Wraps around to Charlie.
+        entry.up();
+        entry.toggle();
+        assert!(entry.submit().is_none());
+        assert_eq!(entry.lines("Voter 1").last().unwrap(), "The ballot does not rank Alice, Bob");
+
+        entry.down();
+        entry.toggle();
+        entry.down();
+        entry.toggle();
+        // Unranks and ranks Bob again, then undoes it.
+        entry.toggle();
+        entry.toggle();
+        entry.undo();
+        entry.toggle();
+
+        assert_eq!(entry.rank_of(1), Some(2));
+        assert_eq!(entry.submit().unwrap().ranks(), ["Charlie", "Alice", "Bob"]);
+    }
+
+    #[test]
+    fn draws_ranks_next_to_candidates() {
+        let candidates = candidates();
+        let mut entry = BallotEntry::new(&candidates, true);
+        entry.down();
+        entry.toggle();
+
+        let lines = entry.lines("Voter 1");
+
+        assert_eq!(lines[0], "Voter 1");
+        assert_eq!(lines[3..6], ["      Alice", ">  1. Bob", "      Charlie"]);
+        assert_eq!(lines.last().unwrap(), "Ranking: Bob");
+        assert_eq!(entry.submit().unwrap().ranks(), ["Bob"]);
+    }
+}
@@ -1,4 +1,10 @@
 use super::helpers;
 
 pub mod inheritance;
-pub mod speller;
\ No newline at end of file
+pub mod list;
+pub mod doubly_linked_list;
+pub mod hashtable;
+pub mod hashtable_open;
+pub mod hashbench;
+pub mod hunspell;
+pub mod speller;
@@ -0,0 +1,566 @@
+use std::fmt::{self, Debug, Formatter};
+use std::marker::PhantomData;
+use std::mem;
+use std::ptr::NonNull;
+
+/// A custom doubly linked list node.
+struct DoublyLinkedNode<T> {
+    /// The node's data.
+    data: T,
+    /// Previous node in the list.
+    prev: Option<NonNull<DoublyLinkedNode<T>>>,
+    /// Next node in the list.
+    next: Option<NonNull<DoublyLinkedNode<T>>>
+}
+
+/// A custom doubly linked list, which can be traversed and modified in both directions with a cursor.
+pub struct DoublyLinkedList<T> {
+    /// The first node in the list.
+    head: Option<NonNull<DoublyLinkedNode<T>>>,
+    /// The last node in the list.
+    tail: Option<NonNull<DoublyLinkedNode<T>>>,
+    /// Number of items in the list.
+    len: usize,
+    /// The list owns its nodes, which are allocated in boxes.
+    marker: PhantomData<Box<DoublyLinkedNode<T>>>
+}
+
+// The list owns its nodes, their pointers are never shared outside of it.
+unsafe impl <T: Send> Send for DoublyLinkedList<T> {}
+unsafe impl <T: Sync> Sync for DoublyLinkedList<T> {}
+
+impl <T> DoublyLinkedList<T> {
+    /// Creates a new empty doubly linked list.
+    pub fn new() -> Self {
+        Self {
+            head: None,
+            tail: None,
+            len: 0,
+            marker: PhantomData
+        }
+    }
+
+    /// Number of items in the list.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Checks if the list has no items.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Gets a reference to the first item in the list.
+    pub fn front(&self) -> Option<&T> {
+        self.head.map(|node| unsafe { &(*node.as_ptr()).data })
+    }
+
+    /// Gets a reference to the last item in the list.
+    pub fn back(&self) -> Option<&T> {
+        self.tail.map(|node| unsafe { &(*node.as_ptr()).data })
+    }
+
+    /// Adds a new item to the start of the list.
+    ///
+    /// # Arguments
+    /// * `data` - The data to add.
+    pub fn push_front(&mut self, data: T) {
+        self.link_between(data, None, self.head);
+    }
+
+    /// Adds a new item to the end of the list.
+    ///
+    /// # Arguments
+    /// * `data` - The data to add.
+    pub fn push_back(&mut self, data: T) {
+        self.link_between(data, self.tail, None);
+    }
+
+    /// Removes the first item in the list and returns it.
+    pub fn pop_front(&mut self) -> Option<T> {
+        self.head.map(|node| self.unlink(node))
+    }
+
+    /// Removes the last item in the list and returns it.
+    pub fn pop_back(&mut self) -> Option<T> {
+        self.tail.map(|node| self.unlink(node))
+    }
+
+    /// Iterates over references to the list's items, from the front or from the back.
+    pub fn iter(&self) -> DoublyLinkedIter<'_, T> {
+        DoublyLinkedIter {
+            head: self.head,
+            tail: self.tail,
+            len: self.len,
+            marker: PhantomData
+        }
+    }
+
+    /// Creates a cursor which starts at the first item, or at the empty position if the list is empty.
+    pub fn cursor_front_mut(&mut self) -> CursorMut<'_, T> {
+        CursorMut {
+            current: self.head,
+            index: 0,
+            list: self
+        }
+    }
+
+    /// Creates a cursor which starts at the last item, or at the empty position if the list is empty.
+    pub fn cursor_back_mut(&mut self) -> CursorMut<'_, T> {
+        CursorMut {
+            current: self.tail,
+            index: self.len.saturating_sub(1),
+            list: self
+        }
+    }
+
+    /// Links a new node between 2 adjacent nodes, or at one of the list's ends if one of them is `None`.
+    /// Returns the new node.
+    ///
+    /// # Arguments
+    /// * `data` - The node's data.
+    /// * `prev` - The node which will be before the new one.
+    /// * `next` - The node which will be after the new one.
+    fn link_between(&mut self, data: T, prev: Option<NonNull<DoublyLinkedNode<T>>>, next: Option<NonNull<DoublyLinkedNode<T>>>) -> NonNull<DoublyLinkedNode<T>> {
+        let node = NonNull::from(Box::leak(Box::new(DoublyLinkedNode { data, prev, next })));
+
+        // Both nodes belong to this list, which is borrowed mutably.
+        unsafe {
+            match prev {
+                Some(prev) => (*prev.as_ptr()).next = Some(node),
+                None => self.head = Some(node)
+            }
+
+            match next {
+                Some(next) => (*next.as_ptr()).prev = Some(node),
+                None => self.tail = Some(node)
+            }
+        }
+
+        self.len += 1;
+        node
+    }
+
+    /// Removes a node from the list and returns its data.
+    ///
+    /// # Arguments
+    /// * `node` - The node, which must belong to this list.
+    fn unlink(&mut self, node: NonNull<DoublyLinkedNode<T>>) -> T {
+        // The node was allocated by `link_between` and is owned by this list, so it can be freed once it's unlinked.
+        let node = unsafe { Box::from_raw(node.as_ptr()) };
+
+        unsafe {
+            match node.prev {
+                Some(prev) => (*prev.as_ptr()).next = node.next,
+                None => self.head = node.next
+            }
+
+            match node.next {
+                Some(next) => (*next.as_ptr()).prev = node.prev,
+                None => self.tail = node.prev
+            }
+        }
+
+        self.len -= 1;
+        node.data
+    }
+
+    /// Moves every node of another list between 2 adjacent nodes of this one, or at one of the list's ends if one of
+    /// them is `None`.
+    ///
+    /// # Arguments
+    /// * `other` - The list to move.
+    /// * `prev` - The node which will be before the other list's nodes.
+    /// * `next` - The node which will be after the other list's nodes.
+    fn splice_between(&mut self, mut other: Self, prev: Option<NonNull<DoublyLinkedNode<T>>>, next: Option<NonNull<DoublyLinkedNode<T>>>) {
+        let (first, last) = match (other.head.take(), other.tail.take()) {
+            (Some(first), Some(last)) => (first, last),
+            _ => return
+        };
+
+        // The other list no longer owns its nodes, which now belong to this list.
+        unsafe {
+            (*first.as_ptr()).prev = prev;
+            (*last.as_ptr()).next = next;
+
+            match prev {
+                Some(prev) => (*prev.as_ptr()).next = Some(first),
+                None => self.head = Some(first)
+            }
+
+            match next {
+                Some(next) => (*next.as_ptr()).prev = Some(last),
+                None => self.tail = Some(last)
+            }
+        }
+
+        self.len += mem::replace(&mut other.len, 0);
+    }
+}
+
+/// A cursor which points to an item of a doubly linked list and can modify the list around it.
+/// Besides the items, the cursor can point to an empty position which is after the last item and before the first one.
+pub struct CursorMut<'a, T> {
+    /// The list.
+    list: &'a mut DoublyLinkedList<T>,
+    /// The node the cursor points to, `None` for the empty position.
+    current: Option<NonNull<DoublyLinkedNode<T>>>,
+    /// Index of the current item, the empty position's index is the list's length.
+    index: usize
+}
+
+impl <'a, T> CursorMut<'a, T> {
+    /// Index of the current item, `None` if the cursor is at the empty position.
+    pub fn index(&self) -> Option<usize> {
+        self.current.map(|_| self.index)
+    }
+
+    /// Gets a mutable reference to the current item, `None` if the cursor is at the empty position.
+    pub fn current(&mut self) -> Option<&mut T> {
+        self.current.map(|node| unsafe { &mut (*node.as_ptr()).data })
+    }
+
+    /// Gets a mutable reference to the item after the current one.
+    pub fn peek_next(&mut self) -> Option<&mut T> {
+        self.next_node().map(|node| unsafe { &mut (*node.as_ptr()).data })
+    }
+
+    /// Gets a mutable reference to the item before the current one.
+    pub fn peek_prev(&mut self) -> Option<&mut T> {
+        self.prev_node().map(|node| unsafe { &mut (*node.as_ptr()).data })
+    }
+
+    /// Moves the cursor to the next item. Moves from the last item to the empty position and from the empty position
+    /// to the first item.
+    pub fn move_next(&mut self) {
+        self.index = match self.current {
+            Some(_) => self.index + 1,
+            None => 0
+        };
+
+        self.current = self.next_node();
+    }
+
+    /// Moves the cursor to the previous item. Moves from the first item to the empty position and from the empty
+    /// position to the last item.
+    pub fn move_prev(&mut self) {
+        self.current = self.prev_node();
+
+        self.index = match self.current {
+            Some(_) => self.index - 1,
+            None => self.list.len
+        };
+    }
+
+    /// Adds an item before the current one. If the cursor is at the empty position, the item is added to the end.
+    ///
+    /// # Arguments
+    /// * `data` - The data to add.
+    pub fn insert_before(&mut self, data: T) {
+        self.list.link_between(data, self.prev_node(), self.current);
+        self.index += 1;
+    }
+
+    /// Adds an item after the current one. If the cursor is at the empty position, the item is added to the start.
+    ///
+    /// # Arguments
+    /// * `data` - The data to add.
+    pub fn insert_after(&mut self, data: T) {
+        self.list.link_between(data, self.current, self.next_node());
+
+        if self.current.is_none() {
+            self.index += 1;
+        }
+    }
+
+    /// Removes the current item and returns it, moving the cursor to the next item.
+    /// Does nothing if the cursor is at the empty position.
+    pub fn remove_current(&mut self) -> Option<T> {
+        let node = self.current?;
+        self.current = self.next_node();
+
+        Some(self.list.unlink(node))
+    }
+
+    /// Moves every item of another list before the current item. If the cursor is at the empty position, the items
+    /// are added to the end.
+    ///
+    /// # Arguments
+    /// * `other` - The list to move.
+    pub fn splice_before(&mut self, other: DoublyLinkedList<T>) {
+        self.index += other.len;
+        self.list.splice_between(other, self.prev_node(), self.current);
+    }
+
+    /// Moves every item of another list after the current item. If the cursor is at the empty position, the items
+    /// are added to the start.
+    ///
+    /// # Arguments
+    /// * `other` - The list to move.
+    pub fn splice_after(&mut self, other: DoublyLinkedList<T>) {
+        if self.current.is_none() {
+            self.index += other.len;
+        }
+
+        self.list.splice_between(other, self.current, self.next_node());
+    }
+
+    /// The node after the current one, wrapping around the empty position.
+    fn next_node(&self) -> Option<NonNull<DoublyLinkedNode<T>>> {
+        match self.current {
+            Some(node) => unsafe { (*node.as_ptr()).next },
+            None => self.list.head
+        }
+    }
+
+    /// The node before the current one, wrapping around the empty position.
+    fn prev_node(&self) -> Option<NonNull<DoublyLinkedNode<T>>> {
+        match self.current {
+            Some(node) => unsafe { (*node.as_ptr()).prev },
+            None => self.list.tail
+        }
+    }
+}
+
+/// An iterator for a doubly linked list.
+pub struct DoublyLinkedIter<'a, T> {
+    /// The next node from the front.
+    head: Option<NonNull<DoublyLinkedNode<T>>>,
+    /// The next node from the back.
+    tail: Option<NonNull<DoublyLinkedNode<T>>>,
+    /// Number of items left, so that both ends stop when they meet.
+    len: usize,
+    /// The iterator borrows the list's nodes.
+    marker: PhantomData<&'a DoublyLinkedNode<T>>
+}
+
+impl <'a, T> Iterator for DoublyLinkedIter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+
+        self.head.map(|node| unsafe {
+            let node = &*node.as_ptr();
+            self.head = node.next;
+            self.len -= 1;
+
+            &node.data
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl <'a, T> DoubleEndedIterator for DoublyLinkedIter<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+
+        self.tail.map(|node| unsafe {
+            let node = &*node.as_ptr();
+            self.tail = node.prev;
+            self.len -= 1;
+
+            &node.data
+        })
+    }
+}
+
+/// An iterator which moves the items out of a doubly linked list.
+pub struct DoublyLinkedIntoIter<T>(DoublyLinkedList<T>);
+
+impl <T> Iterator for DoublyLinkedIntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.pop_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.0.len, Some(self.0.len))
+    }
+}
+
+impl <T> DoubleEndedIterator for DoublyLinkedIntoIter<T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.pop_back()
+    }
+}
+
+impl <'a, T> IntoIterator for &'a DoublyLinkedList<T> {
+    type Item = &'a T;
+    type IntoIter = DoublyLinkedIter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl <T> IntoIterator for DoublyLinkedList<T> {
+    type Item = T;
+    type IntoIter = DoublyLinkedIntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        DoublyLinkedIntoIter(self)
+    }
+}
+
+impl <T> FromIterator<T> for DoublyLinkedList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = DoublyLinkedList::new();
+        list.extend(iter);
+
+        list
+    }
+}
+
+impl <T> Extend<T> for DoublyLinkedList<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            self.push_back(item);
+        }
+    }
+}
+
+impl <T: Clone> Clone for DoublyLinkedList<T> {
+    fn clone(&self) -> Self {
+        self.iter().cloned().collect()
+    }
+}
+
+impl <T: Debug> Debug for DoublyLinkedList<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self).finish()
+    }
+}
+
+impl <T: PartialEq> PartialEq for DoublyLinkedList<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other)
+    }
+}
+
+impl <T: Eq> Eq for DoublyLinkedList<T> {}
+
+impl <T> Drop for DoublyLinkedList<T> {
+    fn drop(&mut self) {
+        while self.pop_front().is_some() {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn iterates_from_both_ends() {
+        let mut list: DoublyLinkedList<i32> = (1..=3).collect();
+        list.push_front(0);
+        list.push_back(4);
+
+        assert!(list.iter().eq([0, 1, 2, 3, 4].iter()));
+        assert!(list.iter().rev().eq([4, 3, 2, 1, 0].iter()));
+
+        let mut iter = list.iter();
+        iter.next();
+        iter.next_back();
+        assert_eq!(iter.count(), 3);
+
+        assert_eq!(list.pop_back(), Some(4));
+        assert_eq!(list.pop_front(), Some(0));
+        assert_eq!(list.front(), Some(&1));
+        assert_eq!(list.back(), Some(&3));
+        assert_eq!(list.clone().into_iter().rev().collect::<Vec<_>>(), vec![3, 2, 1]);
+    }
+
+    #[test]
+    fn cursor_edits_around_the_current_item() {
+        let mut list: DoublyLinkedList<i32> = (0..5).collect();
+        let mut cursor = list.cursor_front_mut();
+
+        assert_eq!(cursor.index(), Some(0));
+        cursor.move_next();
+        assert_eq!(cursor.current(), Some(&mut 1));
+
+        cursor.insert_before(10);
+        assert_eq!(cursor.index(), Some(2));
+        cursor.insert_after(11);
+        assert_eq!(cursor.index(), Some(2));
+
+        assert_eq!(cursor.remove_current(), Some(1));
+        assert_eq!(cursor.current(), Some(&mut 11));
+        assert_eq!(cursor.index(), Some(2));
+
+        cursor.splice_before((20..22).collect());
+        assert_eq!(cursor.index(), Some(4));
+        cursor.splice_after((30..32).collect());
+        assert_eq!(cursor.peek_next(), Some(&mut 30));
+        assert_eq!(cursor.peek_prev(), Some(&mut 21));
+
+        assert!(list.iter().eq([0, 10, 20, 21, 11, 30, 31, 2, 3, 4].iter()));
+        assert!(list.iter().rev().eq([4, 3, 2, 31, 30, 11, 21, 20, 10, 0].iter()));
+        assert_eq!(list.len(), 10);
+    }
+
+    #[test]
+    fn cursor_wraps_through_the_empty_position() {
+        let mut list: DoublyLinkedList<i32> = (0..3).collect();
+        let mut cursor = list.cursor_back_mut();
+
+        assert_eq!(cursor.index(), Some(2));
+        cursor.move_next();
+        assert_eq!(cursor.index(), None);
+        assert_eq!(cursor.current(), None);
+
+        // At the empty position, items are inserted before the first or after the last item.
+        cursor.insert_before(3);
+        cursor.insert_after(-1);
+        cursor.splice_after((-3..-1).collect());
+        cursor.splice_before((4..6).collect());
+
+        cursor.move_next();
+        assert_eq!(cursor.index(), Some(0));
+        assert_eq!(cursor.current(), Some(&mut -3));
+
+        cursor.move_prev();
+        assert_eq!(cursor.index(), None);
+        cursor.move_prev();
+        assert_eq!(cursor.index(), Some(8));
+        assert_eq!(cursor.remove_current(), Some(5));
+        assert_eq!(cursor.index(), None);
+
+        assert!(list.iter().eq([-3, -2, -1, 0, 1, 2, 3, 4].iter()));
+        assert!(list.iter().rev().eq([4, 3, 2, 1, 0, -1, -2, -3].iter()));
+    }
+
+    #[test]
+    fn cursor_handles_an_empty_list() {
+        let mut list: DoublyLinkedList<String> = DoublyLinkedList::new();
+        let mut cursor = list.cursor_back_mut();
+
+        assert_eq!(cursor.index(), None);
+        cursor.move_prev();
+        assert_eq!(cursor.remove_current(), None);
+
+        cursor.insert_after(String::from("a"));
+        cursor.move_next();
+        assert_eq!(cursor.remove_current().as_deref(), Some("a"));
+        assert!(list.is_empty());
+
+        list.push_front(String::from("b"));
+        assert_eq!(list.len(), 1);
+        assert_eq!(list.back().map(String::as_str), Some("b"));
+    }
+
+    #[test]
+    fn drops_long_lists() {
+        let list: DoublyLinkedList<u64> = (0..300_000).collect();
+        assert_eq!(list.len(), 300_000);
+    }
+}
@@ -0,0 +1,73 @@
+use std::env;
+use std::fs::{self, File};
+use std::io::BufReader;
+use std::time::Instant;
+use super::hashtable::HashTable;
+use super::hashtable_open::HashTableOpen;
+use super::speller::Words;
+
+/// Times loading words into a table and looking words up in it. Returns the load and lookup times in seconds and the
+/// number of lookups which found their word.
+///
+/// # Arguments
+/// * `words` - The words to load.
+/// * `lookups` - The words to look up.
+/// * `table` - The empty table.
+/// * `add` - Function which adds a word to the table.
+/// * `contains` - Function which looks up a word in the table.
+fn bench<T, A: Fn(&mut T, String), C: Fn(&T, &str) -> bool>(words: &[String], lookups: &[String], mut table: T, add: A, contains: C) -> (f64, f64, usize) {
+    let start = Instant::now();
+
+    for word in words.iter() {
+        add(&mut table, word.clone());
+    }
+
+    let load = start.elapsed().as_secs_f64();
+    let start = Instant::now();
+    let found = lookups.iter().filter(|word| contains(&table, word)).count();
+
+    (load, start.elapsed().as_secs_f64(), found)
+}
+
+/// Compares the chaining and open addressing hash tables on the speller's workload.
+///
+/// Usage: `hashbench [--runs 5] <dictionary> [text]`
+///
+/// Loads the dictionary's words into each table and looks up the text's words, or the dictionary's words and as many
+/// missing words if there is no text. Prints one CSV row per table and run with the elapsed times.
+pub fn main() {
+    let mut runs = 1;
+    let mut filenames = Vec::new();
+    let mut args = env::args().skip(1);
+
+    // Parses the command line flags.
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--runs" => runs = args.next().and_then(|runs| runs.parse().ok()).expect("Runs must be a non-negative integer."),
+            _ => filenames.push(arg)
+        }
+    }
+
+    let (dictionary, text) = match &filenames[..] {
+        [dictionary] => (dictionary, None),
+        [dictionary, text] => (dictionary, Some(text)),
+        _ => panic!("Usage:\n ./hashbench [--runs 5] <dictionary> [text]")
+    };
+
+    let words: Vec<String> = fs::read_to_string(dictionary).unwrap().lines().map(str::to_lowercase).collect();
+
+    let lookups: Vec<String> = match text {
+        Some(text) => Words::new(BufReader::new(File::open(text).unwrap())).collect::<Result<_, _>>().unwrap(),
+        None => words.iter().cloned().chain(words.iter().map(|word| format!("{word}#"))).collect()
+    };
+
+    println!("table,run,words,lookups,found,load_seconds,lookup_seconds");
+
+    for run in 1..=runs {
+        let (load, lookup, found) = bench(&words, &lookups, HashTable::new(), |table, word| { table.add(word); }, |table, word| table.contains(word));
+        println!("chaining,{run},{},{},{found},{load},{lookup}", words.len(), lookups.len());
+
+        let (load, lookup, found) = bench(&words, &lookups, HashTableOpen::new(), |table, word| { table.add(word); }, |table, word| table.contains(word));
+        println!("open,{run},{},{},{found},{load},{lookup}", words.len(), lookups.len());
+    }
+}
@@ -0,0 +1,427 @@
+use std::borrow::Borrow;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::mem;
+use super::list::List;
+
+/// Computes the smallest prime number which is not less than the given capacity. Prime capacities spread the keys
+/// more evenly across the table.
+///
+/// # Arguments
+/// * `current` - The requested capacity.
+pub fn next_prime(current: usize) -> usize {
+    (current..).find(|&i| is_prime(i)).unwrap()
+}
+
+/// Finds if a number is prime.
+///
+/// # Arguments
+/// * `n` - The number.
+fn is_prime(n: usize) -> bool {
+    n > 1 && (2..).take_while(|i| i * i <= n).all(|i| n % i > 0)
+}
+
+/// A custom hash table which associates unique keys to values. Collisions are resolved by chaining the entries
+/// which fall in the same cell into a linked list.
+/// A table whose values are `()` works as a set of unique keys.
+pub struct HashTable<K, V> {
+    /// Vec which contains the actual table with data. Each cell holds the key-value pairs whose hash is the cell's index.
+    table: Vec<List<(K, V)>>,
+    /// Size of the table in cells.
+    capacity: usize,
+    /// Number of items in the table.
+    len: usize,
+    /// The table grows when the ratio between the number of items and the capacity would exceed this value.
+    max_load_factor: f64
+}
+
+impl <K: Hash + Eq, V> HashTable<K, V> {
+    /// Default capacity.
+    const BASE_CAPACITY: usize = 19;
+    /// Default maximum load factor.
+    const DEFAULT_MAX_LOAD_FACTOR: f64 = 1.0;
+
+    /// Creates a new hashtable with the supplied capacity and the default maximum load factor.
+    ///
+    /// # Arguments
+    /// * `capacity` - The table's capacity.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::with_capacity_and_load_factor(capacity, Self::DEFAULT_MAX_LOAD_FACTOR)
+    }
+
+    /// Creates a new hashtable with the supplied capacity and maximum load factor.
+    ///
+    /// # Arguments
+    /// * `capacity` - The table's capacity.
+    /// * `max_load_factor` - The table grows when the ratio between the number of items and the capacity would exceed this value. Must be positive.
+    pub fn with_capacity_and_load_factor(mut capacity: usize, max_load_factor: f64) -> Self {
+        assert!(max_load_factor > 0.0, "The maximum load factor must be positive");
+        capacity = next_prime(capacity);
+
+        Self {
+            table: (0..capacity).map(|_| List::new()).collect(),
+            capacity,
+            len: 0,
+            max_load_factor
+        }
+    }
+
+    /// Creates a new hashtable with the default capacity.
+    pub fn new() -> Self {
+        Self::with_capacity(Self::BASE_CAPACITY)
+    }
+
+    /// Creates a hashtable from the cells of another table, taken with `into_cells` or copied from `cells`, without
+    /// hashing the keys again. Each key must be in the cell whose index is the key's hash, otherwise it won't be found.
+    ///
+    /// # Arguments
+    /// * `table` - The cells. Must not be empty.
+    pub fn from_cells(table: Vec<List<(K, V)>>) -> Self {
+        assert!(!table.is_empty(), "A hashtable must have at least one cell");
+        let len = table.iter().map(List::len).sum();
+
+        Self {
+            capacity: table.len(),
+            table,
+            len,
+            max_load_factor: Self::DEFAULT_MAX_LOAD_FACTOR
+        }
+    }
+
+    /// The table's cells, each one holds the key-value pairs whose hash is the cell's index.
+    pub fn cells(&self) -> &[List<(K, V)>] {
+        &self.table
+    }
+
+    /// Consumes the table and returns its cells.
+    pub fn into_cells(self) -> Vec<List<(K, V)>> {
+        self.table
+    }
+
+    /// Number of items in the table.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Checks if the table has no items.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Size of the table in cells.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Number of cells, or buckets, in the table. Same as `capacity`.
+    pub fn bucket_count(&self) -> usize {
+        self.capacity
+    }
+
+    /// Counts the cells by the length of their chains. The count at index `i` is the number of cells with `i` items,
+    /// a good hash function leaves few long chains.
+    pub fn chain_length_histogram(&self) -> Vec<usize> {
+        let mut histogram = Vec::new();
+
+        for chain in self.table.iter() {
+            if chain.len() >= histogram.len() {
+                histogram.resize(chain.len() + 1, 0);
+            }
+
+            histogram[chain.len()] += 1;
+        }
+
+        histogram
+    }
+
+    /// Ratio between the number of items and the capacity, which is the average length of the chains.
+    pub fn load_factor(&self) -> f64 {
+        self.len as f64 / self.capacity as f64
+    }
+
+    /// The load factor which the table does not exceed.
+    pub fn max_load_factor(&self) -> f64 {
+        self.max_load_factor
+    }
+
+    /// Iterates over the table's key-value pairs, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.table.iter().flatten().map(|(key, value)| (key, value))
+    }
+
+    /// Associates a value to a key. Returns the key's previous value, if the key was already in the table.
+    ///
+    /// # Arguments
+    /// * `key` - The key.
+    /// * `value` - The value.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        match self.get_mut(&key) {
+            Some(current) => Some(mem::replace(current, value)),
+            None => {
+                self.push(key, value);
+                None
+            }
+        }
+    }
+
+    /// Gets a mutable reference to a key's value, inserting the value returned by `default` if the key is not in the table.
+    ///
+    /// # Arguments
+    /// * `key` - The key.
+    /// * `default` - Function which creates the value if the key is not in the table.
+    pub fn get_or_insert_with<F: FnOnce() -> V>(&mut self, key: K, default: F) -> &mut V {
+        let hash = self.hash(&key);
+
+        match (&self.table[hash]).into_iter().position(|(item_key, _)| *item_key == key) {
+            Some(position) => &mut self.table[hash].iter_mut().nth(position).unwrap().1,
+            None => {
+                let hash = self.push(key, default());
+                &mut self.table[hash].iter_mut().last().unwrap().1
+            }
+        }
+    }
+
+    /// Gets a reference to a key's value.
+    ///
+    /// # Arguments
+    /// * `key` - The key, or a borrowed form of it such as a `&str` for `String` keys.
+    pub fn get<Q: Hash + Eq + ?Sized>(&self, key: &Q) -> Option<&V> where K: Borrow<Q> {
+        let hash = self.hash(key);
+
+        (&self.table[hash])
+            .into_iter()
+            .find(|(item_key, _)| item_key.borrow() == key)
+            .map(|(_, value)| value)
+    }
+
+    /// Gets a mutable reference to a key's value.
+    ///
+    /// # Arguments
+    /// * `key` - The key, or a borrowed form of it such as a `&str` for `String` keys.
+    pub fn get_mut<Q: Hash + Eq + ?Sized>(&mut self, key: &Q) -> Option<&mut V> where K: Borrow<Q> {
+        let hash = self.hash(key);
+
+        self.table[hash]
+            .iter_mut()
+            .find(|(item_key, _)| item_key.borrow() == key)
+            .map(|(_, value)| value)
+    }
+
+    /// Checks if a key is in the hashtable.
+    ///
+    /// # Arguments
+    /// * `key` - The key, or a borrowed form of it such as a `&str` for `String` keys.
+    pub fn contains<Q: Hash + Eq + ?Sized>(&self, key: &Q) -> bool where K: Borrow<Q> {
+        self.get(key).is_some()
+    }
+
+    /// Removes a key from the table. Returns the key's value, if the key was in the table.
+    ///
+    /// # Arguments
+    /// * `key` - The key, or a borrowed form of it such as a `&str` for `String` keys.
+    pub fn remove<Q: Hash + Eq + ?Sized>(&mut self, key: &Q) -> Option<V> where K: Borrow<Q> {
+        let hash = self.hash(key);
+        let (_, value) = self.table[hash].remove_first(|(item_key, _)| item_key.borrow() == key)?;
+        self.len -= 1;
+
+        Some(value)
+    }
+
+    /// Removes every item from the table, keeping its capacity.
+    pub fn clear(&mut self) {
+        for chain in self.table.iter_mut() {
+            *chain = List::new();
+        }
+
+        self.len = 0;
+    }
+
+    /// Moves every item into the smallest table which holds them without exceeding the maximum load factor.
+    pub fn shrink_to_fit(&mut self) {
+        let capacity = next_prime((self.len as f64 / self.max_load_factor).ceil() as usize);
+
+        if capacity < self.capacity {
+            self.resize(capacity);
+        }
+    }
+
+    /// Adds a key which is not in the table, growing the table first if the maximum load factor would be exceeded.
+    /// Returns the index of the key's cell.
+    ///
+    /// # Arguments
+    /// * `key` - The key.
+    /// * `value` - The key's value.
+    fn push(&mut self, key: K, value: V) -> usize {
+        if (self.len + 1) as f64 > self.max_load_factor * self.capacity as f64 {
+            self.grow();
+        }
+
+        let hash = self.hash(&key);
+        self.len += 1;
+        self.table[hash].add((key, value));
+
+        hash
+    }
+
+    /// Moves every item into a table with at least twice as many cells, so that chains stay short.
+    fn grow(&mut self) {
+        self.resize(next_prime(self.capacity * 2));
+    }
+
+    /// Moves every item into a table with a different number of cells.
+    ///
+    /// # Arguments
+    /// * `capacity` - The new table's capacity.
+    fn resize(&mut self, capacity: usize) {
+        let table = mem::replace(&mut self.table, (0..capacity).map(|_| List::new()).collect());
+        self.capacity = capacity;
+
+        for mut chain in table {
+            for (key, value) in chain.drain_to_vec() {
+                let hash = self.hash(&key);
+                self.table[hash].add((key, value));
+            }
+        }
+    }
+
+    /// Computes a key's hash value, which is the index of the key's cell in the table.
+    ///
+    /// # Arguments
+    /// * `key` - The key.
+    fn hash<Q: Hash + ?Sized>(&self, key: &Q) -> usize {
+        let mut hasher = DefaultHasher::new();
+        key.hash(&mut hasher);
+
+        (hasher.finish() % self.capacity as u64) as usize
+    }
+}
+
+impl <K: Hash + Eq> HashTable<K, ()> {
+    /// Adds an item to the hashtable. Returns `false` if the item was already in the table.
+    ///
+    /// # Arguments
+    /// * `item` - The item to add.
+    pub fn add(&mut self, item: K) -> bool {
+        self.insert(item, ()).is_none()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stores_any_hashable_key() {
+        let mut table: HashTable<String, u32> = HashTable::with_capacity(0);
+
+        assert_eq!(table.insert(String::from("a"), 1), None);
+        assert_eq!(table.insert(String::from("a"), 2), Some(1));
+        assert!(table.contains("a"));
+        assert!(!table.contains("b"));
+        assert_eq!(table.len(), 1);
+
+        let mut set: HashTable<(i32, char), ()> = HashTable::new();
+
+        for i in 0..1000 {
+            assert!(set.add((i, 'x')));
+        }
+
+        assert!(!set.add((5, 'x')));
+        assert_eq!(set.len(), 1000);
+        assert!(set.contains(&(999, 'x')));
+    }
+
+    #[test]
+    fn grows_to_keep_the_load_factor() {
+        let mut table: HashTable<u32, u32> = HashTable::new();
+
+        for i in 0..100_000 {
+            table.insert(i, i * 2);
+            assert!(table.load_factor() <= table.max_load_factor());
+        }
+
+        assert_eq!(table.len(), 100_000);
+        assert!(table.capacity() >= 100_000);
+        assert!((0..100_000).all(|i| table.get(&i) == Some(&(i * 2))));
+        assert!(!table.contains(&100_000));
+    }
+
+    #[test]
+    fn respects_a_custom_load_factor() {
+        let mut table: HashTable<u32, ()> = HashTable::with_capacity_and_load_factor(5, 0.5);
+
+        for i in 0..10 {
+            table.add(i);
+        }
+
+        assert_eq!(table.max_load_factor(), 0.5);
+        assert!(table.load_factor() <= 0.5);
+    }
+
+    #[test]
+    fn counts_words_as_a_map() {
+        let mut table: HashTable<String, u32> = HashTable::with_capacity(2);
+
+        for word in "a b a c b a d e f g h i j k a".split(' ') {
+            *table.get_or_insert_with(word.to_string(), || 0) += 1;
+        }
+
+        assert_eq!(table.get("a"), Some(&4));
+        assert_eq!(table.get("b"), Some(&2));
+        assert_eq!(table.len(), 11);
+
+        *table.get_mut("k").unwrap() = 9;
+        assert_eq!(table.get("k"), Some(&9));
+
+        assert_eq!(table.remove("a"), Some(4));
+        assert_eq!(table.remove("a"), None);
+        assert!(!table.contains("a"));
+        assert_eq!(table.len(), 10);
+    }
+
+    #[test]
+    fn histogram_counts_every_bucket() {
+        let mut table: HashTable<u32, u32> = HashTable::new();
+
+        for i in 0..1000 {
+            table.insert(i, i);
+        }
+
+        let histogram = table.chain_length_histogram();
+
+        assert_eq!(histogram.iter().sum::<usize>(), table.bucket_count());
+        assert_eq!(histogram.iter().enumerate().map(|(length, count)| length * count).sum::<usize>(), 1000);
+    }
+
+    #[test]
+    fn shrinks_and_clears() {
+        let mut table: HashTable<u32, u32> = HashTable::new();
+        assert!(table.is_empty());
+
+        for i in 0..1000 {
+            table.insert(i, i);
+        }
+
+        for i in 0..990 {
+            table.remove(&i);
+        }
+
+        table.shrink_to_fit();
+        assert_eq!(table.capacity(), 11);
+        assert!((990..1000).all(|i| table.get(&i) == Some(&i)));
+
+        table.clear();
+        assert!(table.is_empty());
+        assert_eq!(table.capacity(), 11);
+        assert_eq!(table.get(&995), None);
+
+        table.shrink_to_fit();
+        assert_eq!(table.capacity(), 2);
+
+        for i in 1..=3 {
+            table.insert(i, i);
+        }
+
+        assert_eq!(table.len(), 3);
+    }
+}
@@ -0,0 +1,394 @@
+use std::borrow::Borrow;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::mem;
+use super::hashtable::next_prime;
+
+/// An entry of an open addressing hash table.
+struct Slot<K, V> {
+    /// The key.
+    key: K,
+    /// The key's value.
+    value: V,
+    /// Distance from the key's cell, which is the index given by its hash, to the cell the entry is in.
+    distance: usize
+}
+
+/// A custom hash table which associates unique keys to values, with the same interface as `HashTable`.
+/// Collisions are resolved with open addressing: entries are stored in the table itself and an entry whose cell is
+/// taken goes into one of the following cells. Uses robin hood hashing, entries far from their cell take the place
+/// of entries closer to theirs, which keeps every probe short.
+/// A table whose values are `()` works as a set of unique keys.
+pub struct HashTableOpen<K, V> {
+    /// Vec which contains the actual table with data, `None` for empty cells.
+    table: Vec<Option<Slot<K, V>>>,
+    /// Size of the table in cells.
+    capacity: usize,
+    /// Number of items in the table.
+    len: usize,
+    /// The table grows when the ratio between the number of items and the capacity would exceed this value.
+    max_load_factor: f64
+}
+
+impl <K: Hash + Eq, V> HashTableOpen<K, V> {
+    /// Default capacity.
+    const BASE_CAPACITY: usize = 19;
+    /// Default maximum load factor. Probes get long as an open addressing table fills up.
+    const DEFAULT_MAX_LOAD_FACTOR: f64 = 0.8;
+
+    /// Creates a new hashtable with the supplied capacity and the default maximum load factor.
+    ///
+    /// # Arguments
+    /// * `capacity` - The table's capacity.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::with_capacity_and_load_factor(capacity, Self::DEFAULT_MAX_LOAD_FACTOR)
+    }
+
+    /// Creates a new hashtable with the supplied capacity and maximum load factor.
+    ///
+    /// # Arguments
+    /// * `capacity` - The table's capacity.
+    /// * `max_load_factor` - The table grows when the ratio between the number of items and the capacity would exceed this value. Must be between 0 and 1, since every item takes a cell.
+    pub fn with_capacity_and_load_factor(capacity: usize, max_load_factor: f64) -> Self {
+        assert!(max_load_factor > 0.0 && max_load_factor < 1.0, "The maximum load factor must be between 0 and 1");
+        let capacity = next_prime(capacity);
+
+        Self {
+            table: (0..capacity).map(|_| None).collect(),
+            capacity,
+            len: 0,
+            max_load_factor
+        }
+    }
+
+    /// Creates a new hashtable with the default capacity.
+    pub fn new() -> Self {
+        Self::with_capacity(Self::BASE_CAPACITY)
+    }
+
+    /// Number of items in the table.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Checks if the table has no items.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Size of the table in cells.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Number of cells, or buckets, in the table. Same as `capacity`.
+    pub fn bucket_count(&self) -> usize {
+        self.capacity
+    }
+
+    /// Ratio between the number of items and the capacity.
+    pub fn load_factor(&self) -> f64 {
+        self.len as f64 / self.capacity as f64
+    }
+
+    /// The load factor which the table does not exceed.
+    pub fn max_load_factor(&self) -> f64 {
+        self.max_load_factor
+    }
+
+    /// Counts the items by their distance to their cell. The count at index `i` is the number of items which are
+    /// found after probing `i` cells past their own, a good hash function leaves few long probes.
+    pub fn probe_length_histogram(&self) -> Vec<usize> {
+        let mut histogram = Vec::new();
+
+        for slot in self.table.iter().flatten() {
+            if slot.distance >= histogram.len() {
+                histogram.resize(slot.distance + 1, 0);
+            }
+
+            histogram[slot.distance] += 1;
+        }
+
+        histogram
+    }
+
+    /// Iterates over the table's key-value pairs, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.table.iter().flatten().map(|slot| (&slot.key, &slot.value))
+    }
+
+    /// Associates a value to a key. Returns the key's previous value, if the key was already in the table.
+    ///
+    /// # Arguments
+    /// * `key` - The key.
+    /// * `value` - The value.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        match self.get_mut(&key) {
+            Some(current) => Some(mem::replace(current, value)),
+            None => {
+                self.push(key, value);
+                None
+            }
+        }
+    }
+
+    /// Gets a mutable reference to a key's value, inserting the value returned by `default` if the key is not in the table.
+    ///
+    /// # Arguments
+    /// * `key` - The key.
+    /// * `default` - Function which creates the value if the key is not in the table.
+    pub fn get_or_insert_with<F: FnOnce() -> V>(&mut self, key: K, default: F) -> &mut V {
+        let index = match self.find(&key) {
+            Some(index) => index,
+            None => self.push(key, default())
+        };
+
+        &mut self.table[index].as_mut().unwrap().value
+    }
+
+    /// Gets a reference to a key's value.
+    ///
+    /// # Arguments
+    /// * `key` - The key, or a borrowed form of it such as a `&str` for `String` keys.
+    pub fn get<Q: Hash + Eq + ?Sized>(&self, key: &Q) -> Option<&V> where K: Borrow<Q> {
+        let index = self.find(key)?;
+        self.table[index].as_ref().map(|slot| &slot.value)
+    }
+
+    /// Gets a mutable reference to a key's value.
+    ///
+    /// # Arguments
+    /// * `key` - The key, or a borrowed form of it such as a `&str` for `String` keys.
+    pub fn get_mut<Q: Hash + Eq + ?Sized>(&mut self, key: &Q) -> Option<&mut V> where K: Borrow<Q> {
+        let index = self.find(key)?;
+        self.table[index].as_mut().map(|slot| &mut slot.value)
+    }
+
+    /// Checks if a key is in the hashtable.
+    ///
+    /// # Arguments
+    /// * `key` - The key, or a borrowed form of it such as a `&str` for `String` keys.
+    pub fn contains<Q: Hash + Eq + ?Sized>(&self, key: &Q) -> bool where K: Borrow<Q> {
+        self.find(key).is_some()
+    }
+
+    /// Removes a key from the table. Returns the key's value, if the key was in the table.
+    /// The entries after the removed one are shifted back, so that no probe stops early at the empty cell.
+    ///
+    /// # Arguments
+    /// * `key` - The key, or a borrowed form of it such as a `&str` for `String` keys.
+    pub fn remove<Q: Hash + Eq + ?Sized>(&mut self, key: &Q) -> Option<V> where K: Borrow<Q> {
+        let mut index = self.find(key)?;
+        let removed = self.table[index].take().unwrap();
+        self.len -= 1;
+
+        loop {
+            let next = (index + 1) % self.capacity;
+
+            match self.table[next].take() {
+                Some(mut slot) if slot.distance > 0 => {
+                    slot.distance -= 1;
+                    self.table[index] = Some(slot);
+                    index = next;
+                },
+                slot => {
+                    self.table[next] = slot;
+                    break;
+                }
+            }
+        }
+
+        Some(removed.value)
+    }
+
+    /// Removes every item from the table, keeping its capacity.
+    pub fn clear(&mut self) {
+        for slot in self.table.iter_mut() {
+            *slot = None;
+        }
+
+        self.len = 0;
+    }
+
+    /// Moves every item into the smallest table which holds them without exceeding the maximum load factor.
+    pub fn shrink_to_fit(&mut self) {
+        let capacity = next_prime((self.len as f64 / self.max_load_factor).ceil() as usize);
+
+        if capacity < self.capacity {
+            self.resize(capacity);
+        }
+    }
+
+    /// Finds the index of the cell which holds a key.
+    ///
+    /// # Arguments
+    /// * `key` - The key.
+    fn find<Q: Hash + Eq + ?Sized>(&self, key: &Q) -> Option<usize> where K: Borrow<Q> {
+        let mut index = self.hash(key);
+
+        for distance in 0..self.capacity {
+            match &self.table[index] {
+                None => return None,
+                // The key would have taken this entry's place if it was in the table.
+                Some(slot) if slot.distance < distance => return None,
+                Some(slot) if slot.key.borrow() == key => return Some(index),
+                _ => index = (index + 1) % self.capacity
+            }
+        }
+
+        None
+    }
+
+    /// Adds a key which is not in the table, growing the table first if the maximum load factor would be exceeded.
+    /// Returns the index of the key's cell.
+    ///
+    /// # Arguments
+    /// * `key` - The key.
+    /// * `value` - The key's value.
+    fn push(&mut self, key: K, value: V) -> usize {
+        if (self.len + 1) as f64 > self.max_load_factor * self.capacity as f64 {
+            self.grow();
+        }
+
+        self.place(key, value)
+    }
+
+    /// Puts a key which is not in the table into the first cell after its own which holds an entry closer to its
+    /// cell, moving that entry forward in the same way. Returns the index of the key's cell.
+    ///
+    /// # Arguments
+    /// * `key` - The key.
+    /// * `value` - The key's value.
+    fn place(&mut self, key: K, value: V) -> usize {
+        let mut index = self.hash(&key);
+        let mut slot = Slot { key, value, distance: 0 };
+        let mut placed = None;
+        self.len += 1;
+
+        loop {
+            match &mut self.table[index] {
+                None => {
+                    self.table[index] = Some(slot);
+                    return placed.unwrap_or(index);
+                },
+                Some(current) if current.distance < slot.distance => {
+                    mem::swap(current, &mut slot);
+                    placed.get_or_insert(index);
+                },
+                _ => ()
+            }
+
+            index = (index + 1) % self.capacity;
+            slot.distance += 1;
+        }
+    }
+
+    /// Moves every item into a table with at least twice as many cells, so that probes stay short.
+    fn grow(&mut self) {
+        self.resize(next_prime(self.capacity * 2));
+    }
+
+    /// Moves every item into a table with a different number of cells.
+    ///
+    /// # Arguments
+    /// * `capacity` - The new table's capacity.
+    fn resize(&mut self, capacity: usize) {
+        let table = mem::replace(&mut self.table, (0..capacity).map(|_| None).collect());
+        self.capacity = capacity;
+        self.len = 0;
+
+        for slot in table.into_iter().flatten() {
+            self.place(slot.key, slot.value);
+        }
+    }
+
+    /// Computes a key's hash value, which is the index of the key's cell in the table.
+    ///
+    /// # Arguments
+    /// * `key` - The key.
+    fn hash<Q: Hash + ?Sized>(&self, key: &Q) -> usize {
+        let mut hasher = DefaultHasher::new();
+        key.hash(&mut hasher);
+
+        (hasher.finish() % self.capacity as u64) as usize
+    }
+}
+
+impl <K: Hash + Eq> HashTableOpen<K, ()> {
+    /// Adds an item to the hashtable. Returns `false` if the item was already in the table.
+    ///
+    /// # Arguments
+    /// * `item` - The item to add.
+    pub fn add(&mut self, item: K) -> bool {
+        self.insert(item, ()).is_none()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use rand::{Rng, SeedableRng};
+    use rand::rngs::StdRng;
+
+    #[test]
+    fn matches_hash_map_on_random_operations() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut table: HashTableOpen<u32, u32> = HashTableOpen::new();
+        let mut expected: HashMap<u32, u32> = HashMap::new();
+
+        for _ in 0..200_000 {
+            let key = rng.gen_range(0..3000);
+
+            match rng.gen_range(0..4) {
+                0 => assert_eq!(table.insert(key, key * 2), expected.insert(key, key * 2)),
+                1 => assert_eq!(table.remove(&key), expected.remove(&key)),
+                2 => assert_eq!(table.get(&key), expected.get(&key)),
+                _ => {
+                    *table.get_or_insert_with(key, || 1) += 1;
+                    *expected.entry(key).or_insert(1) += 1;
+                }
+            }
+
+            assert_eq!(table.len(), expected.len());
+        }
+
+        assert!(expected.iter().all(|(key, value)| table.get(key) == Some(value)));
+        assert_eq!(table.iter().count(), expected.len());
+        assert_eq!(table.probe_length_histogram().iter().sum::<usize>(), expected.len());
+    }
+
+    #[test]
+    fn shrinks_and_clears() {
+        let mut table: HashTableOpen<u32, u32> = HashTableOpen::new();
+
+        for i in 0..3000 {
+            table.insert(i, i);
+        }
+
+        for i in (0..3000).filter(|i| i % 10 != 0) {
+            table.remove(&i);
+        }
+
+        let capacity = table.capacity();
+        table.shrink_to_fit();
+
+        assert!(table.capacity() < capacity);
+        assert!(table.load_factor() <= table.max_load_factor());
+        assert!((0..3000).step_by(10).all(|i| table.get(&i) == Some(&i)));
+
+        table.clear();
+        assert!(table.is_empty());
+        assert!(!table.contains(&10));
+    }
+
+    #[test]
+    fn adds_items_once() {
+        let mut set: HashTableOpen<String, ()> = HashTableOpen::new();
+
+        assert!(set.add(String::from("a")));
+        assert!(!set.add(String::from("a")));
+        assert!(set.contains("a"));
+        assert_eq!(set.len(), 1);
+    }
+}
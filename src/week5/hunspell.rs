@@ -0,0 +1,274 @@
+use std::fs;
+use std::io::{self, ErrorKind};
+use regex::Regex;
+use super::hashtable::HashTable;
+
+/// How the flags of words and affix rules are written in a Hunspell dictionary.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlagType {
+    /// Every character is a flag. The default.
+    Char,
+    /// Every two characters are a flag.
+    Long,
+    /// Flags are numbers separated by commas.
+    Numeric
+}
+
+impl FlagType {
+    /// Splits a list of flags.
+    ///
+    /// # Arguments
+    /// * `flags` - The flags, as written after a word's slash.
+    pub fn split(self, flags: &str) -> Vec<String> {
+        match self {
+            FlagType::Char => flags.chars().map(String::from).collect(),
+            FlagType::Long => {
+                let chars: Vec<char> = flags.chars().collect();
+                chars.chunks(2).map(|flag| flag.iter().collect()).collect()
+            },
+            FlagType::Numeric => flags.split(',').map(|flag| flag.trim().to_string()).filter(|flag| !flag.is_empty()).collect()
+        }
+    }
+}
+
+/// A rule which adds a prefix or a suffix to a word.
+#[derive(Debug, Clone)]
+struct AffixRule {
+    /// Characters removed from the word before adding the affix.
+    strip: String,
+    /// The affix.
+    add: String,
+    /// Pattern which the word must match for the rule to apply, anchored at the word's start for prefixes and at
+    /// its end for suffixes.
+    condition: Regex,
+    /// If prefixes and suffixes can be applied to the same word.
+    cross_product: bool
+}
+
+impl AffixRule {
+    /// Applies the rule to a word as a prefix. Returns `None` if the word does not meet the rule's condition.
+    ///
+    /// # Arguments
+    /// * `word` - The word.
+    fn prefix(&self, word: &str) -> Option<String> {
+        if !self.condition.is_match(word) {
+            return None;
+        }
+
+        word.strip_prefix(self.strip.as_str()).map(|rest| format!("{}{}", self.add, rest))
+    }
+
+    /// Applies the rule to a word as a suffix. Returns `None` if the word does not meet the rule's condition.
+    ///
+    /// # Arguments
+    /// * `word` - The word.
+    fn suffix(&self, word: &str) -> Option<String> {
+        if !self.condition.is_match(word) {
+            return None;
+        }
+
+        word.strip_suffix(self.strip.as_str()).map(|rest| format!("{}{}", rest, self.add))
+    }
+}
+
+/// The prefix and suffix rules of a Hunspell affix (.aff) file.
+/// Only UTF-8 files are supported, and options other than `FLAG`, `PFX` and `SFX` are ignored.
+pub struct Affixes {
+    /// How flags are written.
+    flag_type: FlagType,
+    /// Prefix rules by flag.
+    prefixes: HashTable<String, Vec<AffixRule>>,
+    /// Suffix rules by flag.
+    suffixes: HashTable<String, Vec<AffixRule>>
+}
+
+impl Affixes {
+    /// Loads an affix file.
+    ///
+    /// # Arguments
+    /// * `path` - The file's name.
+    pub fn load(path: &str) -> Result<Self, io::Error> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    /// Parses the contents of an affix file.
+    ///
+    /// # Arguments
+    /// * `text` - The file's contents.
+    pub fn parse(text: &str) -> Result<Self, io::Error> {
+        let mut affixes = Affixes {
+            flag_type: FlagType::Char,
+            prefixes: HashTable::new(),
+            suffixes: HashTable::new()
+        };
+
+        // Cross product option and number of rules left in the current rule group.
+        let mut cross_product = false;
+        let mut remaining = 0;
+
+        for (number, line) in text.lines().enumerate() {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let invalid = || io::Error::new(ErrorKind::InvalidData, format!("Invalid affix rule on line {}", number + 1));
+
+            match fields[..] {
+                ["FLAG", flag_type, ..] => affixes.flag_type = match flag_type {
+                    "long" => FlagType::Long,
+                    "num" => FlagType::Numeric,
+                    _ => FlagType::Char
+                },
+                [kind @ ("PFX" | "SFX"), flag, cross, count, ..] if remaining == 0 => {
+                    cross_product = cross == "Y";
+                    remaining = count.parse().map_err(|_| invalid())?;
+                    let rules = if kind == "PFX" { &mut affixes.prefixes } else { &mut affixes.suffixes };
+                    rules.get_or_insert_with(flag.to_string(), Vec::new);
+                },
+                [kind @ ("PFX" | "SFX"), flag, strip, add, ..] => {
+                    let condition = fields.get(4).copied().unwrap_or(".");
+                    let pattern = Self::condition_pattern(condition);
+                    let pattern = if kind == "PFX" { format!("^{}", pattern) } else { format!("{}$", pattern) };
+
+                    let rule = AffixRule {
+                        strip: if strip == "0" { String::new() } else { strip.to_string() },
+                        // Continuation flags after the affix are not supported.
+                        add: match add.split('/').next() {
+                            Some("0") | None => String::new(),
+                            Some(add) => add.to_string()
+                        },
+                        condition: Regex::new(&pattern).map_err(|_| invalid())?,
+                        cross_product
+                    };
+
+                    let rules = if kind == "PFX" { &mut affixes.prefixes } else { &mut affixes.suffixes };
+                    rules.get_or_insert_with(flag.to_string(), Vec::new).push(rule);
+                    remaining -= 1;
+                },
+                ["PFX" | "SFX", ..] => return Err(invalid()),
+                _ => ()
+            }
+        }
+
+        Ok(affixes)
+    }
+
+    /// Converts the condition of an affix rule into a regex pattern. Conditions only have characters, `.` and
+    /// bracketed character classes.
+    ///
+    /// # Arguments
+    /// * `condition` - The condition.
+    fn condition_pattern(condition: &str) -> String {
+        let mut in_class = false;
+
+        condition
+            .chars()
+            .map(|c| match c {
+                '[' => { in_class = true; c.to_string() },
+                ']' => { in_class = false; c.to_string() },
+                '.' if !in_class => c.to_string(),
+                '^' if in_class => c.to_string(),
+                _ => regex::escape(&c.to_string())
+            })
+            .collect()
+    }
+
+    /// Expands a dictionary entry into the word and every form created by applying its affix rules.
+    ///
+    /// # Arguments
+    /// * `entry` - A line of a dictionary (.dic) file, like `walk/GS`.
+    pub fn expand(&self, entry: &str) -> Vec<String> {
+        let entry = entry.split_whitespace().next().unwrap_or("");
+
+        let (word, flags) = match entry.split_once('/') {
+            Some((word, flags)) => (word, self.flag_type.split(flags)),
+            None => (entry, Vec::new())
+        };
+
+        let prefixes: Vec<&AffixRule> = flags.iter().filter_map(|flag| self.prefixes.get(flag)).flatten().collect();
+        let suffixes: Vec<&AffixRule> = flags.iter().filter_map(|flag| self.suffixes.get(flag)).flatten().collect();
+        let mut words = vec![word.to_string()];
+
+        for suffix in suffixes.iter() {
+            if let Some(suffixed) = suffix.suffix(word) {
+                if suffix.cross_product {
+                    for prefix in prefixes.iter().filter(|prefix| prefix.cross_product) {
+                        words.extend(prefix.prefix(&suffixed));
+                    }
+                }
+
+                words.push(suffixed);
+            }
+        }
+
+        words.extend(prefixes.iter().filter_map(|prefix| prefix.prefix(word)));
+        words
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Affix rules for the plural, past tense and negation of English words.
+    const ENGLISH: &str = "\
+SET UTF-8
+# Plurals.
+SFX S Y 2
+SFX S y ies [^aeiou]y
+SFX S 0 s [^y]
+
+PFX U Y 1
+PFX U 0 un .
+
+SFX D N 1
+SFX D 0 ed .
+";
+
+    #[test]
+    fn applies_suffixes_with_strip_and_condition() {
+        let affixes = Affixes::parse(ENGLISH).unwrap();
+
+        assert_eq!(affixes.expand("fly/S"), vec!["fly", "flies"]);
+        assert_eq!(affixes.expand("cat/S"), vec!["cat", "cats"]);
+        assert_eq!(affixes.expand("day/S"), vec!["day"]);
+        assert_eq!(affixes.expand("dog"), vec!["dog"]);
+    }
+
+    #[test]
+    fn combines_prefixes_and_suffixes_with_cross_product() {
+        let affixes = Affixes::parse(ENGLISH).unwrap();
+
+        assert_eq!(affixes.expand("do/U"), vec!["do", "undo"]);
+        assert_eq!(affixes.expand("do/US"), vec!["do", "undos", "dos", "undo"]);
+        // The past tense rule doesn't allow cross products, so there is no "unlocked".
+        assert_eq!(affixes.expand("lock/UD"), vec!["lock", "locked", "unlock"]);
+    }
+
+    #[test]
+    fn splits_long_and_numeric_flags() {
+        assert_eq!(FlagType::Char.split("AB"), vec!["A", "B"]);
+        assert_eq!(FlagType::Long.split("AaBbC"), vec!["Aa", "Bb", "C"]);
+        assert_eq!(FlagType::Numeric.split("1, 22,,3"), vec!["1", "22", "3"]);
+
+        let long = Affixes::parse("FLAG long\nSFX Aa Y 1\nSFX Aa 0 s .\n").unwrap();
+        assert_eq!(long.expand("cat/AaBb"), vec!["cat", "cats"]);
+
+        let numeric = Affixes::parse("FLAG num\nSFX 12 Y 1\nSFX 12 0 s .\n").unwrap();
+        assert_eq!(numeric.expand("dog/7,12"), vec!["dog", "dogs"]);
+        assert_eq!(numeric.expand("dog/1,2"), vec!["dog"]);
+    }
+
+    #[test]
+    fn converts_conditions_to_patterns() {
+        assert_eq!(Affixes::condition_pattern("[^aeiou]y"), "[^aeiou]y");
+        assert_eq!(Affixes::condition_pattern("a.b"), "a.b");
+        assert_eq!(Affixes::condition_pattern("x+^"), r"x\+\^");
+    }
+
+    #[test]
+    fn rejects_rules_outside_a_group() {
+        let err = Affixes::parse("SFX S y ies [^aeiou]y\n").err().unwrap();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+        assert_eq!(err.to_string(), "Invalid affix rule on line 1");
+
+        assert!(Affixes::parse("PFX U Y 1\nPFX U 0\n").is_err());
+    }
+}
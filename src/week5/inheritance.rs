@@ -1,26 +1,76 @@
-use std::fmt::Display;
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt::{self, Display, Formatter};
 use std::env;
-use rand::{self, seq::SliceRandom};
+use std::io;
+use csv::Writer;
+use serde::{Serialize, Serializer};
+use serde::ser::SerializeStruct;
+use rand::{self, Rng, SeedableRng, seq::SliceRandom};
+use rand::rngs::StdRng;
 
 /// Allowed alleles
 static ALLELES: [char; 3] = ['A', 'B', 'O'];
 
-/// A person with parents and 2 alleles.
+/// Allowed Rh alleles, `+` is dominant and `-` is recessive.
+static RH_ALLELES: [char; 2] = ['+', '-'];
+
+/// A blood group of the ABO system.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BloodGroup {
+    A,
+    B,
+    AB,
+    O
+}
+
+impl BloodGroup {
+    /// Finds the blood group given by 2 alleles. A and B are codominant and both are dominant over O.
+    ///
+    /// # Arguments
+    /// * `alleles` - The alleles.
+    pub fn from_alleles(alleles: [char; 2]) -> Self {
+        match (alleles.contains(&'A'), alleles.contains(&'B')) {
+            (true, true) => Self::AB,
+            (true, false) => Self::A,
+            (false, true) => Self::B,
+            (false, false) => Self::O
+        }
+    }
+}
+
+impl Display for BloodGroup {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::A => "A",
+            Self::B => "B",
+            Self::AB => "AB",
+            Self::O => "O"
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+/// A person with parents, 2 alleles and 2 Rh alleles.
 pub struct Person {
     /// The person's parents. A person may not have parents.
     parents: Option<Box<(Person, Person)>>,
     /// The person's alleles.
-    alleles: [char; 2]
+    alleles: [char; 2],
+    /// The person's Rh alleles.
+    rh: [char; 2]
 }
 
 impl Person {
     /// Creates a new person with no parents and random alleles.
-    pub fn new() -> Self {
-        let mut rng = rand::thread_rng();
-
+    ///
+    /// # Arguments
+    /// * `rng` - Random number generator used to choose the alleles.
+    pub fn new<R: Rng + ?Sized>(rng: &mut R) -> Self {
         Self {
             parents: None,
-            alleles: [*ALLELES.choose(&mut rng).unwrap(), *ALLELES.choose(&mut rng).unwrap()]
+            alleles: [*ALLELES.choose(rng).unwrap(), *ALLELES.choose(rng).unwrap()],
+            rh: [*RH_ALLELES.choose(rng).unwrap(), *RH_ALLELES.choose(rng).unwrap()]
         }
     }
 
@@ -28,44 +78,152 @@ impl Person {
     ///
     /// # Arguments
     /// * `parents` - The person's parents.
-    pub fn with_parents(parents: (Person, Person)) -> Self {
-        let mut rng = rand::thread_rng();
-        let alleles = [*parents.0.alleles.choose(&mut rng).unwrap(), *parents.1.alleles.choose(&mut rng).unwrap()];
+    /// * `rng` - Random number generator used to choose the alleles.
+    pub fn with_parents<R: Rng + ?Sized>(parents: (Person, Person), rng: &mut R) -> Self {
+        let alleles = [*parents.0.alleles.choose(rng).unwrap(), *parents.1.alleles.choose(rng).unwrap()];
+        let rh = [*parents.0.rh.choose(rng).unwrap(), *parents.1.rh.choose(rng).unwrap()];
 
         Self {
             parents: Some(Box::new(parents)),
-            alleles
+            alleles,
+            rh
         }
     }
 
-    /// Creates a family tree by recursively creating generations.
+    /// The person's parents, `None` if the person has no parents.
+    pub fn parents(&self) -> Option<(&Person, &Person)> {
+        self.parents.as_deref().map(|(first, second)| (first, second))
+    }
+
+    /// The person's alleles.
+    pub fn alleles(&self) -> [char; 2] {
+        self.alleles
+    }
+
+    /// The person's Rh alleles.
+    pub fn rh_alleles(&self) -> [char; 2] {
+        self.rh
+    }
+
+    /// Number of generations in the person's family tree, counting the person's own.
+    pub fn generations(&self) -> usize {
+        match self.parents() {
+            Some((first, second)) => 1 + usize::max(first.generations(), second.generations()),
+            None => 1
+        }
+    }
+
+    /// Iterates over the person's ancestors breadth first, from parents to grandparents and so on.
+    /// Every ancestor comes with their generation number, which is 1 for parents.
+    pub fn ancestors(&self) -> Ancestors<'_> {
+        let mut ancestors = Ancestors(VecDeque::new());
+        ancestors.push_parents(self, 1);
+
+        ancestors
+    }
+
+    /// The person's ABO blood group.
+    pub fn blood_group(&self) -> BloodGroup {
+        BloodGroup::from_alleles(self.alleles)
+    }
+
+    /// Checks if the person is Rh positive, which happens if any of their Rh alleles is `+`.
+    pub fn rh_positive(&self) -> bool {
+        self.rh.contains(&'+')
+    }
+
+    /// The person's alleles and Rh alleles, sorted so that equivalent genotypes are written the same way, like `AO/+-`.
+    pub fn genotype(&self) -> String {
+        let mut alleles = self.alleles;
+        let mut rh = self.rh;
+        alleles.sort_unstable();
+        rh.sort_unstable();
+
+        format!("{}{}/{}{}", alleles[0], alleles[1], rh[0], rh[1])
+    }
+
+    /// The person's full blood type, like `A-` or `AB+`.
+    pub fn blood_type(&self) -> String {
+        format!("{}{}", self.blood_group(), if self.rh_positive() { '+' } else { '-' })
+    }
+
+    /// Creates a family tree by recursively creating generations. The same random number generator state always
+    /// creates the same family.
     ///
     /// # Arguments
     /// * `generations` - The number of generations in the family.
-    pub fn create_family(generations: usize) -> Self {
-        Self::recurse_family(generations)
+    /// * `rng` - Random number generator used to choose the alleles.
+    ///
+    /// # Panics
+    /// If there are no generations.
+    pub fn create_family<R: Rng + ?Sized>(generations: usize, rng: &mut R) -> Self {
+        assert!(generations > 0, "A family needs at least 1 generation");
+        Self::recurse_family(generations, rng)
     }
 
     /// Creates a family tree by recursively creating generations.
     ///
     /// # Arguments
     /// * `generations` - The number of generations left to create.
-    fn recurse_family(gens_left: usize) -> Self {
+    /// * `rng` - Random number generator used to choose the alleles.
+    fn recurse_family<R: Rng + ?Sized>(gens_left: usize, rng: &mut R) -> Self {
         match gens_left {
-            1 => Self::new(),
+            1 => Self::new(rng),
             _ => {
-                let parents = (Self::recurse_family(gens_left - 1), Self::recurse_family(gens_left - 1));
-                Self::with_parents(parents)
+                let parents = (Self::recurse_family(gens_left - 1, rng), Self::recurse_family(gens_left - 1, rng));
+                Self::with_parents(parents, rng)
             }
         }
     }
 
+    /// Converts the person's family tree to JSON. Every person has their blood type, alleles, Rh alleles and parents,
+    /// if they have any.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+
+    /// Converts the person's family tree to a Graphviz DOT graph, with arrows from parents to children.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph family {\n    node [shape=box];\n");
+        let mut queue = VecDeque::from([(0, 0, self)]);
+        let mut next_id = 1;
+
+        // Visits the family breadth first, numbering every person.
+        while let Some((id, generation, person)) = queue.pop_front() {
+            dot += &format!(
+                "    p{} [label=\"Generation {}\\n{} ({}, {})\"];\n",
+                id,
+                generation,
+                person.blood_type(),
+                person.alleles.into_iter().collect::<String>(),
+                person.rh.into_iter().collect::<String>()
+            );
+
+            if let Some((first, second)) = person.parents() {
+                for parent in [first, second] {
+                    dot += &format!("    p{} -> p{};\n", next_id, id);
+                    queue.push_back((next_id, generation + 1, parent));
+                    next_id += 1;
+                }
+            }
+        }
+
+        dot + "}"
+    }
+
     /// Formats the person's family tree as a string.
     ///
     /// # Arguments
     /// * `generation` - The current generation's number.
     fn as_string(&self, generation: usize) -> String {
-        let string = "\t".repeat(generation) + "(Generation " + &generation.to_string() + "): Blood type " + &self.alleles.into_iter().collect::<String>();
+        let string = format!(
+            "{}(Generation {}): Blood type {} (alleles {}, Rh {})",
+            "\t".repeat(generation),
+            generation,
+            self.blood_type(),
+            self.alleles.into_iter().collect::<String>(),
+            self.rh.into_iter().collect::<String>()
+        );
 
         match self.parents {
             Some(ref parents) => string + "\n" + &parents.0.as_string(generation + 1) + "\n" + &parents.1.as_string(generation + 1),
@@ -74,20 +232,293 @@ impl Person {
     }
 }
 
+impl Serialize for Person {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut person = serializer.serialize_struct("Person", 4)?;
+        person.serialize_field("blood_type", &self.blood_type())?;
+        person.serialize_field("alleles", &self.alleles.into_iter().collect::<String>())?;
+        person.serialize_field("rh", &self.rh.into_iter().collect::<String>())?;
+
+        match self.parents() {
+            Some((first, second)) => person.serialize_field("parents", &[first, second])?,
+            None => person.skip_field("parents")?
+        }
+
+        person.end()
+    }
+}
+
+/// Genotype and blood type counts of a generation across many families.
+#[derive(Debug, Clone, Default)]
+pub struct GenerationFrequencies {
+    /// The generation's number, 0 for the youngest generation.
+    pub generation: usize,
+    /// Number of people in the generation.
+    pub people: usize,
+    /// Number of people by genotype, like `AO/+-`.
+    pub genotypes: BTreeMap<String, usize>,
+    /// Number of people by blood type, like `A+`.
+    pub blood_types: BTreeMap<String, usize>
+}
+
+/// A row of the CSV frequency tables.
+#[derive(Debug, Clone, Serialize)]
+struct FrequencyRow<'a> {
+    /// The generation's number.
+    generation: usize,
+    /// Either `genotype` or `blood_type`.
+    kind: &'a str,
+    /// The genotype or blood type.
+    value: &'a str,
+    /// Number of people with this genotype or blood type.
+    count: usize,
+    /// Fraction of the generation with this genotype or blood type.
+    frequency: f64
+}
+
+/// Creates many family trees and counts the genotypes and blood types of each generation.
+/// Returns one table per generation, starting with the youngest one. The same seed always gives the same tables.
+///
+/// # Arguments
+/// * `families` - Number of family trees to create.
+/// * `generations` - The number of generations in each family.
+/// * `seed` - Seed of the random number generator.
+///
+/// # Panics
+/// If there are no generations.
+pub fn simulate_families(families: usize, generations: usize, seed: u64) -> Vec<GenerationFrequencies> {
+    assert!(generations > 0, "A family needs at least 1 generation");
+
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut tables: Vec<GenerationFrequencies> = (0..generations)
+        .map(|generation| GenerationFrequencies { generation, ..Default::default() })
+        .collect();
+
+    for _ in 0..families {
+        let family = Person::create_family(generations, &mut rng);
+
+        for (generation, person) in [(0, &family)].into_iter().chain(family.ancestors()) {
+            let table = &mut tables[generation];
+            table.people += 1;
+            *table.genotypes.entry(person.genotype()).or_insert(0) += 1;
+            *table.blood_types.entry(person.blood_type()).or_insert(0) += 1;
+        }
+    }
+
+    tables
+}
+
+/// Writes frequency tables as CSV, with one row per generation and genotype or blood type.
+///
+/// # Arguments
+/// * `tables` - The tables.
+/// * `writer` - Where the CSV is written.
+pub fn write_frequencies_csv<W: io::Write>(tables: &[GenerationFrequencies], writer: W) -> Result<(), csv::Error> {
+    let mut writer = Writer::from_writer(writer);
+
+    for table in tables {
+        let genotypes = table.genotypes.iter().map(|(value, count)| ("genotype", value, count));
+        let blood_types = table.blood_types.iter().map(|(value, count)| ("blood_type", value, count));
+
+        for (kind, value, &count) in genotypes.chain(blood_types) {
+            writer.serialize(FrequencyRow {
+                generation: table.generation,
+                kind,
+                value,
+                count,
+                frequency: count as f64 / table.people as f64
+            })?;
+        }
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+/// A breadth first iterator over a person's ancestors and their generation numbers.
+pub struct Ancestors<'a>(VecDeque<(usize, &'a Person)>);
+
+impl <'a> Ancestors<'a> {
+    /// Queues a person's parents.
+    ///
+    /// # Arguments
+    /// * `person` - The person.
+    /// * `generation` - The parents' generation number.
+    fn push_parents(&mut self, person: &'a Person, generation: usize) {
+        if let Some((first, second)) = person.parents() {
+            self.0.push_back((generation, first));
+            self.0.push_back((generation, second));
+        }
+    }
+}
+
+impl <'a> Iterator for Ancestors<'a> {
+    type Item = (usize, &'a Person);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (generation, person) = self.0.pop_front()?;
+        self.push_parents(person, generation + 1);
+
+        Some((generation, person))
+    }
+}
+
 impl Display for Person {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         write!(f, "{}", self.as_string(0))
     }
 }
 
+/// Usage: `inheritance [height] [--seed 42] [--format <text|json|dot>] [--simulate 1000]`
+///
+/// Running with the same seed creates the same family tree, which is printed as indented text, JSON or a Graphviz DOT
+/// graph. With `--simulate`, creates that many family trees and
+/// prints the genotype and blood type frequencies of each generation as CSV instead.
 pub fn main() {
-    // Reads the family tree's height from command line args.
-    let height: usize = env::args().nth(1)
-        .unwrap_or("3".to_string())
-        .parse()
-        .unwrap();
+    // Reads the family tree's height, the seed and the number of simulated families from command line args.
+    let mut height: usize = 3;
+    let mut seed: u64 = rand::thread_rng().gen();
+    let mut families: Option<usize> = None;
+    let mut format = String::from("text");
+    let mut args = env::args().skip(1);
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--seed" => seed = args.next().and_then(|seed| seed.parse().ok()).expect("Seed must be a non-negative integer."),
+            "--format" => format = args.next().expect("Missing value for --format."),
+            "--simulate" => families = Some(args.next().and_then(|families| families.parse().ok()).expect("Families must be a non-negative integer.")),
+            _ => height = arg.parse().expect("Height must be a positive integer.")
+        }
+    }
+
+    if let Some(families) = families {
+        let tables = simulate_families(families, height, seed);
+        write_frequencies_csv(&tables, io::stdout()).unwrap();
+        return;
+    }
 
     // Creates and prints the family tree.
-    let family_tree = Person::create_family(height);
-    println!("{family_tree}");
-}
\ No newline at end of file
+    let family_tree = Person::create_family(height, &mut StdRng::seed_from_u64(seed));
+
+    match format.as_str() {
+        "text" => println!("{family_tree}"),
+        "json" => println!("{}", family_tree.to_json()),
+        "dot" => println!("{}", family_tree.to_dot()),
+        _ => panic!("The format must be text, json or dot.")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ancestors_are_visited_by_generation() {
+        let person = Person::create_family(4, &mut StdRng::seed_from_u64(3));
+        let (first_parent, second_parent) = person.parents().unwrap();
+        let generations: Vec<usize> = person.ancestors().map(|(generation, _)| generation).collect();
+
+        assert_eq!(person.generations(), 4);
+        assert_eq!(generations, vec![1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 3, 3, 3, 3]);
+        assert!(std::ptr::eq(person.ancestors().next().unwrap().1, first_parent));
+        assert!(person.ancestors().filter(|&(generation, _)| generation == 3).all(|(_, ancestor)| ancestor.parents().is_none()));
+        assert!(person.alleles().iter().all(|allele| first_parent.alleles().contains(allele) || second_parent.alleles().contains(allele)));
+    }
+
+    #[test]
+    fn same_seed_creates_the_same_family() {
+        let family = Person::create_family(3, &mut StdRng::seed_from_u64(11));
+        let same = Person::create_family(3, &mut StdRng::seed_from_u64(11));
+        let families: Vec<String> = (0..20).map(|seed| Person::create_family(3, &mut StdRng::seed_from_u64(seed)).to_json()).collect();
+
+        assert_eq!(family.to_json(), same.to_json());
+        assert_eq!(family.to_string(), same.to_string());
+        assert!(families.iter().any(|other| *other != family.to_json()));
+    }
+
+    #[test]
+    fn finds_blood_types_from_alleles() {
+        let cases = [
+            (['A', 'O'], ['+', '-'], BloodGroup::A, true, "A+"),
+            (['O', 'B'], ['-', '+'], BloodGroup::B, true, "B+"),
+            (['A', 'B'], ['+', '+'], BloodGroup::AB, true, "AB+"),
+            (['B', 'A'], ['-', '-'], BloodGroup::AB, false, "AB-"),
+            (['O', 'O'], ['-', '-'], BloodGroup::O, false, "O-"),
+            (['A', 'A'], ['-', '-'], BloodGroup::A, false, "A-")
+        ];
+
+        for (alleles, rh, group, rh_positive, blood_type) in cases {
+            let person = Person { parents: None, alleles, rh };
+
+            assert_eq!(BloodGroup::from_alleles(alleles), group);
+            assert_eq!(person.rh_positive(), rh_positive);
+            assert_eq!(person.blood_type(), blood_type);
+        }
+    }
+
+    #[test]
+    fn simulates_every_generation() {
+        let tables = simulate_families(50, 4, 9);
+
+        assert_eq!(tables.len(), 4);
+
+        for (generation, table) in tables.iter().enumerate() {
+            assert_eq!(table.generation, generation);
+            assert_eq!(table.people, 50 * 2_usize.pow(generation as u32));
+            assert_eq!(table.genotypes.values().sum::<usize>(), table.people);
+            assert_eq!(table.blood_types.values().sum::<usize>(), table.people);
+        }
+
+        assert_eq!(simulate_families(50, 4, 9)[3].genotypes, tables[3].genotypes);
+    }
+
+    #[test]
+    #[should_panic(expected = "A family needs at least 1 generation")]
+    fn simulating_without_generations_panics() {
+        simulate_families(10, 0, 1);
+    }
+
+    #[test]
+    fn writes_frequencies_as_csv() {
+        let table = GenerationFrequencies {
+            generation: 1,
+            people: 4,
+            genotypes: BTreeMap::from([(String::from("AO/+-"), 3), (String::from("OO/--"), 1)]),
+            blood_types: BTreeMap::from([(String::from("A+"), 3), (String::from("O-"), 1)])
+        };
+
+        let mut csv = Vec::new();
+        write_frequencies_csv(&[table], &mut csv).unwrap();
+
+        assert_eq!(String::from_utf8(csv).unwrap(), "\
+generation,kind,value,count,frequency
+1,genotype,AO/+-,3,0.75
+1,genotype,OO/--,1,0.25
+1,blood_type,A+,3,0.75
+1,blood_type,O-,1,0.25
+");
+    }
+
+    #[test]
+    fn converts_family_to_json_and_dot() {
+        let family = Person::create_family(2, &mut StdRng::seed_from_u64(5));
+        let (first, second) = family.parents().unwrap();
+        let json: serde_json::Value = serde_json::from_str(&family.to_json()).unwrap();
+        let parents = json["parents"].as_array().unwrap();
+
+        assert_eq!(json["blood_type"], family.blood_type());
+        assert_eq!(parents.len(), 2);
+        assert_eq!(parents[0]["alleles"], first.alleles().into_iter().collect::<String>());
+        assert_eq!(parents[1]["rh"], second.rh_alleles().into_iter().collect::<String>());
+        assert!(parents.iter().all(|parent| parent.get("parents").is_none()));
+
+        let dot = family.to_dot();
+        let edges: Vec<&str> = dot.lines().filter(|line| line.contains("->")).map(str::trim).collect();
+
+        assert!(dot.starts_with("digraph family {"));
+        assert!(dot.ends_with('}'));
+        assert_eq!(edges, vec!["p1 -> p0;", "p2 -> p0;"]);
+        assert!(dot.contains(&format!("p0 [label=\"Generation 0\\n{}", family.blood_type())));
+        assert!(dot.contains(&format!("p2 [label=\"Generation 1\\n{}", second.blood_type())));
+    }
+}
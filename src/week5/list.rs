@@ -0,0 +1,658 @@
+use std::fmt::{self, Debug, Formatter};
+use std::marker::PhantomData;
+use std::mem;
+use std::ptr::NonNull;
+use crate::week3::sort::{GapSequence, SortAlgorithm, Sortable};
+
+/// A custom singly linked list node.
+struct ListNode<T> {
+    /// The node's data.
+    data: T,
+    /// Next node in the list.
+    next: Option<NonNull<ListNode<T>>>
+}
+
+impl <T> ListNode<T> {
+    /// Allocates a new linked list node containing the supplied data. The node must be freed with `Box::from_raw`.
+    ///
+    /// # Arguments
+    /// * `data` - The node's data.
+    /// * `next` - Next node in the list.
+    fn alloc(data: T, next: Option<NonNull<ListNode<T>>>) -> NonNull<ListNode<T>> {
+        NonNull::from(Box::leak(Box::new(Self { data, next })))
+    }
+}
+
+/// An iterator for a linked list.
+pub struct ListIter<'a, T> {
+    /// The next node.
+    next: Option<NonNull<ListNode<T>>>,
+    /// Number of items left.
+    len: usize,
+    /// The iterator borrows the list's nodes.
+    marker: PhantomData<&'a ListNode<T>>
+}
+
+impl <'a, T> Iterator for ListIter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // The node belongs to the list, which is borrowed for the iterator's lifetime.
+        self.next.map(|node| unsafe {
+            let node = &*node.as_ptr();
+            self.next = node.next;
+            self.len -= 1;
+
+            &node.data
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+/// A custom singly linked list.
+pub struct List<T> {
+    /// The first node in the list.
+    head: Option<NonNull<ListNode<T>>>,
+    /// The last node in the list, so that items can be added to the end in constant time.
+    tail: Option<NonNull<ListNode<T>>>,
+    /// Number of items in the list.
+    len: usize,
+    /// The list owns its nodes, which are allocated in boxes.
+    marker: PhantomData<Box<ListNode<T>>>
+}
+
+// The list owns its nodes, their pointers are never shared outside of it.
+unsafe impl <T: Send> Send for List<T> {}
+unsafe impl <T: Sync> Sync for List<T> {}
+
+impl <T> List<T> {
+    /// Creates a new empty linked list.
+    pub fn new() -> Self {
+        Self {
+            head: None,
+            tail: None,
+            len: 0,
+            marker: PhantomData
+        }
+    }
+
+    /// Number of items in the list.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Checks if the list has no items.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Iterates over references to the list's items.
+    pub fn iter(&self) -> ListIter<'_, T> {
+        ListIter {
+            next: self.head,
+            len: self.len,
+            marker: PhantomData
+        }
+    }
+
+    /// Adds a new item to the end of the list.
+    ///
+    /// # Arguments
+    /// * `data` - The data to add.
+    pub fn add(&mut self, data: T) {
+        self.push_back(data);
+    }
+
+    /// Adds a new item to the end of the list in constant time.
+    ///
+    /// # Arguments
+    /// * `data` - The data to add.
+    pub fn push_back(&mut self, data: T) {
+        let node = ListNode::alloc(data, None);
+
+        match self.tail {
+            // The tail belongs to this list, which is borrowed mutably.
+            Some(tail) => unsafe { (*tail.as_ptr()).next = Some(node) },
+            None => self.head = Some(node)
+        }
+
+        self.tail = Some(node);
+        self.len += 1;
+    }
+
+    /// Adds a new item to the start of the list in constant time.
+    ///
+    /// # Arguments
+    /// * `data` - The data to add.
+    pub fn push_front(&mut self, data: T) {
+        let node = ListNode::alloc(data, self.head);
+
+        if self.tail.is_none() {
+            self.tail = Some(node);
+        }
+
+        self.head = Some(node);
+        self.len += 1;
+    }
+
+    /// Removes the first item in the list and returns it.
+    pub fn pop_front(&mut self) -> Option<T> {
+        self.head.map(|node| {
+            // The node was allocated by `ListNode::alloc` and is owned by this list, so it can be freed once it's
+            // unlinked.
+            let node = unsafe { Box::from_raw(node.as_ptr()) };
+            self.head = node.next;
+            self.len -= 1;
+
+            if self.head.is_none() {
+                self.tail = None;
+            }
+
+            node.data
+        })
+    }
+
+    /// Iterates over mutable references to the list's items.
+    pub fn iter_mut(&mut self) -> ListIterMut<'_, T> {
+        ListIterMut {
+            next: self.head,
+            marker: PhantomData
+        }
+    }
+
+    /// Removes the first item which matches a predicate and returns it.
+    ///
+    /// # Arguments
+    /// * `predicate` - Function which specifies if an item should be removed.
+    pub fn remove_first<F: FnMut(&T) -> bool>(&mut self, mut predicate: F) -> Option<T> {
+        let mut prev: Option<NonNull<ListNode<T>>> = None;
+        let mut current = self.head;
+
+        while let Some(node) = current {
+            // Every node belongs to this list, which is borrowed mutably.
+            if predicate(unsafe { &(*node.as_ptr()).data }) {
+                let node = unsafe { Box::from_raw(node.as_ptr()) };
+
+                match prev {
+                    Some(prev) => unsafe { (*prev.as_ptr()).next = node.next },
+                    None => self.head = node.next
+                }
+
+                if node.next.is_none() {
+                    self.tail = prev;
+                }
+
+                self.len -= 1;
+                return Some(node.data);
+            }
+
+            prev = current;
+            current = unsafe { (*node.as_ptr()).next };
+        }
+
+        None
+    }
+}
+
+impl <T> List<T> {
+    /// Removes every item from the list and returns them in order.
+    pub fn drain_to_vec(&mut self) -> Vec<T> {
+        let mut items = Vec::with_capacity(self.len);
+
+        while let Some(item) = self.pop_front() {
+            items.push(item);
+        }
+
+        items
+    }
+
+    /// Replaces the list's items with the supplied ones, keeping their order.
+    ///
+    /// # Arguments
+    /// * `items` - The new items.
+    fn fill_from_vec(&mut self, items: Vec<T>) {
+        self.drain_to_vec();
+        self.extend(items);
+    }
+
+    /// Points the tail to the last node, after the nodes were relinked.
+    fn reset_tail(&mut self) {
+        let mut last = self.head;
+
+        // Every node belongs to this list, which is borrowed mutably.
+        while let Some(next) = last.and_then(|node| unsafe { (*node.as_ptr()).next }) {
+            last = Some(next);
+        }
+
+        self.tail = last;
+    }
+
+    /// Sorts the list's items with the given slice sorting function.
+    ///
+    /// # Arguments
+    /// * `sort` - Function which sorts the items once they are moved into a buffer.
+    fn sort_buffered<F: FnOnce(&mut [T])>(&mut self, sort: F) {
+        let mut items = self.drain_to_vec();
+        sort(&mut items);
+        self.fill_from_vec(items);
+    }
+
+    /// Sorts a chain of nodes using merge sort by relinking them. Returns the new first node.
+    ///
+    /// # Arguments
+    /// * `head` - The chain's first node.
+    /// * `len` - Number of nodes in the chain.
+    /// * `is_smaller` - Function which specifies if the current element is smaller than the other.
+    fn merge_sort_nodes<F: Fn(&T, &T) -> bool>(head: Option<NonNull<ListNode<T>>>, len: usize, is_smaller: &F) -> Option<NonNull<ListNode<T>>> {
+        let mut last = match head {
+            Some(head) if len >= 2 => head,
+            _ => return head
+        };
+
+        let mid = len / 2;
+
+        // The chain has `len` nodes, which belong to the list being sorted.
+        let second = unsafe {
+            for _ in 1..mid {
+                last = (*last.as_ptr()).next.unwrap();
+            }
+
+            (*last.as_ptr()).next.take()
+        };
+
+        let first = Self::merge_sort_nodes(head, mid, is_smaller);
+        let second = Self::merge_sort_nodes(second, len - mid, is_smaller);
+
+        Self::merge_nodes(first, second, is_smaller)
+    }
+
+    /// Merges 2 sorted chains of nodes into a single sorted chain. Returns the new first node.
+    ///
+    /// # Arguments
+    /// * `first` - The first chain. Wins ties, so the merge is stable.
+    /// * `second` - The second chain.
+    /// * `is_smaller` - Function which specifies if the current element is smaller than the other.
+    fn merge_nodes<F: Fn(&T, &T) -> bool>(mut first: Option<NonNull<ListNode<T>>>, mut second: Option<NonNull<ListNode<T>>>, is_smaller: &F) -> Option<NonNull<ListNode<T>>> {
+        let mut head = None;
+        let mut tail: Option<NonNull<ListNode<T>>> = None;
+
+        // Both chains belong to the list being sorted and every node is moved to the merged chain exactly once.
+        unsafe {
+            loop {
+                let source = match (first, second) {
+                    (Some(a), Some(b)) => if is_smaller(&(*b.as_ptr()).data, &(*a.as_ptr()).data) { &mut second } else { &mut first },
+                    (Some(_), None) => &mut first,
+                    (None, Some(_)) => &mut second,
+                    (None, None) => break
+                };
+
+                let node = source.unwrap();
+                *source = (*node.as_ptr()).next.take();
+
+                match tail {
+                    Some(tail) => (*tail.as_ptr()).next = Some(node),
+                    None => head = Some(node)
+                }
+
+                tail = Some(node);
+            }
+        }
+
+        head
+    }
+}
+
+impl <T: Clone> Sortable<T> for List<T> {
+    fn selection_sort_by<F: Fn(&T, &T) -> bool>(&mut self, is_smaller: &F) {
+        self.sort_buffered(|items| items.selection_sort_by(is_smaller));
+    }
+
+    fn bubble_sort_by<F: Fn(&T, &T) -> bool>(&mut self, is_smaller: &F) {
+        self.sort_buffered(|items| items.bubble_sort_by(is_smaller));
+    }
+
+    /// Sorts the list in place by relinking its nodes, no buffer is used.
+    fn merge_sort_by<F: Fn(&T, &T) -> bool>(&mut self, is_smaller: &F) {
+        // The list stays empty while its nodes are relinked, so a panicking comparison leaks them instead of leaving
+        // the list inconsistent.
+        let head = self.head.take();
+        let len = mem::replace(&mut self.len, 0);
+        self.tail = None;
+
+        self.head = Self::merge_sort_nodes(head, len, is_smaller);
+        self.len = len;
+        self.reset_tail();
+    }
+
+    fn insertion_sort_by<F: Fn(&T, &T) -> bool>(&mut self, is_smaller: &F) {
+        self.sort_buffered(|items| items.insertion_sort_by(is_smaller));
+    }
+
+    fn heap_sort_by<F: Fn(&T, &T) -> bool>(&mut self, is_smaller: &F) {
+        self.sort_buffered(|items| items.heap_sort_by(is_smaller));
+    }
+
+    fn tim_sort_by<F: Fn(&T, &T) -> bool>(&mut self, is_smaller: &F) {
+        self.sort_buffered(|items| items.tim_sort_by(is_smaller));
+    }
+
+    fn shell_sort_by<F: Fn(&T, &T) -> bool>(&mut self, gaps: GapSequence, is_smaller: &F) {
+        self.sort_buffered(|items| items.shell_sort_by(gaps, is_smaller));
+    }
+
+    fn select_nth_by<F: Fn(&T, &T) -> bool>(&mut self, k: usize, is_smaller: &F) -> &T {
+        self.sort_buffered(|items| { items.select_nth_by(k, is_smaller); });
+        self.iter().nth(k).unwrap()
+    }
+
+    fn partial_sort_by<F: Fn(&T, &T) -> bool>(&mut self, k: usize, is_smaller: &F) -> Vec<&T> {
+        self.sort_buffered(|items| { items.partial_sort_by(k, is_smaller); });
+        self.iter().take(k).collect()
+    }
+
+    /// Merge sort relinks the nodes like `merge_sort_by`, every other algorithm sorts a buffer.
+    fn sort_with<F: Fn(&T, &T) -> bool>(&mut self, algorithm: SortAlgorithm, is_smaller: &F) {
+        match algorithm {
+            SortAlgorithm::Merge => self.merge_sort_by(is_smaller),
+            _ => self.sort_buffered(|items| items.sort_with(algorithm, is_smaller))
+        }
+    }
+}
+
+/// A mutable iterator for a linked list.
+pub struct ListIterMut<'a, T> {
+    /// The next node.
+    next: Option<NonNull<ListNode<T>>>,
+    /// The iterator borrows the list's nodes mutably.
+    marker: PhantomData<&'a mut ListNode<T>>
+}
+
+impl <'a, T> Iterator for ListIterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // The node belongs to the list, which is borrowed mutably for the iterator's lifetime, and every node is
+        // visited once.
+        self.next.map(|node| unsafe {
+            let node = &mut *node.as_ptr();
+            self.next = node.next;
+
+            &mut node.data
+        })
+    }
+}
+
+impl <'a, T> IntoIterator for &'a List<T> {
+    type Item = &'a T;
+    type IntoIter = ListIter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator which moves the items out of a linked list.
+pub struct ListIntoIter<T>(List<T>);
+
+impl <T> Iterator for ListIntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.pop_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.0.len, Some(self.0.len))
+    }
+}
+
+impl <T> IntoIterator for List<T> {
+    type Item = T;
+    type IntoIter = ListIntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        ListIntoIter(self)
+    }
+}
+
+impl <T> FromIterator<T> for List<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = List::new();
+        list.extend(iter);
+
+        list
+    }
+}
+
+impl <T> Extend<T> for List<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            self.push_back(item);
+        }
+    }
+}
+
+impl <T: Debug> Debug for List<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self).finish()
+    }
+}
+
+impl <T: PartialEq> PartialEq for List<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other)
+    }
+}
+
+impl <T: Eq> Eq for List<T> {}
+
+impl <T> Drop for List<T> {
+    /// Frees the nodes one by one, from the front.
+    fn drop(&mut self) {
+        while self.pop_front().is_some() {}
+    }
+}
+
+impl <T: Clone> Clone for List<T> {
+    fn clone(&self) -> Self {
+        self.iter().cloned().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn sort_with_sorts_by_every_algorithm() {
+        let items = [5, 3, 9, 1, 3, 7, 0, 8];
+        let mut expected = items.to_vec();
+        expected.sort();
+
+        for algorithm in [SortAlgorithm::Merge, SortAlgorithm::Insertion, SortAlgorithm::Tim, SortAlgorithm::Intro] {
+            let mut list: List<i32> = items.iter().copied().collect();
+            list.sort_with(algorithm, &|smaller, greater| smaller < greater);
+
+            assert!(list.iter().eq(expected.iter()), "{:?}", algorithm);
+            list.push_back(10);
+            assert_eq!(list.iter().last(), Some(&10));
+        }
+    }
+
+    #[test]
+    fn sorts_by_relinking_and_buffering() {
+        let items = [12, -4, 7, 7, 0, 31, -9, 2, 18, 5, 5, 1];
+        let mut expected = items.to_vec();
+        expected.sort();
+
+        let mut merged: List<i32> = items.iter().copied().collect();
+        merged.merge_sort();
+        assert!(merged.iter().eq(expected.iter()));
+
+        let mut heap: List<i32> = items.iter().copied().collect();
+        heap.heap_sort();
+        assert!(heap.iter().eq(expected.iter()));
+    }
+
+    #[test]
+    fn merge_sort_is_stable() {
+        let items: Vec<(i32, usize)> = [3, 1, 2, 1, 3, 0, 2, 1, 0, 3].into_iter().zip(0..).collect();
+        let mut expected = items.clone();
+        expected.sort_by_key(|&(key, _)| key);
+
+        let mut list: List<(i32, usize)> = items.into_iter().collect();
+        list.merge_sort_by_key(|&(key, _)| key);
+
+        assert!(list.iter().eq(expected.iter()));
+    }
+
+    #[test]
+    fn select_nth_finds_the_median() {
+        let mut list: List<i32> = [9, 2, 7, 4, 5, 1, 8].into_iter().collect();
+
+        assert_eq!(*list.select_nth(3), 5);
+        assert_eq!(list.len(), 7);
+    }
+
+    #[test]
+    fn partial_sort_finds_smallest_items() {
+        let mut list: List<i32> = [6, 3, 9, 1, 4, 8].into_iter().collect();
+
+        assert_eq!(list.partial_sort(3), vec![&1, &3, &4]);
+        assert_eq!(list.partial_sort(10).len(), 6);
+        assert!(list.partial_sort(0).is_empty());
+    }
+
+    #[test]
+    fn remove_first_unlinks_any_position() {
+        let mut list: List<i32> = (0..5).collect();
+
+        assert_eq!(list.remove_first(|&item| item == 0), Some(0));
+        assert_eq!(list.remove_first(|&item| item == 4), Some(4));
+        assert_eq!(list.remove_first(|&item| item == 2), Some(2));
+        assert_eq!(list.remove_first(|&item| item == 7), None);
+        assert!(list.iter().eq([1, 3].iter()));
+        assert_eq!(list.len(), 2);
+
+        let mut empty: List<i32> = List::new();
+        assert_eq!(empty.remove_first(|_| true), None);
+    }
+
+    #[test]
+    fn tail_follows_the_last_node() {
+        let mut list = List::new();
+        list.push_front(2);
+        list.push_back(3);
+        list.push_front(1);
+        assert!(list.iter().eq([1, 2, 3].iter()));
+
+        assert_eq!(list.remove_first(|&item| item == 3), Some(3));
+        list.push_back(4);
+        assert!(list.iter().eq([1, 2, 4].iter()));
+
+        while list.remove_first(|_| true).is_some() {}
+        list.extend([7, 5, 6]);
+
+        list.merge_sort();
+        list.push_back(9);
+        assert!(list.iter().eq([5, 6, 7, 9].iter()));
+
+        list.bubble_sort();
+        list.push_back(0);
+        assert!(list.iter().eq([5, 6, 7, 9, 0].iter()));
+
+        let mut clone = list.clone();
+        clone.push_back(1);
+        assert_eq!(list.drain_to_vec(), vec![5, 6, 7, 9, 0]);
+        list.push_back(3);
+        assert!(list.iter().eq([3].iter()));
+
+        // Moving the list doesn't move its nodes, so the tail stays valid.
+        let mut moved = vec![clone].pop().unwrap();
+        moved.push_back(2);
+        assert!(moved.iter().eq([5, 6, 7, 9, 0, 1, 2].iter()));
+    }
+
+    #[test]
+    fn push_back_keeps_order() {
+        let mut list = List::new();
+
+        for i in 0..100_000 {
+            list.push_back(i);
+        }
+
+        assert_eq!(list.len(), 100_000);
+        assert!(list.into_iter().eq(0..100_000));
+    }
+
+    #[test]
+    fn iter_mut_updates_every_item() {
+        let mut list: List<i32> = (1..=4).collect();
+
+        for item in list.iter_mut() {
+            *item *= 10;
+        }
+
+        list.push_back(50);
+        assert!(list.iter().eq([10, 20, 30, 40, 50].iter()));
+    }
+
+    #[test]
+    fn drops_long_lists_without_overflowing_the_stack() {
+        let mut list = List::new();
+
+        for i in 0..500_000 {
+            list.push_front(i);
+        }
+
+        drop(list);
+
+        let mut list = List::new();
+
+        for i in 0..500_000 {
+            list.push_back(i.to_string());
+        }
+
+        let dropper = thread::Builder::new()
+            .stack_size(64 * 1024)
+            .spawn(move || drop(list))
+            .unwrap();
+
+        dropper.join().unwrap();
+    }
+
+    #[test]
+    fn implements_collection_traits() {
+        let mut list: List<i32> = (1..=3).collect();
+        assert_eq!(list.len(), 3);
+
+        list.extend([5, 4]);
+        assert_eq!(list.len(), 5);
+        assert_eq!(format!("{:?}", list), "[1, 2, 3, 5, 4]");
+
+        let clone = list.clone();
+        assert_eq!(clone, list);
+
+        list.merge_sort();
+        assert_ne!(clone, list);
+        assert_eq!(list.into_iter().collect::<Vec<_>>(), vec![1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn pop_front_empties_the_list() {
+        let mut list: List<i32> = List::new();
+        assert!(list.is_empty());
+        assert_eq!(list.pop_front(), None);
+
+        list.push_back(1);
+        assert_eq!(list.pop_front(), Some(1));
+        assert!(list.is_empty());
+
+        list.push_back(2);
+        assert!(list.iter().eq([2].iter()));
+    }
+}
@@ -1,250 +1,976 @@
-use std::{env, fs};
-use std::fmt::Display;
-use std::io::{BufRead, BufReader, Read};
-use std::fs::File;
+use std::borrow::Cow;
+use std::collections::VecDeque;
+use std::env;
+use std::io::{self, BufRead, BufReader, BufWriter, ErrorKind, Write};
+use std::fs::{self, File, OpenOptions};
+use std::path::Path;
+use std::str;
+use memmap2::Mmap;
+use std::time::{Duration, Instant};
 use regex::Regex;
+use serde::Serialize;
+use super::hashtable::HashTable;
+use super::helpers;
+use super::hunspell::Affixes;
+use super::list::List;
+use crate::week3::sort::Sortable;
+
+/// The first bytes of a compiled dictionary file.
+const COMPILED_MAGIC: &[u8] = b"CS50DIC1";
+/// Number of words checked after loading a compiled dictionary, to make sure the table's hash function has not changed.
+const COMPILED_SAMPLES: usize = 8;
+
+/// Counts and timings of a spell check. Times are in seconds.
+#[derive(Debug, Clone, Serialize)]
+struct SpellerStats {
+    /// Number of misspelled words in the text.
+    words_misspelled: u32,
+    /// Number of words in the dictionary.
+    words_in_dictionary: usize,
+    /// Number of words in the text.
+    words_in_text: u32,
+    /// Time spent loading the dictionary.
+    time_in_load: f64,
+    /// Time spent spell checking the text.
+    time_in_check: f64,
+    /// Time spent counting the words in the dictionary.
+    time_in_size: f64,
+    /// Time spent freeing the dictionary.
+    time_in_unload: f64,
+    /// Sum of every other time.
+    time_in_total: f64
+}
+
+/// The result of a spell check, printed with `--format json`.
+#[derive(Debug, Clone, Serialize)]
+struct SpellerReport {
+    /// The misspelled words in the order they were found.
+    misspelled: Vec<String>,
+    /// Counts and timings.
+    #[serde(flatten)]
+    stats: SpellerStats
+}
+
+/// How many times a word appears in a text, printed with `--frequencies` and `--format json`.
+#[derive(Debug, Clone, Serialize)]
+struct WordFrequency<'a> {
+    /// The word.
+    word: &'a str,
+    /// Number of times the word appears.
+    count: u32
+}
 
-/// A custom singly linked list node.
-#[derive(Clone)]
-struct ListNode<T> {
-    /// The node's data.
-    data: T,
-    /// Next node in the list.
-    next: Option<Box<ListNode<T>>>
+impl SpellerStats {
+    /// Prints the stats in the same format as CS50's speller.
+    fn print(&self) {
+        println!("WORDS MISSPELLED:     {}", self.words_misspelled);
+        println!("WORDS IN DICTIONARY:  {}", self.words_in_dictionary);
+        println!("WORDS IN TEXT:        {}", self.words_in_text);
+        println!("TIME IN load:         {:.2}", self.time_in_load);
+        println!("TIME IN check:        {:.2}", self.time_in_check);
+        println!("TIME IN size:         {:.2}", self.time_in_size);
+        println!("TIME IN unload:       {:.2}", self.time_in_unload);
+        println!("TIME IN TOTAL:        {:.2}", self.time_in_total);
+    }
 }
 
-impl <T> ListNode<T> {
-    /// Creates a new linked list node containing the supplied data.
+/// Runs a function and measures how long it took. Returns the function's result and the elapsed time.
+///
+/// # Arguments
+/// * `f` - The function.
+fn timed<T, F: FnOnce() -> T>(f: F) -> (T, Duration) {
+    let start = Instant::now();
+    let result = f();
+
+    (result, start.elapsed())
+}
+
+/// Maps a file into memory, so that a dictionary can borrow its words from the file instead of copying them.
+/// The file must not be modified while it is mapped.
+///
+/// # Arguments
+/// * `path` - The filename.
+pub fn map_file(path: &str) -> Result<Mmap, io::Error> {
+    let file = File::open(path)?;
+
+    // The map is read only, the caller is responsible for not modifying the file.
+    unsafe { Mmap::map(&file) }
+}
+
+/// A dictionary of correctly spelled words. Lookups ignore case.
+/// The words may be borrowed from a buffer which outlives the dictionary, like a memory mapped file.
+pub struct Dictionary<'a> {
+    /// The dictionary's lowercase words.
+    words: HashTable<Cow<'a, str>, ()>
+}
+
+impl <'a> Dictionary<'a> {
+    /// Creates an empty dictionary.
+    pub fn new() -> Self {
+        Dictionary {
+            words: HashTable::new()
+        }
+    }
+
+    /// Loads a dictionary file, which contains one word per line or was written by `save_compiled`.
     ///
     /// # Arguments
-    /// * `data` - The node's data.
-    pub fn new(data: T) -> Self {
-        Self {
-            data,
-            next: None
+    /// * `path` - The dictionary's filename.
+    pub fn load(path: &str) -> Result<Self, io::Error> {
+        let bytes = fs::read(path)?;
+
+        if Self::is_compiled(&bytes) {
+            Ok(Dictionary::from_compiled(&bytes)?.into_owned())
+        } else {
+            Self::from_reader(&bytes[..])
         }
     }
 
-    /// Adds a new item to the end of the list.
+    /// Loads a Hunspell dictionary, adding every form of its words which can be created with the affix rules.
     ///
     /// # Arguments
-    /// * `data` - The data to add.
-    pub fn add(&mut self, data: T) {
-        match self.next.as_mut() {
-            Some(next) => next.add(data),
-            None => self.next = Some(Box::new(ListNode::new(data)))
+    /// * `dic_path` - The dictionary (.dic) file's name.
+    /// * `aff_path` - The affix (.aff) file's name.
+    pub fn load_hunspell(dic_path: &str, aff_path: &str) -> Result<Self, io::Error> {
+        let affixes = Affixes::load(aff_path)?;
+        let text = fs::read_to_string(dic_path)?;
+        let mut lines = text.lines().peekable();
+
+        // The first line is the approximate number of words.
+        let count = lines.next_if(|line| line.trim().parse::<usize>().is_ok()).map_or(0, |line| line.trim().parse().unwrap());
+
+        let mut dictionary = Dictionary {
+            words: HashTable::with_capacity(count)
+        };
+
+        for line in lines {
+            for word in affixes.expand(line) {
+                if !word.is_empty() {
+                    dictionary.add(&word);
+                }
+            }
         }
+
+        Ok(dictionary)
     }
-}
 
-/// An iterator for a linked list.
-struct ListIter<'a, T>(Option<&'a ListNode<T>>);
+    /// Loads a dictionary file written by `save_compiled`.
+    ///
+    /// # Arguments
+    /// * `path` - The dictionary's filename.
+    pub fn load_compiled(path: &str) -> Result<Self, io::Error> {
+        Ok(Dictionary::from_compiled(&fs::read(path)?)?.into_owned())
+    }
 
-impl <'a, T> Iterator for ListIter<'a, T> {
-    type Item = &'a T;
+    /// Reads a dictionary with one word per line.
+    ///
+    /// # Arguments
+    /// * `reader` - The dictionary's contents.
+    pub fn from_reader<R: BufRead>(reader: R) -> Result<Self, io::Error> {
+        let words: Vec<String> = reader.lines().collect::<Result<_, _>>()?;
+        let mut dictionary = Dictionary {
+            words: HashTable::with_capacity(words.len())
+        };
+
+        for word in words.iter() {
+            dictionary.add(word);
+        }
 
-    fn next(&mut self) -> Option<Self::Item> {
-        match self.0 {
-            None => None,
-            Some(node) => {
-                let data = &node.data;
-                self.0 = node.next.as_ref().map(|next| next.as_ref());
-                Some(data)
+        Ok(dictionary)
+    }
+
+    /// Reads a dictionary with one word per line from a buffer without copying its words, unless they have to be
+    /// converted to lowercase.
+    ///
+    /// # Arguments
+    /// * `bytes` - The dictionary's contents, which must be valid UTF-8. For example a file mapped with `map_file`.
+    pub fn from_bytes(bytes: &'a [u8]) -> Result<Self, io::Error> {
+        let text = str::from_utf8(bytes).map_err(|err| io::Error::new(ErrorKind::InvalidData, err))?;
+        let mut dictionary = Dictionary {
+            words: HashTable::with_capacity(text.lines().count())
+        };
+
+        for word in text.lines() {
+            if word.chars().any(char::is_uppercase) {
+                dictionary.words.add(Cow::Owned(word.to_lowercase()));
+            } else {
+                dictionary.words.add(Cow::Borrowed(word));
             }
         }
+
+        Ok(dictionary)
     }
-}
 
-/// A custom singly linked list.
-#[derive(Clone)]
-struct List<T> {
-    /// The first node in the list.
-    head: Option<ListNode<T>>
-}
+    /// Checks if a buffer holds a compiled dictionary.
+    ///
+    /// # Arguments
+    /// * `bytes` - The buffer.
+    pub fn is_compiled(bytes: &[u8]) -> bool {
+        bytes.starts_with(COMPILED_MAGIC)
+    }
 
-impl <T> List<T> {
-    /// Creates a new empty linked list.
-    pub fn new() -> Self {
-        Self {
-            head: None
+    /// Reads a compiled dictionary from a buffer without copying its words or hashing them again.
+    /// If the words are not where the table expects them, which happens if the hash function changed after the
+    /// dictionary was compiled, the table is built again.
+    ///
+    /// # Arguments
+    /// * `bytes` - The dictionary's contents, as written by `save_compiled`. For example a file mapped with `map_file`.
+    pub fn from_compiled(bytes: &'a [u8]) -> Result<Self, io::Error> {
+        let invalid = || io::Error::new(ErrorKind::InvalidData, "Invalid compiled dictionary");
+        let body = bytes.strip_prefix(COMPILED_MAGIC).ok_or_else(invalid)?;
+        let capacity = read_u32(body, 0).ok_or_else(invalid)? as usize;
+        let count = read_u32(body, 1).ok_or_else(invalid)? as usize;
+        let blob = body.get((2 + capacity + count) * 4..).ok_or_else(invalid)?;
+        let text = str::from_utf8(blob).map_err(|err| io::Error::new(ErrorKind::InvalidData, err))?;
+
+        if capacity == 0 {
+            return Err(invalid());
+        }
+
+        let mut cells = Vec::with_capacity(capacity);
+        let (mut word, mut start) = (0, 0);
+
+        for cell in 0..capacity {
+            let end = read_u32(body, 2 + cell).ok_or_else(invalid)? as usize;
+            let mut chain = List::new();
+
+            if end < word || end > count {
+                return Err(invalid());
+            }
+
+            for index in word..end {
+                let word_end = read_u32(body, 2 + capacity + index).ok_or_else(invalid)? as usize;
+                chain.add((Cow::Borrowed(text.get(start..word_end).ok_or_else(invalid)?), ()));
+                start = word_end;
+            }
+
+            word = end;
+            cells.push(chain);
+        }
+
+        if word != count {
+            return Err(invalid());
+        }
+
+        let words = HashTable::from_cells(cells);
+        let step = (count / COMPILED_SAMPLES).max(1);
+        let hashed = words.cells().iter().flatten().step_by(step).all(|(word, _)| words.contains(word.as_ref()));
+
+        if hashed {
+            Ok(Dictionary { words })
+        } else {
+            let mut dictionary = Dictionary {
+                words: HashTable::with_capacity(count)
+            };
+
+            for (word, _) in words.cells().iter().flatten() {
+                dictionary.words.add(word.clone());
+            }
+
+            Ok(dictionary)
         }
     }
 
-    /// Adds a new item to the end of the list.
+    /// Writes the dictionary into a compact binary file which can be loaded without parsing or hashing its words.
     ///
     /// # Arguments
-    /// * `data` - The data to add.
-    pub fn add(&mut self, data: T) {
-        match self.head.as_mut() {
-            Some(head) => head.add(data),
-            None => self.head = Some(ListNode::new(data))
+    /// * `path` - The file's name.
+    pub fn save_compiled(&self, path: &str) -> Result<(), io::Error> {
+        let cells = self.words.cells();
+        let mut cell_ends = Vec::with_capacity(cells.len());
+        let mut word_ends = Vec::with_capacity(self.len());
+        let mut blob = String::new();
+
+        for cell in cells {
+            for (word, _) in cell {
+                blob.push_str(word);
+                word_ends.push(blob.len() as u32);
+            }
+
+            cell_ends.push(word_ends.len() as u32);
         }
+
+        let mut writer = BufWriter::new(File::create(path)?);
+        writer.write_all(COMPILED_MAGIC)?;
+
+        for n in [cells.len() as u32, word_ends.len() as u32].iter().chain(&cell_ends).chain(&word_ends) {
+            writer.write_all(&n.to_le_bytes())?;
+        }
+
+        writer.write_all(blob.as_bytes())?;
+        writer.flush()
+    }
+
+    /// Copies the words borrowed by the dictionary, so that it no longer depends on the buffer it was read from.
+    pub fn into_owned(self) -> Dictionary<'static> {
+        let cells = self.words
+            .into_cells()
+            .into_iter()
+            .map(|mut chain| {
+                let mut owned = List::new();
+
+                for (word, _) in chain.drain_to_vec() {
+                    owned.add((Cow::Owned(word.into_owned()), ()));
+                }
+
+                owned
+            })
+            .collect();
+
+        Dictionary {
+            words: HashTable::from_cells(cells)
+        }
+    }
+
+    /// Adds the words in a file with one word per line to the dictionary. Returns the number of new words.
+    ///
+    /// # Arguments
+    /// * `path` - The file's name.
+    pub fn add_words(&mut self, path: &str) -> Result<usize, io::Error> {
+        let mut added = 0;
+
+        for word in BufReader::new(File::open(path)?).lines() {
+            if self.add(&word?) {
+                added += 1;
+            }
+        }
+
+        Ok(added)
     }
-}
 
-impl <'a, T> IntoIterator for &'a List<T> {
-    type Item = &'a T;
-    type IntoIter = ListIter<'a, T>;
+    /// Adds a word to the dictionary. Returns `false` if the word was already in the dictionary.
+    ///
+    /// # Arguments
+    /// * `word` - The word.
+    pub fn add(&mut self, word: &str) -> bool {
+        self.words.add(Cow::Owned(word.to_lowercase()))
+    }
+
+    /// Checks if a word is spelled correctly, which means that it is in the dictionary.
+    ///
+    /// # Arguments
+    /// * `word` - The word. Case insensitive.
+    pub fn check(&self, word: &str) -> bool {
+        self.words.contains(word.to_lowercase().as_str())
+    }
 
-    fn into_iter(self) -> Self::IntoIter {
-        ListIter(self.head.as_ref())
+    /// Number of words in the dictionary.
+    pub fn len(&self) -> usize {
+        self.words.len()
+    }
+
+    /// Checks if the dictionary has no words.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
     }
 }
 
-/// A custom hash table for quick lookup of unique data.
-struct HashTable<T> {
-    /// Vec which contains the actual table with data.
-    table: Vec<List<T>>,
-    /// Size of the table in cells.
-    capacity: usize,
-    /// Number of items in the table.
-    len: usize
+/// Reads the nth little endian `u32` in a buffer.
+///
+/// # Arguments
+/// * `bytes` - The buffer.
+/// * `index` - The number's index, in `u32`s.
+fn read_u32(bytes: &[u8], index: usize) -> Option<u32> {
+    let number = bytes.get(index * 4..index * 4 + 4)?;
+
+    Some(u32::from_le_bytes(number.try_into().unwrap()))
 }
 
-impl <T: Clone> HashTable<T> {
-    /// Default capacity.
-    const BASE_CAPACITY: usize = 19;
+/// Splits a text into lowercase words, reading one line at a time.
+pub struct Words<R> {
+    /// The text.
+    reader: R,
+    /// Regex used to split words in the text.
+    split_regex: Regex,
+    /// Regex which matches inline code, HTML tags and entities and URLs, which are removed before splitting words.
+    /// `None` if the text is not read as markup.
+    markup_regex: Option<Regex>,
+    /// If the last line read was inside a Markdown code block, which is skipped.
+    in_code_block: bool,
+    /// Words in the last line read which have not been returned yet.
+    pending: VecDeque<String>
+}
 
-    /// Creates a new hashtable with the supplied capacity.
+impl <R: BufRead> Words<R> {
+    /// Creates an iterator over the words of a text.
     ///
     /// # Arguments
-    /// * `capacity` - The table's capacity.
-    pub fn with_capacity(mut capacity: usize) -> Self {
-        capacity = Self::next_capacity(capacity);
-
-        Self {
-            table: vec![List::new(); capacity],
-            capacity,
-            len: 0
+    /// * `reader` - The text.
+    pub fn new(reader: R) -> Self {
+        Words {
+            reader,
+            split_regex: Regex::new("[^a-zA-Z']+").unwrap(),
+            markup_regex: None,
+            in_code_block: false,
+            pending: VecDeque::new()
         }
     }
 
-    /// Creates a new hashtable with the default capacity.
-    pub fn new() -> Self {
-        Self::with_capacity(Self::BASE_CAPACITY)
+    /// Reads the text as HTML or Markdown: code blocks, inline code, tags, entities and URLs are skipped.
+    /// Tags which span multiple lines are not recognized.
+    pub fn markup(mut self) -> Self {
+        self.markup_regex = Some(Regex::new(r"`[^`]*`|<[^>]*>|&#?\w+;|\b(?:https?|ftp)://\S+|\bwww\.\S+").unwrap());
+        self
     }
 
-    /// Computes the next prime number at least twice as big as the table's current capacity.
+    /// Splits a line into words, keeping them to be returned.
     ///
     /// # Arguments
-    /// * `current` - The table's current capacity.
-    fn next_capacity(current: usize) -> usize {
-        for i in current.. {
-            if Self::is_prime(i) {
-                return i
+    /// * `line` - The line.
+    fn split_line(&mut self, line: &str) {
+        let line = match &self.markup_regex {
+            Some(markup_regex) => {
+                let fence = line.trim_start().starts_with("```") || line.trim_start().starts_with("~~~");
+                self.in_code_block ^= fence;
+
+                if fence || self.in_code_block {
+                    return;
+                }
+
+                markup_regex.replace_all(line, " ")
+            },
+            None => Cow::Borrowed(line)
+        };
+
+        for word in self.split_regex.split(&line.to_lowercase()) {
+            if !word.is_empty() {
+                self.pending.push_back(word.to_string());
+            }
+        }
+    }
+}
+
+impl <R: BufRead> Iterator for Words<R> {
+    type Item = Result<String, io::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut line = String::new();
+
+        while self.pending.is_empty() {
+            line.clear();
+
+            match self.reader.read_line(&mut line) {
+                Ok(0) => return None,
+                Ok(_) => self.split_line(&line),
+                Err(err) => return Some(Err(err))
             }
         }
 
-        return 0
+        self.pending.pop_front().map(Ok)
     }
+}
+
+/// Counts how many times each word appears in a text.
+///
+/// # Arguments
+/// * `words` - The text's words.
+pub fn word_frequencies<R: BufRead>(words: Words<R>) -> Result<HashTable<String, u32>, io::Error> {
+    let mut frequencies = HashTable::new();
+
+    for word in words {
+        *frequencies.get_or_insert_with(word?, || 0) += 1;
+    }
+
+    Ok(frequencies)
+}
+
+/// Spell checks a text, one line at a time. Iterates over the misspelled words in the order they appear.
+/// Strings can be checked by reading their bytes, for example `SpellChecker::new(&dictionary, text.as_bytes())`.
+pub struct SpellChecker<'a, R> {
+    /// The dictionary to use as reference to find words.
+    dictionary: &'a Dictionary<'a>,
+    /// Words which are never reported, even if they are not in the dictionary.
+    ignored: Option<&'a Dictionary<'a>>,
+    /// Words accepted while checking the text, which are not reported again.
+    accepted: Dictionary<'static>,
+    /// The text's words.
+    text: Words<R>,
+    /// Number of words checked so far.
+    words: u32,
+    /// Number of misspelled words found so far.
+    misspelled: u32
+}
 
-    /// Finds if a number is prime.
+impl <'a, R: BufRead> SpellChecker<'a, R> {
+    /// Creates a spell checker for a text.
     ///
     /// # Arguments
-    /// * `n` - The number.
-    fn is_prime(n: usize) -> bool {
-        (2..n / 2).all(|i| n % i > 0)
+    /// * `dictionary` - The dictionary to use as reference to find words.
+    /// * `reader` - The text.
+    pub fn new(dictionary: &'a Dictionary<'a>, reader: R) -> Self {
+        SpellChecker {
+            dictionary,
+            ignored: None,
+            accepted: Dictionary::new(),
+            text: Words::new(reader),
+            words: 0,
+            misspelled: 0
+        }
     }
-}
 
-impl HashTable<String> {
-    /// Adds an item to the hashtable.
+    /// Sets words which are never reported, even if they are not in the dictionary.
     ///
     /// # Arguments
-    /// * `item` - The item to add.
-    pub fn add(&mut self, item: String) {
-        self.len += 1;
-        let hash = self.hash(&item);
-        self.table[hash].add(item);
+    /// * `ignored` - The words.
+    pub fn ignore(mut self, ignored: &'a Dictionary<'a>) -> Self {
+        self.ignored = Some(ignored);
+        self
+    }
+
+    /// Checks the text as HTML or Markdown: code blocks, inline code, tags, entities and URLs are skipped.
+    pub fn markup(mut self) -> Self {
+        self.text = self.text.markup();
+        self
     }
 
-    /// Checks if an item is in the hashtable.
+    /// Accepts a word, so that it is not reported again in the rest of the text.
     ///
     /// # Arguments
-    /// * `item` - The item.
-    pub fn contains(&self, item: &str) -> bool {
-        let hash = self.hash(&item);
+    /// * `word` - The word.
+    pub fn accept(&mut self, word: &str) {
+        self.accepted.add(word);
+    }
 
-        (&self.table[hash])
-            .into_iter()
-            .any(|list_item| list_item == item)
+    /// Number of words checked so far.
+    pub fn words(&self) -> u32 {
+        self.words
+    }
+
+    /// Number of misspelled words found so far.
+    pub fn misspelled(&self) -> u32 {
+        self.misspelled
     }
 
-    /// Computes an item's hash value.
+    /// Checks if a word is in the dictionary, is ignored or was accepted.
     ///
     /// # Arguments
-    /// * `item` - The item.
-    fn hash(&self, item: &str) -> usize {
-        let len = item.len();
+    /// * `word` - The word.
+    fn is_correct(&self, word: &str) -> bool {
+        self.dictionary.check(word)
+            || self.ignored.is_some_and(|ignored| ignored.check(word))
+            || self.accepted.check(word)
+    }
+}
 
-        let value: usize = item.as_bytes()
-            .into_iter()
-            .enumerate()
-            .map(|(i, &byte)| byte as usize * Self::BASE_CAPACITY * (len - i - 1))
-            .sum();
+impl <'a, R: BufRead> Iterator for SpellChecker<'a, R> {
+    type Item = Result<String, io::Error>;
 
-        value % self.capacity
+    fn next(&mut self) -> Option<Self::Item> {
+        // Words are checked as they are read, so that accepted words are not reported again.
+        loop {
+            let word = match self.text.next()? {
+                Ok(word) => word,
+                Err(err) => return Some(Err(err))
+            };
+
+            self.words += 1;
+
+            if !self.is_correct(&word) {
+                self.misspelled += 1;
+                return Some(Ok(word));
+            }
+        }
+    }
+}
+
+/// Opens the text to check or analyze.
+///
+/// # Arguments
+/// * `filename` - The text's filename, stdin is read if it's `None`.
+fn open_text(filename: Option<&String>) -> Box<dyn BufRead> {
+    match filename {
+        Some(filename) => Box::new(BufReader::new(File::open(filename).unwrap())),
+        None => Box::new(io::stdin().lock())
     }
 }
 
-/// Loads a dictionary file into a hashtable.
+/// Finds the most frequent words, from the most to the least frequent. Ties are sorted alphabetically.
 ///
 /// # Arguments
-/// * `filename` - The dictionary's filename.
-fn load_dict(filename: &str) -> HashTable<String> {
-    let dict_file = BufReader::new(File::open(filename).unwrap());
-    let words: Vec<_> = dict_file.lines().collect::<Result<Vec<_>, _>>().unwrap();
-    let mut dictionary = HashTable::with_capacity(words.len());
+/// * `frequencies` - How many times each word appears, as counted by `word_frequencies`.
+/// * `top` - Number of words to find.
+fn top_frequencies(frequencies: &HashTable<String, u32>, top: usize) -> Vec<WordFrequency<'_>> {
+    let mut counts: Vec<WordFrequency> = frequencies
+        .iter()
+        .map(|(word, count)| WordFrequency { word, count: *count })
+        .collect();
+
+    counts
+        .partial_sort_by(top, &|a, b| a.count > b.count || (a.count == b.count && a.word < b.word))
+        .into_iter()
+        .cloned()
+        .collect()
+}
+
+/// Prints the most frequent words in a text, ties are sorted alphabetically.
+///
+/// # Arguments
+/// * `words` - The text's words.
+/// * `top` - Number of words to print.
+/// * `json` - If the words should be printed as JSON.
+fn print_frequencies<R: BufRead>(words: Words<R>, top: usize, json: bool) {
+    let frequencies = word_frequencies(words).unwrap();
+    let top = top_frequencies(&frequencies, top);
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&top).unwrap());
+        return;
+    }
+
+    println!("WORD FREQUENCIES");
 
-    for word in words.into_iter() {
-        dictionary.add(word);
+    for frequency in top {
+        println!("{:<24}{}", frequency.word, frequency.count);
     }
 
-    dictionary
+    println!();
+    println!("DISTINCT WORDS:       {}", frequencies.len());
 }
 
-/// Spell checks a text file in order to find misspelled words.
+/// Asks the user whether a misspelled word is correct and appends it to the personal dictionary if so.
+/// Returns `true` if the word was accepted.
 ///
 /// # Arguments
-/// * `filename` - The text file's name.
-/// * `dictionary` - The dictionary to use as reference to find words.
-/// * `split_regex` - Regex used to split words in the text.
-fn check(filename: &str, dictionary: &HashTable<String>, split_regex: &Regex) -> (u32, u32) {
-    let file = BufReader::new(File::open(filename).unwrap());
-    let mut words = 0;
-    let mut misspelled = 0;
-
-    for line in file.lines() {
-        for word in split_regex.split(&line.unwrap().to_lowercase()) {
-            if !word.is_empty() {
-                if !dictionary.contains(word) {
-                    println!("{word}");
-                    misspelled += 1;
-                }
+/// * `word` - The word.
+/// * `personal` - The personal dictionary's filename.
+fn accept_word(word: &str, personal: &str) -> bool {
+    let answer = helpers::read_line(&format!("Add \"{}\" to the personal dictionary? [y/N] ", word)).unwrap();
 
-                words += 1;
-            }
-        }
+    if !answer.eq_ignore_ascii_case("y") {
+        return false;
     }
 
-    (words, misspelled)
+    let mut file = OpenOptions::new().create(true).append(true).open(personal).unwrap();
+    writeln!(file, "{}", word).unwrap();
+
+    true
 }
 
 pub fn main() {
-    // Reads filenames from command line args.
-    let split_regex = Regex::new("[^a-zA-Z']+").unwrap();
+    // Reads filenames and options from command line args.
+    let usage = "Usage:\n ./speller [--stats-json] [--mmap] [--compile <output>] [--personal <words>] [--ignore <words>] [--interactive] [--markup] [--format <text|json>] <dictionary> [text]\n ./speller --frequencies <top> [--markup] [--format <text|json>] [text]";
     let mut args = env::args().skip(1);
-    let dict_filename = args.next().unwrap();
-    let filename = args.next().unwrap();
+    let (mut stats_json, mut mmap, mut interactive, mut markup) = (false, false, false, false);
+    let (mut compile, mut personal, mut ignore) = (None, None, None);
+    let mut json = false;
+    let mut frequencies = None;
+    let mut filenames = Vec::new();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--stats-json" => stats_json = true,
+            "--mmap" => mmap = true,
+            "--interactive" => interactive = true,
+            "--markup" => markup = true,
+            "--compile" => compile = Some(args.next().expect(usage)),
+            "--personal" => personal = Some(args.next().expect(usage)),
+            "--ignore" => ignore = Some(args.next().expect(usage)),
+            "--frequencies" => frequencies = Some(args.next().and_then(|top| top.parse().ok()).expect(usage)),
+            "--format" => json = match args.next().as_deref() {
+                Some("json") => true,
+                Some("text") => false,
+                _ => panic!("The format must be text or json\n{}", usage)
+            },
+            _ => filenames.push(arg)
+        }
+    }
 
-    // Loads the dictionary.
-    let dictionary = load_dict(&dict_filename);
+    // Counts the text's words instead of spell checking it, no dictionary is needed.
+    if let Some(top) = frequencies {
+        let filename = match &filenames[..] {
+            [] => None,
+            [filename] => Some(filename).filter(|filename| *filename != "-"),
+            _ => panic!("{}", usage)
+        };
+
+        let words = Words::new(open_text(filename));
+        print_frequencies(if markup { words.markup() } else { words }, top, json);
+        return;
+    }
+
+    // The text is read from stdin if its filename is missing or "-".
+    let (dict_filename, filename) = match &filenames[..] {
+        [dict_filename] => (dict_filename, None),
+        [dict_filename, filename] => (dict_filename, Some(filename).filter(|filename| *filename != "-")),
+        _ => panic!("{}", usage)
+    };
+
+    // Accepted words are appended to the personal dictionary, the answers are read from stdin.
+    if interactive && (personal.is_none() || filename.is_none() || json) {
+        panic!("Interactive mode needs a personal dictionary, the text in a file and text output\n{}", usage);
+    }
+
+    // Loads the dictionary, borrowing its words from the mapped file when using mmap.
+    // Hunspell dictionaries are read with the affix file next to them and can't borrow their words.
+    let hunspell = dict_filename.ends_with(".dic");
+    let (map, map_time) = timed(|| (mmap && !hunspell).then(|| map_file(dict_filename).unwrap()));
+
+    let (mut dictionary, build_time) = timed(|| match &map {
+        _ if hunspell => {
+            let aff_filename = Path::new(dict_filename).with_extension("aff");
+            Dictionary::load_hunspell(dict_filename, &aff_filename.to_string_lossy()).unwrap()
+        },
+        Some(map) if Dictionary::is_compiled(map) => Dictionary::from_compiled(map).unwrap(),
+        Some(map) => Dictionary::from_bytes(map).unwrap(),
+        None => Dictionary::load(dict_filename).unwrap()
+    });
+
+    // Saves the compiled dictionary so that the next runs can load it faster.
+    if let Some(output) = compile {
+        dictionary.save_compiled(&output).unwrap();
+    }
+
+    // Adds the personal words, the personal dictionary may not exist yet in interactive mode.
+    let (ignored, words_time) = timed(|| {
+        if let Some(personal) = &personal {
+            match dictionary.add_words(personal) {
+                Err(err) if err.kind() == ErrorKind::NotFound && interactive => (),
+                result => { result.unwrap(); }
+            }
+        }
+
+        ignore.map(|ignore| Dictionary::load(&ignore).unwrap())
+    });
+
+    let load_time = map_time + build_time + words_time;
 
     // Spell checks text file.
-    println!("MISSPELLED WORDS");
-    let (words, misspelled) = check(&filename, &dictionary, &split_regex);
+    if !json {
+        println!("MISSPELLED WORDS");
+    }
+
+    let ((words, misspelled, misspelled_words), check_time) = timed(|| {
+        // Stdout is line buffered, so misspelled words are shown as soon as they are found.
+        let mut checker = SpellChecker::new(&dictionary, open_text(filename));
+        let mut accepted = 0;
+        let mut misspelled_words = Vec::new();
+        let mut rejected: HashTable<String, ()> = HashTable::new();
+
+        if let Some(ignored) = &ignored {
+            checker = checker.ignore(ignored);
+        }
+
+        if markup {
+            checker = checker.markup();
+        }
+
+        while let Some(word) = checker.next() {
+            let word = word.unwrap();
+
+            // Words are only asked about once.
+            if interactive && !rejected.contains(&word) {
+                if accept_word(&word, personal.as_deref().unwrap()) {
+                    checker.accept(&word);
+                    accepted += 1;
+                    continue;
+                }
+
+                rejected.add(word.clone());
+            }
+
+            if json {
+                misspelled_words.push(word);
+            } else {
+                println!("{}", word);
+            }
+        }
+
+        (checker.words(), checker.misspelled() - accepted, misspelled_words)
+    });
+
+    let (dictionary_words, size_time) = timed(|| dictionary.len());
+    let (_, drop_time) = timed(|| drop(dictionary));
+    let (_, unmap_time) = timed(|| drop(map));
+    let unload_time = drop_time + unmap_time;
+
+    let stats = SpellerStats {
+        words_misspelled: misspelled,
+        words_in_dictionary: dictionary_words,
+        words_in_text: words,
+        time_in_load: load_time.as_secs_f64(),
+        time_in_check: check_time.as_secs_f64(),
+        time_in_size: size_time.as_secs_f64(),
+        time_in_unload: unload_time.as_secs_f64(),
+        time_in_total: (load_time + check_time + size_time + unload_time).as_secs_f64()
+    };
+
+    // Prints the summary.
+    if json {
+        let report = SpellerReport {
+            misspelled: misspelled_words,
+            stats
+        };
+
+        println!("{}", serde_json::to_string_pretty(&report).unwrap());
+        return;
+    }
+
+    println!();
+
+    if stats_json {
+        println!("{}", serde_json::to_string_pretty(&stats).unwrap());
+    } else {
+        stats.print();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Creates a dictionary from a list of words.
+    ///
+    /// # Arguments
+    /// * `words` - The words, one per line.
+    fn dictionary(words: &str) -> Dictionary<'static> {
+        Dictionary::from_reader(words.as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn checks_strings_ignoring_case() {
+        let dictionary = dictionary("the\nCat\nsat\non\nmat\n");
+        let mut checker = SpellChecker::new(&dictionary, "The cat SAT on teh mat.\nThe dgo sat.\n".as_bytes());
+        let misspelled: Vec<String> = checker.by_ref().collect::<Result<_, _>>().unwrap();
+
+        assert!(dictionary.check("CAT"));
+        assert_eq!(misspelled, vec!["teh", "dgo"]);
+        assert_eq!(checker.words(), 9);
+        assert_eq!(checker.misspelled(), 2);
+    }
+
+    /// Compiles a dictionary and returns the compiled file's contents.
+    ///
+    /// # Arguments
+    /// * `dictionary` - The dictionary.
+    /// * `name` - The compiled file's name.
+    fn compile(dictionary: &Dictionary, name: &str) -> Vec<u8> {
+        let path = env::temp_dir().join(format!("{}-{}", std::process::id(), name));
+        let path = path.to_str().unwrap();
 
-    println!("WORDS MISSPELLED:     {}", misspelled);
-    println!("WORDS IN DICTIONARY:  {}", dictionary.len);
-    println!("WORDS IN TEXT:        {}", words);
-}
\ No newline at end of file
+        dictionary.save_compiled(path).unwrap();
+        let bytes = fs::read(path).unwrap();
+        fs::remove_file(path).unwrap();
+
+        bytes
+    }
+
+    #[test]
+    fn compiled_dictionary_round_trips() {
+        let words = ["apple", "Banana", "cherry", "déjà", "elder", "fig", "grape", "honeydew", "kiwi", "lemon"];
+        let dictionary = dictionary(&words.join("\n"));
+        let bytes = compile(&dictionary, "round-trip.dic");
+        let loaded = Dictionary::from_compiled(&bytes).unwrap();
+
+        assert!(Dictionary::is_compiled(&bytes));
+        assert_eq!(loaded.len(), words.len());
+        assert!(words.iter().all(|word| loaded.check(word)));
+        assert!(!loaded.check("mango"));
+
+        let owned = loaded.into_owned();
+        assert!(words.iter().all(|word| owned.check(word)));
+    }
+
+    #[test]
+    fn rejects_truncated_and_corrupt_compiled_dictionaries() {
+        let dictionary = dictionary("one
+two
+three
+four
+five
+");
+        let bytes = compile(&dictionary, "corrupt.dic");
+        let header = COMPILED_MAGIC.len();
+        let capacity = read_u32(&bytes[header..], 0).unwrap() as usize;
+
+        // Every truncation is missing either part of the tables or the end of the last word.
+        for length in 0..bytes.len() {
+            assert!(Dictionary::from_compiled(&bytes[..length]).is_err(), "{}", length);
+        }
+
+        let corrupt = |index: usize, value: u32| {
+            let mut corrupt = bytes.clone();
+            corrupt[header + index * 4..header + index * 4 + 4].copy_from_slice(&value.to_le_bytes());
+            let rejected = Dictionary::from_compiled(&corrupt).is_err();
+            rejected
+        };
+
+        assert!(corrupt(0, 0), "no cells");
+        assert!(corrupt(0, u32::MAX), "more cells than the buffer holds");
+        assert!(corrupt(1, 4), "fewer words than the cells hold");
+        assert!(corrupt(2, 6), "a cell ends after the last word");
+        assert!(corrupt(2 + capacity, 100), "a word ends after the text");
+        assert!(corrupt(2 + capacity + 1, 0), "a word ends before it starts");
+        assert!(Dictionary::from_compiled(b"CS50DIC2").is_err());
+    }
+
+    #[test]
+    fn compiled_dictionary_is_hashed_again_if_words_are_misplaced() {
+        let words = ["alpha", "beta", "gamma", "delta", "epsilon"];
+        let mut cells: Vec<List<(Cow<str>, ())>> = (0..7).map(|_| List::new()).collect();
+
+        // Every word is in the first cell, as if the hash function had changed.
+        for word in words {
+            cells[0].add((Cow::Borrowed(word), ()));
+        }
+
+        let misplaced = Dictionary { words: HashTable::from_cells(cells) };
+        assert!(!words.iter().all(|word| misplaced.check(word)));
+
+        let bytes = compile(&misplaced, "misplaced.dic");
+        let loaded = Dictionary::from_compiled(&bytes).unwrap();
+
+        assert_eq!(loaded.len(), words.len());
+        assert!(words.iter().all(|word| loaded.check(word)));
+    }
+
+    #[test]
+    fn skips_ignored_and_accepted_words() {
+        let english = dictionary("a\nis\nname\nmy\n");
+        let ignored = dictionary("Rustacean\n");
+        let mut checker = SpellChecker::new(&english, "My name is Ferris.\nFerris is a rustacean, ferris is a crab.\n".as_bytes())
+            .ignore(&ignored);
+
+        assert_eq!(checker.next().unwrap().unwrap(), "ferris");
+        checker.accept("Ferris");
+
+        let misspelled: Vec<String> = checker.by_ref().collect::<Result<_, _>>().unwrap();
+
+        assert_eq!(misspelled, vec!["crab"]);
+        assert_eq!(checker.words(), 12);
+        assert_eq!(checker.misspelled(), 2);
+    }
+
+    #[test]
+    fn skips_markup() {
+        let text = "\
+# Install <em>the</em> crate&nbsp;now
+Run `cargo add cratte` from https://crates.io/crates/regex or www.exampel.com.
+
+```rust
+let mut vecc = Vec::new();
+```
+~~~
+fn mian() {}
+~~~
+Done &#8212; <a href=\"linc\">enjoy</a>!
+";
+        let words: Vec<String> = Words::new(text.as_bytes()).markup().collect::<Result<_, _>>().unwrap();
+
+        assert_eq!(words, vec!["install", "the", "crate", "now", "run", "from", "or", "done", "enjoy"]);
+
+        let plain = Words::new(text.as_bytes()).count();
+        assert!(plain > words.len());
+    }
+
+    #[test]
+    fn counts_word_frequencies() {
+        let frequencies = word_frequencies(Words::new("b a c\nA b d B\nc e".as_bytes())).unwrap();
+        let top: Vec<(&str, u32)> = top_frequencies(&frequencies, 4).iter().map(|frequency| (frequency.word, frequency.count)).collect();
+
+        assert_eq!(frequencies.len(), 5);
+        assert_eq!(frequencies.get("b"), Some(&3));
+        assert_eq!(frequencies.get("e"), Some(&1));
+        assert_eq!(top, vec![("b", 3), ("a", 2), ("c", 2), ("d", 1)]);
+        assert_eq!(top_frequencies(&frequencies, 10).len(), 5);
+    }
+}
@@ -4,4 +4,5 @@ pub mod jar;
 pub mod seven_day_average;
 pub mod taqueria;
 pub mod world_cup;
-pub mod dna;
\ No newline at end of file
+pub mod dna;
+pub mod dnabench;
@@ -1,34 +1,273 @@
-use std::collections::HashMap;
 use std::env;
+use std::fmt::{self, Debug, Formatter};
 use std::fs::File;
-use std::io::{BufReader, Read};
+use std::io::{self, BufReader, Read};
 use csv::ReaderBuilder;
 use itertools::Itertools;
+use rayon::prelude::*;
 
-/// Single DNA record. A Hashmap which contains the name of the person and the longest consecutive sequence of an STR.
-type DnaRecord = HashMap<String, String>;
+/// A short tandem repeat (STR), a short DNA sequence which is repeated consecutively a different number of times in
+/// each person's DNA.
+pub type Str = String;
 
-/// Reads the database file. Returns a Vector containing each record in the DNA database.
+/// Number of closest people reported when a sequence does not match anyone.
+const NEAREST_LIMIT: usize = 3;
+
+/// Error which may occur while reading DNA files.
+pub enum DnaError {
+    /// The file could not be opened.
+    IoError(io::Error),
+    /// The file is not a valid CSV file.
+    CsvError(csv::Error),
+    /// The database's header does not start with a name column.
+    MissingNameError,
+    /// A record has a count which is not a non-negative integer.
+    InvalidCountError(String)
+}
+
+impl Debug for DnaError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            DnaError::IoError(err) => write!(f, "Could not open file: {}", err),
+            DnaError::CsvError(err) => write!(f, "Malformed CSV file: {}", err),
+            DnaError::MissingNameError => write!(f, "The database's first column must be the name"),
+            DnaError::InvalidCountError(count) => write!(f, "Invalid STR count: {}", count)
+        }
+    }
+}
+
+/// A person in the DNA database.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Record {
+    /// The person's name.
+    pub name: String,
+    /// Longest run of each of the database's STRs in the person's DNA, in the same order as the database's STRs.
+    pub counts: Vec<usize>
+}
+
+/// A database of people and the longest runs of some STRs in their DNA.
+#[derive(Debug, Clone)]
+pub struct StrDatabase {
+    /// The STRs, in the order of the file's columns.
+    strs: Vec<Str>,
+    /// The people.
+    records: Vec<Record>
+}
+
+impl StrDatabase {
+    /// Creates a database from its STRs and people.
+    ///
+    /// # Arguments
+    /// * `strs` - The STRs whose runs are counted.
+    /// * `records` - The people, whose counts are in the same order as the STRs.
+    pub fn new(strs: Vec<Str>, records: Vec<Record>) -> Self {
+        assert!(records.iter().all(|record| record.counts.len() == strs.len()), "Every record must have a count for each STR");
+        StrDatabase { strs, records }
+    }
+
+    /// Loads a database from a CSV file whose header is `name` followed by the STRs.
+    ///
+    /// # Arguments
+    /// * `path` - Name of the database file.
+    pub fn load(path: &str) -> Result<Self, DnaError> {
+        Self::from_reader(BufReader::new(File::open(path).map_err(DnaError::IoError)?))
+    }
+
+    /// Reads a database in CSV format whose header is `name` followed by the STRs.
+    ///
+    /// # Arguments
+    /// * `reader` - The database's contents.
+    pub fn from_reader<R: Read>(reader: R) -> Result<Self, DnaError> {
+        let mut csv_reader = ReaderBuilder::new().trim(csv::Trim::All).from_reader(reader);
+        let header = csv_reader.headers().map_err(DnaError::CsvError)?.clone();
+
+        if header.get(0) != Some("name") {
+            return Err(DnaError::MissingNameError);
+        }
+
+        let records = csv_reader
+            .records()
+            .map(|record| {
+                let record = record.map_err(DnaError::CsvError)?;
+                let counts = record.iter()
+                    .skip(1)
+                    .map(|count| count.parse().map_err(|_| DnaError::InvalidCountError(count.to_string())))
+                    .collect::<Result<_, _>>()?;
+
+                Ok(Record {
+                    name: record[0].to_string(),
+                    counts
+                })
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(StrDatabase {
+            strs: header.iter().skip(1).map(String::from).collect(),
+            records
+        })
+    }
+
+    /// The STRs whose runs are counted, in the order of each record's counts.
+    pub fn strs(&self) -> &[Str] {
+        &self.strs
+    }
+
+    /// The people in the database.
+    pub fn records(&self) -> &[Record] {
+        &self.records
+    }
+
+    /// Finds the person whose counts are the same as a profile's for every STR in the database. The records are
+    /// compared in parallel, and the first matching one in the database's order is returned.
+    ///
+    /// # Arguments
+    /// * `profile` - The profile of a DNA sequence, which must have counts for the database's STRs.
+    pub fn match_profile(&self, profile: &DnaProfile) -> Option<&Record> {
+        let counts: Vec<usize> = self.strs.iter().map(|str_sequence| profile.count(str_sequence)).collect::<Option<_>>()?;
+        self.records.par_iter().find_first(|record| record.counts == counts)
+    }
+
+    /// Finds the people whose counts are closest to a profile's, for when no one matches exactly. Returns up to `limit`
+    /// people sorted by their distance to the profile, which is the sum of the absolute differences between their
+    /// counts and the profile's. People at the same distance keep the database's order.
+    ///
+    /// # Arguments
+    /// * `profile` - The profile of a DNA sequence, which must have counts for the database's STRs.
+    /// * `limit` - Maximum number of people returned.
+    pub fn nearest(&self, profile: &DnaProfile, limit: usize) -> Vec<NearMatch<'_>> {
+        let counts: Vec<usize> = match self.strs.iter().map(|str_sequence| profile.count(str_sequence)).collect() {
+            Some(counts) => counts,
+            None => return Vec::new()
+        };
+
+        let mut matches: Vec<NearMatch> = self.records
+            .par_iter()
+            .map(|record| {
+                let mismatches: Vec<StrMismatch> = self.strs.iter()
+                    .zip(record.counts.iter().zip(counts.iter()))
+                    .filter(|(_, (expected, found))| expected != found)
+                    .map(|(str_sequence, (&expected, &found))| StrMismatch { str_sequence, expected, found })
+                    .collect();
+
+                NearMatch {
+                    record,
+                    distance: mismatches.iter().map(|mismatch| mismatch.expected.abs_diff(mismatch.found)).sum(),
+                    mismatches
+                }
+            })
+            .collect();
+
+        matches.sort_by_key(|near_match| near_match.distance);
+        matches.truncate(limit);
+        matches
+    }
+}
+
+/// A person in the database whose counts are close to a profile's.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NearMatch<'a> {
+    /// The person.
+    pub record: &'a Record,
+    /// Sum of the absolute differences between the person's counts and the profile's.
+    pub distance: usize,
+    /// The STRs whose counts differ.
+    pub mismatches: Vec<StrMismatch<'a>>
+}
+
+/// An STR whose count differs between a person and a profile.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StrMismatch<'a> {
+    /// The STR.
+    pub str_sequence: &'a str,
+    /// The person's count.
+    pub expected: usize,
+    /// The profile's count.
+    pub found: usize
+}
+
+/// The longest run of some STRs in a DNA sequence.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DnaProfile {
+    /// Each STR with its longest run.
+    counts: Vec<(Str, usize)>
+}
+
+impl DnaProfile {
+    /// Counts the longest run of each STR in a DNA sequence, searching for every STR in parallel.
+    ///
+    /// # Arguments
+    /// * `sequence` - The DNA sequence.
+    /// * `strs` - The STRs to count.
+    pub fn from_sequence(sequence: &str, strs: &[Str]) -> Self {
+        DnaProfile {
+            counts: strs.par_iter().map(|str_sequence| (str_sequence.clone(), longest_match(str_sequence, sequence))).collect()
+        }
+    }
+
+    /// The longest run of an STR, `None` if the profile does not have the STR.
+    ///
+    /// # Arguments
+    /// * `str_sequence` - The STR.
+    pub fn count(&self, str_sequence: &str) -> Option<usize> {
+        self.counts.iter().find(|(other, _)| other == str_sequence).map(|(_, count)| *count)
+    }
+
+    /// Each STR with its longest run.
+    pub fn counts(&self) -> &[(Str, usize)] {
+        &self.counts
+    }
+}
+
+/// A DNA sequence from a sequence file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sequence {
+    /// The record's header line without the `>`, `None` for plain sequence files.
+    pub header: Option<String>,
+    /// The sequence's bases.
+    pub bases: String
+}
+
+/// Parses the contents of a sequence file. FASTA files, which start with a `>` header line, have a sequence per
+/// header whose lines are joined. Any other file is a single sequence. In both, `;` comment lines are skipped and
+/// lowercase (soft-masked) bases are converted to uppercase.
 ///
 /// # Arguments
-/// * `filename` - Name of the database file.
-fn read_database(filename: &str) -> Vec<DnaRecord> {
-    let reader = BufReader::new(File::open(filename).unwrap());
-    let mut csv_reader = ReaderBuilder::new().from_reader(reader);
+/// * `text` - The file's contents.
+pub fn parse_sequences(text: &str) -> Vec<Sequence> {
+    let lines = text.lines().map(str::trim).filter(|line| !line.is_empty() && !line.starts_with(';'));
 
-    csv_reader.deserialize().collect::<Result<_, _>>().unwrap()
+    if !lines.clone().next().is_some_and(|line| line.starts_with('>')) {
+        return vec![Sequence {
+            header: None,
+            bases: lines.map(str::to_ascii_uppercase).collect()
+        }];
+    }
+
+    let mut sequences: Vec<Sequence> = Vec::new();
+
+    for line in lines {
+        match line.strip_prefix('>') {
+            Some(header) => sequences.push(Sequence {
+                header: Some(header.trim().to_string()),
+                bases: String::new()
+            }),
+            None => sequences.last_mut().unwrap().bases.push_str(&line.to_ascii_uppercase())
+        }
+    }
+
+    sequences
 }
 
-/// Reads the DNA sequence file. Returns the sequence as a string.
+/// Reads a sequence file, either a plain DNA sequence or a FASTA file with one or more records.
 ///
 /// # Arguments
 /// * `filename` - Name of the sequence file.
-fn read_sequence(filename: &str) -> String {
-    let mut reader = BufReader::new(File::open(filename).unwrap());
-    let mut sequence = String::new();
-    reader.read_to_string(&mut sequence).unwrap();
+pub fn read_sequences(filename: &str) -> Result<Vec<Sequence>, DnaError> {
+    let mut reader = BufReader::new(File::open(filename).map_err(DnaError::IoError)?);
+    let mut text = String::new();
+    reader.read_to_string(&mut text).map_err(DnaError::IoError)?;
 
-    sequence
+    Ok(parse_sequences(&text))
 }
 
 /// Finds the longest consecutive sequence of an STR in a DNA sequence. Returns the number of times that the STR is repeated.
@@ -36,18 +275,21 @@ fn read_sequence(filename: &str) -> String {
 /// # Arguments
 /// * `str_sequence` - The STR sequence.
 /// * `dna_sequence` - DNA sequence where the STR will be found.
-fn longest_match(str_sequence: &str, dna_sequence: &str) -> usize {
+pub fn longest_match(str_sequence: &str, dna_sequence: &str) -> usize {
     let str_bytes = str_sequence.as_bytes();
     let dna_bytes = dna_sequence.as_bytes();
     let len = str_bytes.len();
-    let end = dna_bytes.len() - len;
     let mut max_repeats = 0;
     let mut i = 0;
 
-    while i < end {
+    if len == 0 {
+        return 0;
+    }
+
+    while i + len <= dna_bytes.len() {
         let mut repeats = 0;
 
-        while i < end && str_bytes == &dna_bytes[i..i + len] {
+        while i + len <= dna_bytes.len() && str_bytes == &dna_bytes[i..i + len] {
             i += len;
             repeats += 1;
         }
@@ -65,28 +307,163 @@ fn longest_match(str_sequence: &str, dna_sequence: &str) -> usize {
 pub fn main() {
     // Reads from database file and DNA sequence file.
     let (database_file, sequence_file): (String, String) = env::args().skip(1).collect_tuple().unwrap();
-    let database = read_database(&database_file);
-    let sequence = read_sequence(&sequence_file);
-
-    // Finds the longest consecutive sequence of each STR in the DNA sequence.
-    let longest_matches: Vec<_> = database.first()
-        .expect("Empty database.")
-        .keys()
-        .map(|key| key.clone())
-        .filter(|key| key != "name")
-        .map(|str_sequence| {
-            let repeats = longest_match(&str_sequence, &sequence);
-            (str_sequence, repeats)
-        })
-        .collect();
+    let database = StrDatabase::load(&database_file).unwrap();
+    let sequences = read_sequences(&sequence_file).unwrap();
+
+    for sequence in sequences.iter() {
+        // Finds the longest consecutive sequence of each STR in the DNA sequence.
+        let profile = DnaProfile::from_sequence(&sequence.bases, database.strs());
+
+        // Finds the if the DNA sequence belongs to a person in the database, or the closest people if it does not.
+        let result = match database.match_profile(&profile) {
+            Some(record) => record.name.clone(),
+            None => {
+                let nearest = database.nearest(&profile, NEAREST_LIMIT);
+                let mut result = String::from("No match");
+
+                if !nearest.is_empty() {
+                    result.push_str(", closest records:");
+                }
+
+                for near_match in nearest.iter() {
+                    let mismatches = near_match.mismatches
+                        .iter()
+                        .map(|mismatch| format!("{} (record {}, sequence {})", mismatch.str_sequence, mismatch.expected, mismatch.found))
+                        .join(", ");
+
+                    result.push_str(&format!("\n  {}: distance {}, mismatching {}", near_match.record.name, near_match.distance, mismatches));
+                }
 
-    // Finds the if the DNA sequence belongs to a person in the database.
-    for record in database {
-        if longest_matches.iter().all(|(str_seq, repeats)| record[str_seq].parse::<usize>().unwrap() == *repeats) {
-            println!("{}", record["name"]);
-            return
+                result
+            }
+        };
+
+        match &sequence.header {
+            Some(header) => println!("{}: {}", header, result),
+            None => println!("{}", result)
         }
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A small database with the same STRs as CS50's.
+    const DATABASE: &[u8] = b"name,AGATC,AATG,TATC\nAlice,2,8,3\nBob,4,1,5\nCharlie,3,2,5\nBobby,4,1,5\n";
+
+    #[test]
+    fn matches_a_profile() {
+        let database = StrDatabase::from_reader(DATABASE).unwrap();
+        let profile = DnaProfile::from_sequence("AGATCAGATCAGATCAGATCGGAATGTTATCTATCTATCTATCTATC", database.strs());
+
+        assert_eq!(database.strs(), ["AGATC", "AATG", "TATC"]);
+        assert_eq!(database.records().len(), 4);
+        assert_eq!(profile.count("AGATC"), Some(4));
+        assert_eq!(profile.count("GATTACA"), None);
+        assert_eq!(database.match_profile(&profile).map(|record| record.name.as_str()), Some("Bob"));
+    }
 
-    println!("No match")
-}
\ No newline at end of file
+    #[test]
+    fn finds_no_match() {
+        let database = StrDatabase::from_reader(DATABASE).unwrap();
+        let profile = DnaProfile::from_sequence("AGATCAATGTATC", database.strs());
+
+        assert_eq!(database.match_profile(&profile), None);
+
+        // Profiles without every STR of the database never match.
+        let partial = DnaProfile::from_sequence("AGATCAGATC", &[String::from("AGATC")]);
+        assert_eq!(database.match_profile(&partial), None);
+    }
+
+    #[test]
+    fn rejects_invalid_databases() {
+        assert!(matches!(StrDatabase::from_reader(&b"person,AGATC\nAlice,2\n"[..]), Err(DnaError::MissingNameError)));
+        assert!(matches!(StrDatabase::from_reader(&b""[..]), Err(DnaError::MissingNameError)));
+
+        match StrDatabase::from_reader(&b"name,AGATC,AATG\nAlice,2,many\n"[..]) {
+            Err(DnaError::InvalidCountError(count)) => assert_eq!(count, "many"),
+            result => panic!("Expected an invalid count, got {:?}", result)
+        }
+
+        assert!(matches!(StrDatabase::from_reader(&b"name,AGATC\nAlice,-1\n"[..]), Err(DnaError::InvalidCountError(_))));
+        assert!(matches!(StrDatabase::from_reader(&b"name,AGATC\nAlice,1,2\n"[..]), Err(DnaError::CsvError(_))));
+    }
+
+    #[test]
+    fn match_profile_returns_the_first_record_in_database_order() {
+        let records = (0..1000).map(|i| Record { name: format!("Person {}", i), counts: vec![i % 10] }).collect();
+        let database = StrDatabase::new(vec![String::from("AT")], records);
+        let profile = DnaProfile::from_sequence("ATATATATATAT", database.strs());
+
+        assert_eq!(database.match_profile(&profile).unwrap().name, "Person 6");
+
+        let duplicated = StrDatabase::from_reader(DATABASE).unwrap();
+        let profile = DnaProfile::from_sequence("AGATCAGATCAGATCAGATCAATGTATCTATCTATCTATCTATC", duplicated.strs());
+        assert_eq!(duplicated.match_profile(&profile).unwrap().name, "Bob");
+    }
+
+    /// Creates a FASTA record.
+    ///
+    /// # Arguments
+    /// * `header` - The record's header line without the `>`.
+    /// * `bases` - The record's bases.
+    fn record(header: &str, bases: &str) -> Sequence {
+        Sequence { header: Some(header.to_string()), bases: bases.to_string() }
+    }
+
+    #[test]
+    fn parses_plain_sequences() {
+        assert_eq!(parse_sequences("AGATC\nagatc\n\n"), vec![Sequence { header: None, bases: String::from("AGATCAGATC") }]);
+        assert_eq!(parse_sequences(""), vec![Sequence { header: None, bases: String::new() }]);
+    }
+
+    #[test]
+    fn parses_multiple_wrapped_fasta_records() {
+        let sequences = parse_sequences(">r1 first read\nAGAT\nCAGA\n\n>r2\nTTAG\n");
+
+        assert_eq!(sequences, vec![record("r1 first read", "AGATCAGA"), record("r2", "TTAG")]);
+    }
+
+    #[test]
+    fn skips_fasta_comments() {
+        assert_eq!(parse_sequences(";c\n>r1\nAG\nAT\n>r2\nTT\n"), vec![record("r1", "AGAT"), record("r2", "TT")]);
+        assert_eq!(parse_sequences("  ; a comment\n>r1\n;another one\nAG\n"), vec![record("r1", "AG")]);
+    }
+
+    #[test]
+    fn converts_soft_masked_bases_to_uppercase() {
+        let sequences = parse_sequences(">r1\nagatcAGATC\naGaTc\n");
+        let profile = DnaProfile::from_sequence(&sequences[0].bases, &[String::from("AGATC")]);
+
+        assert_eq!(sequences, vec![record("r1", "AGATCAGATCAGATC")]);
+        assert_eq!(profile.count("AGATC"), Some(3));
+    }
+
+    #[test]
+    fn nearest_sorts_by_distance_keeping_database_order() {
+        let database = StrDatabase::from_reader(DATABASE).unwrap();
+        let profile = DnaProfile {
+            counts: vec![(String::from("AGATC"), 4), (String::from("AATG"), 2), (String::from("TATC"), 5)]
+        };
+
+        let nearest = database.nearest(&profile, 10);
+        let distances: Vec<(&str, usize)> = nearest.iter().map(|near| (near.record.name.as_str(), near.distance)).collect();
+
+        assert_eq!(distances, vec![("Bob", 1), ("Charlie", 1), ("Bobby", 1), ("Alice", 10)]);
+        assert_eq!(nearest[0].mismatches, vec![StrMismatch { str_sequence: "AATG", expected: 1, found: 2 }]);
+        assert_eq!(nearest[1].mismatches, vec![StrMismatch { str_sequence: "AGATC", expected: 3, found: 4 }]);
+        assert_eq!(nearest[3].mismatches, vec![
+            StrMismatch { str_sequence: "AGATC", expected: 2, found: 4 },
+            StrMismatch { str_sequence: "AATG", expected: 8, found: 2 },
+            StrMismatch { str_sequence: "TATC", expected: 3, found: 5 }
+        ]);
+
+        let limited = database.nearest(&profile, 2);
+        assert_eq!(limited, nearest[..2]);
+        assert!(database.nearest(&profile, 0).is_empty());
+
+        let partial = DnaProfile { counts: vec![(String::from("AGATC"), 4)] };
+        assert!(database.nearest(&partial, 3).is_empty());
+    }
+}
@@ -0,0 +1,129 @@
+use std::env;
+use std::time::Instant;
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+use super::dna::{self, DnaProfile, Record, Str, StrDatabase};
+
+/// The DNA bases.
+const BASES: [u8; 4] = *b"ACGT";
+
+/// Generates a random DNA sequence with runs of the STRs planted in it.
+///
+/// # Arguments
+/// * `length` - Number of bases.
+/// * `strs` - The STRs to plant.
+/// * `rng` - Random number generator.
+fn generate_sequence(length: usize, strs: &[Str], rng: &mut StdRng) -> String {
+    let mut sequence: Vec<u8> = (0..length).map(|_| BASES[rng.gen_range(0..BASES.len())]).collect();
+
+    // Every STR gets a few runs of up to 50 repeats, overwriting the random bases.
+    for str_sequence in strs.iter() {
+        for _ in 0..4 {
+            let run = str_sequence.repeat(rng.gen_range(1..=50));
+
+            if run.len() < length {
+                let start = rng.gen_range(0..length - run.len());
+                sequence[start..start + run.len()].copy_from_slice(run.as_bytes());
+            }
+        }
+    }
+
+    String::from_utf8(sequence).unwrap()
+}
+
+/// Generates a database of people with random counts, whose last record matches a profile.
+///
+/// # Arguments
+/// * `size` - Number of records.
+/// * `strs` - The STRs whose runs are counted.
+/// * `profile` - The profile which the last record matches.
+/// * `rng` - Random number generator.
+fn generate_database(size: usize, strs: Vec<Str>, profile: &DnaProfile, rng: &mut StdRng) -> StrDatabase {
+    let counts: Vec<usize> = profile.counts().iter().map(|(_, count)| *count).collect();
+
+    let mut records: Vec<Record> = (1..size)
+        .map(|i| Record {
+            name: format!("person{i}"),
+            // Counts above 50 never match the profile.
+            counts: counts.iter().map(|_| rng.gen_range(51..100)).collect()
+        })
+        .collect();
+
+    records.push(Record { name: format!("person{size}"), counts });
+    StrDatabase::new(strs, records)
+}
+
+/// Times a function. Returns its result and the elapsed seconds.
+///
+/// # Arguments
+/// * `f` - The function.
+fn timed<T, F: FnOnce() -> T>(f: F) -> (T, f64) {
+    let start = Instant::now();
+    let result = f();
+
+    (result, start.elapsed().as_secs_f64())
+}
+
+/// Compares sequential and parallel STR matching on a synthetic sequence and database.
+///
+/// Usage: `dnabench [--megabases 4] [--strs 32] [--records 100000] [--runs 1] [--seed 42]`
+///
+/// Counts the longest run of each STR in a random sequence with runs of the STRs planted in it, and finds the
+/// sequence's owner in a random database where only the last person matches. Prints one CSV row per step, mode and
+/// run with the elapsed time.
+pub fn main() {
+    let mut megabases = 4.0;
+    let mut str_count = 32;
+    let mut record_count = 100000;
+    let mut runs = 1;
+    let mut seed: u64 = rand::thread_rng().gen();
+    let mut args = env::args().skip(1);
+
+    // Parses the command line flags.
+    while let Some(flag) = args.next() {
+        let value = args.next().unwrap_or_else(|| panic!("Missing value for {flag}."));
+
+        match flag.as_str() {
+            "--megabases" => megabases = value.parse().expect("Megabases must be a non-negative number."),
+            "--strs" => str_count = value.parse().expect("STRs must be a positive integer."),
+            "--records" => record_count = value.parse().expect("Records must be a positive integer."),
+            "--runs" => runs = value.parse().expect("Runs must be a non-negative integer."),
+            "--seed" => seed = value.parse().expect("Seed must be a non-negative integer."),
+            _ => panic!("Unknown flag: {flag}.")
+        }
+    }
+
+    assert!(str_count > 0, "STRs must be a positive integer.");
+    assert!(record_count > 0, "Records must be a positive integer.");
+    let mut rng = StdRng::seed_from_u64(seed);
+    let length = (megabases * 1_000_000.0) as usize;
+
+    // STRs of 4 to 8 bases.
+    let strs: Vec<Str> = (0..str_count)
+        .map(|_| (0..rng.gen_range(4..=8)).map(|_| BASES[rng.gen_range(0..BASES.len())] as char).collect())
+        .collect();
+
+    let sequence = generate_sequence(length, &strs, &mut rng);
+    let profile = DnaProfile::from_sequence(&sequence, &strs);
+    let database = generate_database(record_count, strs, &profile, &mut rng);
+    let expected = database.records().last().map(|record| record.name.as_str());
+
+    println!("step,mode,run,bases,strs,records,seconds");
+
+    for run in 1..=runs {
+        let (sequential, seconds) = timed(|| database.strs().iter().map(|str_sequence| dna::longest_match(str_sequence, &sequence)).collect::<Vec<_>>());
+        println!("profile,sequential,{run},{length},{str_count},{record_count},{seconds}");
+
+        let (parallel, seconds) = timed(|| DnaProfile::from_sequence(&sequence, database.strs()));
+        println!("profile,parallel,{run},{length},{str_count},{record_count},{seconds}");
+        assert!(parallel.counts().iter().map(|(_, count)| *count).eq(sequential.iter().copied()), "The parallel profile differs from the sequential one.");
+
+        let (found, seconds) = timed(|| database.records().iter().find(|record| record.counts == sequential));
+        println!("match,sequential,{run},{length},{str_count},{record_count},{seconds}");
+        assert_eq!(found.map(|record| record.name.as_str()), expected);
+
+        let (found, seconds) = timed(|| database.match_profile(&parallel));
+        println!("match,parallel,{run},{length},{str_count},{record_count},{seconds}");
+        assert_eq!(found.map(|record| record.name.as_str()), expected);
+    }
+}
@@ -1,16 +1,23 @@
-use std::cmp::Reverse;
+use std::cmp::{Ordering, Reverse};
 use std::env;
+use std::fmt::{self, Display, Formatter};
+use std::thread;
 use std::fs::File;
 use csv::ReaderBuilder;
 use itertools::Itertools;
 use num_traits::Pow;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use serde;
-use rand;
-use rand::Rng;
+use rand::{self, Rng, RngCore, SeedableRng};
+use rand::rngs::StdRng;
 
-/// Number of tournament simulations to do.
+/// Default number of tournament simulations to do.
 const SIMULATIONS: u32 = 100000;
+/// Probability of a draw in a group stage match between teams with the same rating. Matches between teams with
+/// different ratings are less likely to be drawn.
+const MAX_DRAW_PROBABILITY: f64 = 0.3;
+/// Number of teams of each group which pass to the knockout stage, the winner and the runner-up.
+const GROUP_ADVANCING: usize = 2;
 
 /// A team playing in the World Cup.
 #[derive(Deserialize, Debug)]
@@ -19,81 +26,537 @@ struct Team {
     #[serde(rename = "team")]
     name: String,
     /// The team's skill rating.
-    rating: u32
+    rating: u32,
+    /// The team's group in the group stage, `None` if the tournament has no group stage.
+    #[serde(default)]
+    group: Option<String>,
+    /// The team's seed in the knockout bracket, 1 for the best team. `None` to seed the teams by rating.
+    #[serde(default)]
+    seed: Option<u32>
 }
 
-impl Team {
-    /// Simulates a match with another Team. Returns true if this team is the winner.
+/// Probability of a team winning a knockout match with another team, given by the difference between their ratings.
+///
+/// # Arguments
+/// * `rating` - The team's rating.
+/// * `other` - The other team's rating.
+fn win_probability(rating: f64, other: f64) -> f64 {
+    1.0 / (1.0 + 10_f64.pow((other - rating) / 600.0))
+}
+
+/// A way of simulating matches between teams. The teams' ratings start at the ones in the CSV file in every simulated
+/// tournament, and a model may change them after each match.
+pub trait MatchModel: Sync {
+    /// Simulates a knockout match, which cannot end in a draw. Returns true if the first team is the winner.
+    ///
+    /// # Arguments
+    /// * `ratings` - The current rating of each team.
+    /// * `first` - Index of the first team.
+    /// * `second` - Index of the second team.
+    /// * `rng` - Random number generator used to choose the result.
+    fn knockout(&self, ratings: &mut [f64], first: usize, second: usize, rng: &mut dyn RngCore) -> bool;
+
+    /// Simulates a group stage match, which may end in a draw. Returns `Ordering::Greater` if the first team is the
+    /// winner, `Ordering::Less` if the second team is the winner and `Ordering::Equal` for a draw.
     ///
     /// # Arguments
-    /// * `other` - The other team being faced in the match.
-    pub fn game<'a>(&'a self, other: &'a Self) -> bool {
-        let probability = 1.0 / (1.0 + 10_f64.pow((other.rating as f64 - self.rating as f64) / 600.0));
-        let random = rand::thread_rng().gen::<f64>();
+    /// * `ratings` - The current rating of each team.
+    /// * `first` - Index of the first team.
+    /// * `second` - Index of the second team.
+    /// * `rng` - Random number generator used to choose the result.
+    fn group(&self, ratings: &mut [f64], first: usize, second: usize, rng: &mut dyn RngCore) -> Ordering;
+}
+
+/// Elo-style logistic model. The probability of winning grows with the difference between the teams' ratings, and
+/// group matches between teams with similar ratings are more likely to be drawn.
+pub struct EloModel;
+
+impl MatchModel for EloModel {
+    fn knockout(&self, ratings: &mut [f64], first: usize, second: usize, rng: &mut dyn RngCore) -> bool {
+        let probability = win_probability(ratings[first], ratings[second]);
+        let random = rng.gen::<f64>();
 
         random < probability
     }
+
+    fn group(&self, ratings: &mut [f64], first: usize, second: usize, rng: &mut dyn RngCore) -> Ordering {
+        let probability = win_probability(ratings[first], ratings[second]);
+        let draw = MAX_DRAW_PROBABILITY * 4.0 * probability * (1.0 - probability);
+
+        if rng.gen::<f64>() < draw {
+            Ordering::Equal
+        } else if self.knockout(ratings, first, second, rng) {
+            Ordering::Greater
+        } else {
+            Ordering::Less
+        }
+    }
+}
+
+/// Goals model. Each team scores a Poisson distributed number of goals, whose mean is the average goals per team
+/// multiplied by ten to the power of the rating difference over 1200. Drawn knockout matches go to a penalty
+/// shootout, which either team wins with the same probability.
+pub struct PoissonModel;
+
+impl PoissonModel {
+    /// Average number of goals scored by each of two teams with the same rating.
+    const AVERAGE_GOALS: f64 = 1.35;
+
+    /// Simulates the goals scored by both teams. Returns the first team's goals and the second team's goals.
+    ///
+    /// # Arguments
+    /// * `ratings` - The current rating of each team.
+    /// * `first` - Index of the first team.
+    /// * `second` - Index of the second team.
+    /// * `rng` - Random number generator used to choose the goals.
+    fn score(ratings: &[f64], first: usize, second: usize, rng: &mut dyn RngCore) -> (u32, u32) {
+        let difference = (ratings[first] - ratings[second]) / 1200.0;
+        let first_goals = Self::poisson(Self::AVERAGE_GOALS * 10_f64.pow(difference), rng);
+        let second_goals = Self::poisson(Self::AVERAGE_GOALS * 10_f64.pow(-difference), rng);
+
+        (first_goals, second_goals)
+    }
+
+    /// Draws a number from a Poisson distribution by multiplying uniform random numbers until their product falls
+    /// below e to the power of minus the mean. Fast for the small means of football scores.
+    ///
+    /// # Arguments
+    /// * `mean` - The distribution's mean.
+    /// * `rng` - Random number generator.
+    fn poisson(mean: f64, rng: &mut dyn RngCore) -> u32 {
+        let limit = (-mean).exp();
+        let mut product = rng.gen::<f64>();
+        let mut count = 0;
+
+        while product > limit {
+            product *= rng.gen::<f64>();
+            count += 1;
+        }
+
+        count
+    }
+}
+
+impl MatchModel for PoissonModel {
+    fn knockout(&self, ratings: &mut [f64], first: usize, second: usize, rng: &mut dyn RngCore) -> bool {
+        match self.group(ratings, first, second, rng) {
+            Ordering::Equal => rng.gen::<bool>(),
+            result => result == Ordering::Greater
+        }
+    }
+
+    fn group(&self, ratings: &mut [f64], first: usize, second: usize, rng: &mut dyn RngCore) -> Ordering {
+        let (first_goals, second_goals) = Self::score(ratings, first, second, rng);
+        first_goals.cmp(&second_goals)
+    }
+}
+
+/// Elo model whose ratings are updated after every match, so teams which win get stronger for the next rounds.
+/// Each team gains `K_FACTOR` times the difference between its result, 1 for a win, 0.5 for a draw and 0 for a loss,
+/// and its probability of winning.
+pub struct EloUpdatesModel;
+
+impl EloUpdatesModel {
+    /// Maximum rating change after a match.
+    const K_FACTOR: f64 = 60.0;
+
+    /// Updates the ratings of two teams after a match.
+    ///
+    /// # Arguments
+    /// * `ratings` - The current rating of each team.
+    /// * `first` - Index of the first team.
+    /// * `second` - Index of the second team.
+    /// * `result` - The first team's result.
+    fn update(ratings: &mut [f64], first: usize, second: usize, result: Ordering) {
+        let expected = win_probability(ratings[first], ratings[second]);
+
+        let score = match result {
+            Ordering::Greater => 1.0,
+            Ordering::Equal => 0.5,
+            Ordering::Less => 0.0
+        };
+
+        let change = Self::K_FACTOR * (score - expected);
+        ratings[first] += change;
+        ratings[second] -= change;
+    }
+}
+
+impl MatchModel for EloUpdatesModel {
+    fn knockout(&self, ratings: &mut [f64], first: usize, second: usize, rng: &mut dyn RngCore) -> bool {
+        let won = EloModel.knockout(ratings, first, second, rng);
+        Self::update(ratings, first, second, if won { Ordering::Greater } else { Ordering::Less });
+
+        won
+    }
+
+    fn group(&self, ratings: &mut [f64], first: usize, second: usize, rng: &mut dyn RngCore) -> Ordering {
+        let result = EloModel.group(ratings, first, second, rng);
+        Self::update(ratings, first, second, result);
+
+        result
+    }
+}
+
+/// A team's row in the points table of a group.
+#[derive(Debug, Clone, Copy, Default)]
+struct GroupStanding {
+    /// Index of the team.
+    team: usize,
+    /// Points, 3 for each win and 1 for each draw.
+    points: u32,
+    /// Points earned in the matches against the teams with the same number of points.
+    head_to_head: u32,
+    /// Number of matches won.
+    wins: u32,
+    /// Random number which breaks the remaining ties, like drawing lots.
+    lots: u32
+}
+
+/// How often a team reached each stage of the knockout bracket over many simulated tournaments.
+#[derive(Serialize, Debug)]
+struct TeamResults<'a> {
+    /// Name of the team.
+    #[serde(rename = "team")]
+    name: &'a str,
+    /// Number of simulations where the team reached each stage.
+    reached: Vec<u32>,
+    /// Probability of the team reaching each stage.
+    probabilities: Vec<f64>
+}
+
+/// The results of many simulated tournaments.
+#[derive(Serialize, Debug)]
+struct SimulationResults<'a> {
+    /// Number of simulated tournaments.
+    simulations: u32,
+    /// Names of the stages of the knockout bracket, from the first round to the tournament's winner.
+    stages: Vec<String>,
+    /// Each team's results, sorted by number of wins.
+    teams: Vec<TeamResults<'a>>
+}
+
+impl Display for SimulationResults<'_> {
+    /// Formats the results as a table with each team's probability of reaching each stage, in percent.
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let width = self.teams.iter().map(|team| team.name.len()).chain([4]).max().unwrap_or(0);
+        write!(f, "{:width$}", "team")?;
+
+        for stage in self.stages.iter() {
+            write!(f, "  {:>12}", stage)?;
+        }
+
+        for team in self.teams.iter() {
+            write!(f, "\n{:width$}", team.name)?;
+
+            for probability in team.probabilities.iter() {
+                write!(f, "  {:>11.1}%", probability * 100.0)?;
+            }
+        }
+
+        Ok(())
+    }
 }
 
-/// A world cup tournament.
+/// Places seeded teams in a knockout bracket, so that the best seeds meet as late as possible. The bracket's size is
+/// the next power of two and the best seeds get a bye, an empty slot which lets them pass the first round without
+/// playing.
+///
+/// # Arguments
+/// * `seeded` - The teams, from the best seed to the worst.
+fn seeded_bracket<T: Copy>(seeded: &[T]) -> Vec<Option<T>> {
+    let size = seeded.len().next_power_of_two();
+    let mut positions = vec![0];
+
+    // Every seed faces the seed which adds up to one less than the size of the round.
+    while positions.len() < size {
+        let round = positions.len() * 2;
+        positions = positions.into_iter().flat_map(|seed| [seed, round - 1 - seed]).collect();
+    }
+
+    positions.into_iter().map(|seed| seeded.get(seed).copied()).collect()
+}
+
+/// A world cup tournament. If the teams have groups, the tournament starts with a group stage where the teams of
+/// each group play each other once, and the best teams of each group pass to a knockout bracket.
+/// Otherwise the tournament is a single knockout bracket.
 struct Tournament {
     /// The list of teams participating in the tournament.
-    teams: Vec<Team>
+    teams: Vec<Team>,
+    /// Indices of the teams in each slot of the knockout bracket, `None` for byes. Empty if there is a group stage.
+    bracket: Vec<Option<usize>>,
+    /// Indices of the teams in each group, in the order of the groups' first appearance. Empty if there is no group stage.
+    groups: Vec<Vec<usize>>
 }
 
 impl Tournament {
-    /// Simulates a single tournament. Returns the index of the winner.
-    fn simulate_one(&self) -> usize {
-        let mut teams: Vec<_> = self.teams.iter()
-            .enumerate()
-            .collect();
+    /// Number of slots in the knockout bracket, including byes.
+    fn bracket_size(&self) -> usize {
+        if self.groups.is_empty() {
+            self.bracket.len()
+        } else {
+            (self.groups.len() * GROUP_ADVANCING).next_power_of_two()
+        }
+    }
+
+    /// Names of the stages of the knockout bracket, from the first round to the tournament's winner.
+    pub fn stages(&self) -> Vec<String> {
+        let mut stages = Vec::new();
+        let mut teams = self.bracket_size();
 
-        while teams.len() > 1 {
-            teams = Self::simulate_round(teams);
+        while teams >= 1 {
+            stages.push(match teams {
+                1 => String::from("winner"),
+                2 => String::from("final"),
+                4 => String::from("semifinal"),
+                8 => String::from("quarterfinal"),
+                _ => format!("round of {teams}")
+            });
+
+            teams /= 2;
         }
 
-        teams[0].0
+        stages
+    }
+
+    /// Simulates a single tournament. Adds one to the count of each stage of the knockout bracket reached by each team.
+    ///
+    /// # Arguments
+    /// * `model` - Model which simulates each match.
+    /// * `rng` - Random number generator used to choose the winner of each match.
+    /// * `reached` - Number of times each team reached each stage, by team and stage.
+    fn simulate_one<R: Rng>(&self, model: &dyn MatchModel, rng: &mut R, reached: &mut [Vec<u32>]) {
+        let mut ratings: Vec<f64> = self.teams.iter().map(|team| team.rating as f64).collect();
+
+        let mut teams = if self.groups.is_empty() {
+            self.bracket.clone()
+        } else {
+            self.simulate_groups(model, &mut ratings, rng)
+        };
+
+        let mut stage = 0;
+
+        loop {
+            for &team in teams.iter().flatten() {
+                reached[team][stage] += 1;
+            }
+
+            if teams.len() == 1 {
+                break;
+            }
+
+            teams = Self::simulate_round(teams, model, &mut ratings, rng);
+            stage += 1;
+        }
     }
 
     /// Simulates the current tournament a specific number of times.
-    /// Returns a Vec containing each team and the number of simulations where that team won.
+    /// The same random number generator state always gives the same results.
+    ///
+    /// # Arguments
+    /// * `times` - Number of times to simulate the tournament.
+    /// * `model` - Model which simulates each match.
+    /// * `rng` - Random number generator used to choose the winner of each match.
+    pub fn simulate<R: Rng>(&self, times: u32, model: &dyn MatchModel, rng: &mut R) -> SimulationResults<'_> {
+        let reached = self.count_stages(times, model, rng);
+        self.results(times, reached)
+    }
+
+    /// Simulates the current tournament a specific number of times, splitting the simulations between threads.
+    /// Each thread has its own random number generator, seeded with the seed plus the thread's index, so the same seed
+    /// and number of threads always give the same results. With a single thread the results are the same as `simulate`'s
+    /// with a generator seeded with the seed.
     ///
     /// # Arguments
     /// * `times` - Number of times to simulate the tournament.
-    pub fn simulate(&self, times: u32) -> Vec<(&Team, u32)> {
-        let mut wins = vec![0_u32; self.teams.len()];
+    /// * `threads` - Number of threads.
+    /// * `seed` - Seed of the random number generators.
+    /// * `model` - Model which simulates each match.
+    pub fn simulate_parallel(&self, times: u32, threads: u32, seed: u64, model: &dyn MatchModel) -> SimulationResults<'_> {
+        assert!(threads > 0, "There must be at least one thread.");
+
+        let thread_reached: Vec<Vec<Vec<u32>>> = thread::scope(|scope| {
+            let handles: Vec<_> = (0..threads)
+                .map(|thread| {
+                    // The first threads do one more simulation when they cannot be split evenly.
+                    let thread_times = times / threads + u32::from(thread < times % threads);
+                    let mut rng = StdRng::seed_from_u64(seed.wrapping_add(thread as u64));
+
+                    scope.spawn(move || self.count_stages(thread_times, model, &mut rng))
+                })
+                .collect();
+
+            handles.into_iter().map(|handle| handle.join().unwrap()).collect()
+        });
+
+        let mut reached = vec![vec![0_u32; self.stages().len()]; self.teams.len()];
 
-        for i in 0..times {
-            let winner = self.simulate_one();
-            wins[winner] += 1;
+        for thread_reached in thread_reached.iter() {
+            for (total, counts) in reached.iter_mut().flatten().zip(thread_reached.iter().flatten()) {
+                *total += counts;
+            }
         }
 
-        let mut teams: Vec<_> = self.teams.iter()
-            .zip(wins.into_iter())
+        self.results(times, reached)
+    }
+
+    /// Simulates the current tournament a specific number of times. Returns the number of times each team reached
+    /// each stage of the knockout bracket, by team and stage.
+    ///
+    /// # Arguments
+    /// * `times` - Number of times to simulate the tournament.
+    /// * `model` - Model which simulates each match.
+    /// * `rng` - Random number generator used to choose the winner of each match.
+    fn count_stages<R: Rng>(&self, times: u32, model: &dyn MatchModel, rng: &mut R) -> Vec<Vec<u32>> {
+        let mut reached = vec![vec![0_u32; self.stages().len()]; self.teams.len()];
+
+        for _ in 0..times {
+            self.simulate_one(model, rng, &mut reached);
+        }
+
+        reached
+    }
+
+    /// Pairs each team with the number of times it reached each stage. The teams are sorted by wins, from the most to
+    /// the fewest, and then by how often they reached the previous stages.
+    ///
+    /// # Arguments
+    /// * `simulations` - Number of simulations.
+    /// * `reached` - Number of times each team reached each stage, by team and stage.
+    fn results(&self, simulations: u32, reached: Vec<Vec<u32>>) -> SimulationResults<'_> {
+        let mut teams: Vec<TeamResults> = self.teams.iter()
+            .zip(reached)
+            .map(|(team, reached)| TeamResults {
+                name: &team.name,
+                probabilities: reached.iter().map(|&count| count as f64 / simulations as f64).collect(),
+                reached
+            })
             .collect();
 
-        teams.sort_unstable_by_key(|(_, wins)| Reverse(*wins));
+        teams.sort_by(|first, second| second.reached.iter().rev().cmp(first.reached.iter().rev()));
 
-        teams
+        SimulationResults {
+            simulations,
+            stages: self.stages(),
+            teams
+        }
+    }
+
+    /// Simulates the group stage. Returns the knockout bracket with the teams which pass to the knockout stage.
+    /// When the number of groups is an even power of two, the winner of each group faces the runner-up of the group it
+    /// is paired with, and the two winners of a pair of groups are in different halves of the bracket. Otherwise the
+    /// group winners are seeded before the runners-up, in the order of their groups, and the best get byes.
+    ///
+    /// # Arguments
+    /// * `model` - Model which simulates each match.
+    /// * `ratings` - The current rating of each team.
+    /// * `rng` - Random number generator used to choose the result of each match.
+    fn simulate_groups<R: Rng>(&self, model: &dyn MatchModel, ratings: &mut [f64], rng: &mut R) -> Vec<Option<usize>> {
+        let tables: Vec<Vec<GroupStanding>> = self.groups.iter()
+            .map(|group| self.simulate_group(group, model, ratings, rng))
+            .collect();
+
+        if !tables.len().is_multiple_of(2) || !tables.len().is_power_of_two() {
+            let seeded: Vec<usize> = (0..GROUP_ADVANCING)
+                .flat_map(|place| tables.iter().map(move |table| table[place].team))
+                .collect();
+
+            return seeded_bracket(&seeded);
+        }
+
+        let mut top = Vec::new();
+        let mut bottom = Vec::new();
+
+        for pair in tables.chunks(2) {
+            let (first, second) = (&pair[0], &pair[1]);
+
+            top.extend([first[0].team, second[1].team]);
+            bottom.extend([second[0].team, first[1].team]);
+        }
+
+        top.into_iter().chain(bottom).map(Some).collect()
+    }
+
+    /// Simulates the matches of a group, where every team plays every other team once.
+    /// Returns the group's points table, sorted by points, then head-to-head points, then wins, then drawing lots.
+    ///
+    /// # Arguments
+    /// * `group` - Indices of the group's teams.
+    /// * `model` - Model which simulates each match.
+    /// * `ratings` - The current rating of each team.
+    /// * `rng` - Random number generator used to choose the result of each match.
+    fn simulate_group<R: Rng>(&self, group: &[usize], model: &dyn MatchModel, ratings: &mut [f64], rng: &mut R) -> Vec<GroupStanding> {
+        let mut table: Vec<GroupStanding> = group.iter()
+            .map(|&team| GroupStanding { team, lots: rng.gen(), ..Default::default() })
+            .collect();
+
+        // Points earned by each team against each other team.
+        let mut results = vec![vec![0_u32; group.len()]; group.len()];
+
+        for (i, j) in (0..group.len()).tuple_combinations() {
+            let (points, other_points, winner) = match model.group(ratings, group[i], group[j], rng) {
+                Ordering::Greater => (3, 0, Some(i)),
+                Ordering::Equal => (1, 1, None),
+                Ordering::Less => (0, 3, Some(j))
+            };
+
+            results[i][j] = points;
+            results[j][i] = other_points;
+            table[i].points += points;
+            table[j].points += other_points;
+
+            if let Some(winner) = winner {
+                table[winner].wins += 1;
+            }
+        }
+
+        for i in 0..group.len() {
+            table[i].head_to_head = (0..group.len())
+                .filter(|&j| j != i && table[j].points == table[i].points)
+                .map(|j| results[i][j])
+                .sum();
+        }
+
+        table.sort_unstable_by_key(|standing| Reverse((standing.points, standing.head_to_head, standing.wins, standing.lots)));
+        table
+    }
+
+    /// Creates the knockout bracket of a tournament without a group stage. If the number of teams is a power of two
+    /// and the teams have no seeds, the bracket follows the order of the teams. Otherwise the teams are seeded by their
+    /// `seed`, or by rating if they have none, and placed in a seeded bracket with byes.
+    ///
+    /// # Arguments
+    /// * `teams` - The teams.
+    fn knockout_bracket(teams: &[Team]) -> Vec<Option<usize>> {
+        let mut seeded: Vec<usize> = (0..teams.len()).collect();
+
+        if teams.iter().all(|team| team.seed.is_none()) {
+            if teams.len().is_power_of_two() {
+                return seeded.into_iter().map(Some).collect();
+            }
+
+            seeded.sort_by_key(|&team| Reverse(teams[team].rating));
+        } else {
+            seeded.sort_by_key(|&team| teams[team].seed.expect("Every team must have a seed if any team has one."));
+        }
+
+        seeded_bracket(&seeded)
     }
 
     /// Simulates a single round of a tournament.
-    /// Returns a Vec containing the teams that pass to the next round.
+    /// Returns a Vec containing the teams that pass to the next round. A team facing a bye passes without playing.
     ///
     /// # Arguments
-    /// * `teams` - A vector containing each team in the current round and the team's index or ID.
-    fn simulate_round(teams: Vec<(usize, &Team)>) -> Vec<(usize, &Team)> {
+    /// * `teams` - A vector containing the index of each team in the current round, `None` for byes.
+    /// * `model` - Model which simulates each match.
+    /// * `ratings` - The current rating of each team.
+    /// * `rng` - Random number generator used to choose the winner of each match.
+    fn simulate_round<R: Rng>(teams: Vec<Option<usize>>, model: &dyn MatchModel, ratings: &mut [f64], rng: &mut R) -> Vec<Option<usize>> {
         teams.into_iter()
-            .chunks(2)
-            .into_iter()
-            .map(|chunk| {
-                let teams: Vec<_> = chunk.collect();
-
-                if teams.len() == 1 || teams[0].1.game(teams[1].1) {
-                    teams[0]
-                } else {
-                    teams[1]
-                }
+            .tuples()
+            .map(|match_teams| match match_teams {
+                (Some(first), Some(second)) => if model.knockout(ratings, first, second, rng) { Some(first) } else { Some(second) },
+                (team, None) | (None, team) => team
             })
             .collect()
     }
@@ -103,31 +566,315 @@ impl FromIterator<Team> for Tournament {
     fn from_iter<T: IntoIterator<Item=Team>>(iter: T) -> Self {
         let teams: Vec<_> = iter.into_iter().collect();
 
-        match teams.len() {
-            0 => panic!("Empty tournament."),
-            len if len % 2 == 0 => Self { teams },
-            _ => panic!("Tournament must have an even number of teams."),
+        if teams.is_empty() {
+            panic!("Empty tournament.");
+        }
+
+        if teams.iter().all(|team| team.group.is_none()) {
+            let bracket = Self::knockout_bracket(&teams);
+            return Self { teams, bracket, groups: Vec::new() };
+        }
+
+        let names: Vec<&String> = teams.iter()
+            .map(|team| team.group.as_ref().expect("Every team must have a group if any team has one."))
+            .unique()
+            .collect();
+
+        let groups: Vec<Vec<usize>> = names.iter()
+            .map(|&name| (0..teams.len()).filter(|&team| teams[team].group.as_ref() == Some(name)).collect())
+            .collect();
+
+        if groups.iter().any(|group| group.len() < GROUP_ADVANCING) {
+            panic!("Every group must have at least {GROUP_ADVANCING} teams.");
         }
+
+        Self { teams, bracket: Vec::new(), groups }
     }
 }
 
+/// Usage: `world_cup <teams.csv> [--seed 42] [--simulations 100000] [--threads 4] [--format <text|json>] [--model <elo|poisson|elo-updates>]`
+///
+/// The CSV file has `team` and `rating` columns, an optional `group` column which adds a group stage and an optional
+/// `seed` column which places the teams in the knockout bracket. Any number of teams is allowed, the best seeds get
+/// byes when the bracket is not full.
+/// The simulations run on every available core unless `--threads` is given. Running with the same seed and number of
+/// threads gives the same results. Prints each team's probability to reach each stage of the knockout bracket.
+/// Matches are simulated with the Elo-style model unless `--model` chooses the Poisson goals model or the Elo model
+/// with rating updates after each match.
 pub fn main() {
+    let mut csv_filename = None;
+    let mut seed: u64 = rand::thread_rng().gen();
+    let mut simulations = SIMULATIONS;
+    let mut threads = thread::available_parallelism().map_or(1, |threads| threads.get() as u32);
+    let mut format = String::from("text");
+    let mut model_name = String::from("elo");
+    let mut args = env::args().skip(1);
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--format" => format = args.next().expect("Missing value for --format."),
+            "--model" => model_name = args.next().expect("Missing value for --model."),
+            "--seed" => seed = args.next().and_then(|seed| seed.parse().ok()).expect("Seed must be a non-negative integer."),
+            "--simulations" => simulations = args.next().and_then(|simulations| simulations.parse().ok()).filter(|&simulations| simulations > 0).expect("Simulations must be a positive integer."),
+            "--threads" => threads = args.next().and_then(|threads| threads.parse().ok()).filter(|&threads| threads > 0).expect("Threads must be a positive integer."),
+            _ => csv_filename = Some(arg)
+        }
+    }
+
     // Opens and reads CSV file.
-    let csv_filename = env::args().nth(1).expect("Missing CSV file parameter.");
+    let csv_filename = csv_filename.expect("Missing CSV file parameter.");
     let csv_file = File::open(csv_filename).expect("Could not open CSV file.");
     let mut reader = ReaderBuilder::new().from_reader(csv_file);
 
-    // Deserializes the csv into a tournament and simulates 1000 tournaments.
+    // Deserializes the csv into a tournament and simulates the tournaments.
     let teams = reader.deserialize().collect::<Result<Tournament, _>>().expect("Malformed CSV.");
-    let team_wins = teams.simulate(SIMULATIONS);
+    let model: Box<dyn MatchModel> = match model_name.as_str() {
+        "elo" => Box::new(EloModel),
+        "poisson" => Box::new(PoissonModel),
+        "elo-updates" => Box::new(EloUpdatesModel),
+        _ => panic!("The model must be elo, poisson or elo-updates.")
+    };
 
-    let total_matches: u32 = team_wins.iter()
-        .map(|(_, wins)| *wins)
-        .sum();
+    let results = teams.simulate_parallel(simulations, threads, seed, model.as_ref());
 
-    // Prints each team's probability to win a tournament in percent.
-    for (team, wins) in team_wins {
-        let percent = wins as f64 * 100.0 / total_matches as f64;
-        println!("{}: {:.1}% chance of winning", team.name, percent);
+    // Prints each team's probability to reach each stage.
+    match format.as_str() {
+        "text" => println!("{results}"),
+        "json" => println!("{}", serde_json::to_string_pretty(&results).unwrap()),
+        _ => panic!("The format must be text or json.")
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Creates a team without a group or seed.
+    ///
+    /// # Arguments
+    /// * `name` - Name of the team.
+    /// * `rating` - The team's skill rating.
+    fn team(name: &str, rating: u32) -> Team {
+        Team { name: name.to_string(), rating, group: None, seed: None }
+    }
+
+    /// Creates a knockout tournament with 4 teams.
+    fn knockout() -> Tournament {
+        [team("Norway", 1600), team("Brazil", 2000), team("Chile", 1800), team("Japan", 1700)].into_iter().collect()
+    }
+
+    /// The number of times each team reached each stage, by team name.
+    ///
+    /// # Arguments
+    /// * `results` - Results of the simulations.
+    fn reached<'a>(results: &SimulationResults<'a>) -> Vec<(&'a str, Vec<u32>)> {
+        results.teams.iter().map(|team| (team.name, team.reached.clone())).collect()
+    }
+
+    /// Creates a team in a group.
+    ///
+    /// # Arguments
+    /// * `name` - Name of the team.
+    /// * `rating` - The team's skill rating.
+    /// * `group` - The team's group.
+    fn grouped(name: &str, rating: u32, group: &str) -> Team {
+        Team { group: Some(group.to_string()), ..team(name, rating) }
+    }
+
+    /// Model with fixed results. Group matches in the table have the given result, and otherwise the team with the
+    /// best rating wins.
+    struct FixedModel(Vec<((usize, usize), Ordering)>);
+
+    impl MatchModel for FixedModel {
+        fn knockout(&self, ratings: &mut [f64], first: usize, second: usize, _rng: &mut dyn RngCore) -> bool {
+            ratings[first] > ratings[second]
+        }
+
+        fn group(&self, ratings: &mut [f64], first: usize, second: usize, _rng: &mut dyn RngCore) -> Ordering {
+            self.0.iter()
+                .find(|(teams, _)| *teams == (first, second))
+                .map_or_else(|| ratings[first].total_cmp(&ratings[second]), |&(_, result)| result)
+        }
+    }
+
+    #[test]
+    fn seeded_simulations_are_reproducible() {
+        let tournament = knockout();
+        let results = tournament.simulate(1000, &EloModel, &mut StdRng::seed_from_u64(42));
+
+        assert_eq!(results.stages, vec!["semifinal", "final", "winner"]);
+        assert_eq!(reached(&results), vec![
+            ("Brazil", vec![1000, 833, 606]),
+            ("Chile", vec![1000, 564, 218]),
+            ("Japan", vec![1000, 436, 131]),
+            ("Norway", vec![1000, 167, 45])
+        ]);
+    }
+
+    #[test]
+    fn single_thread_matches_sequential_simulation() {
+        let tournament = knockout();
+        let sequential = tournament.simulate(1000, &EloModel, &mut StdRng::seed_from_u64(7));
+        let parallel = tournament.simulate_parallel(1000, 1, 7, &EloModel);
+
+        assert_eq!(reached(&parallel), reached(&sequential));
+    }
+
+    #[test]
+    fn threads_split_every_simulation() {
+        let tournament = knockout();
+        let results = tournament.simulate_parallel(1001, 4, 7, &EloModel);
+
+        assert_eq!(results.simulations, 1001);
+        assert!(results.teams.iter().all(|team| team.reached[0] == 1001));
+        assert_eq!(results.teams.iter().map(|team| team.reached[2]).sum::<u32>(), 1001);
+    }
+
+    #[test]
+    fn group_standings_break_ties_by_head_to_head_then_wins() {
+        let tournament: Tournament = (0..5).map(|i| grouped(&format!("Team {}", i), 1500, "A")).collect();
+        let results = [1, 0, 0, 0, 1, 0, -1, 0, 1, 0].map(|result: i32| result.cmp(&0));
+        let model = FixedModel((0..5).tuple_combinations().zip(results).collect());
+        let mut ratings = vec![1500.0; 5];
+
+        let table = tournament.simulate_group(&[0, 1, 2, 3, 4], &model, &mut ratings, &mut StdRng::seed_from_u64(1));
+        let standings: Vec<(usize, u32, u32, u32)> = table.iter()
+            .map(|standing| (standing.team, standing.points, standing.head_to_head, standing.wins))
+            .collect();
+
+        // Teams 2 and 4 are tied on points, team 2 beat team 4. Teams 1 and 3 are also tied on head-to-head points.
+        assert_eq!(standings, vec![(0, 6, 0, 1), (2, 5, 3, 1), (4, 5, 0, 1), (1, 4, 1, 1), (3, 4, 1, 0)]);
+    }
+
+    #[test]
+    fn group_winners_face_runners_up_of_the_paired_group() {
+        let teams = [("A", 1900, 1800), ("B", 1950, 1700), ("C", 2000, 1600), ("D", 1850, 1750)];
+        let tournament: Tournament = teams.iter()
+            .flat_map(|&(group, winner, runner_up)| [grouped(&format!("{}1", group), winner, group), grouped(&format!("{}2", group), runner_up, group)])
+            .collect();
+
+        let mut ratings: Vec<f64> = tournament.teams.iter().map(|team| team.rating as f64).collect();
+        let bracket = tournament.simulate_groups(&FixedModel(Vec::new()), &mut ratings, &mut StdRng::seed_from_u64(1));
+
+        // A1 v B2 and C1 v D2 in the top half, B1 v A2 and D1 v C2 in the bottom half.
+        assert_eq!(bracket, [0, 3, 4, 7, 2, 1, 6, 5].map(Some));
+    }
+
+    #[test]
+    fn group_winners_are_seeded_first_with_an_odd_number_of_groups() {
+        let tournament: Tournament = ["A", "B", "C"].iter()
+            .flat_map(|&group| [grouped(&format!("{}1", group), 1800, group), grouped(&format!("{}2", group), 1600, group)])
+            .collect();
+
+        let mut ratings: Vec<f64> = tournament.teams.iter().map(|team| team.rating as f64).collect();
+        let bracket = tournament.simulate_groups(&FixedModel(Vec::new()), &mut ratings, &mut StdRng::seed_from_u64(1));
+
+        assert_eq!(bracket, vec![Some(0), None, Some(1), Some(3), Some(2), None, Some(4), Some(5)]);
+    }
+
+    #[test]
+    fn seeded_bracket_gives_byes_to_the_best_seeds() {
+        assert_eq!(seeded_bracket(&[1, 2, 3]), vec![Some(1), None, Some(2), Some(3)]);
+        assert_eq!(seeded_bracket(&[1, 2, 3, 4, 5]), vec![Some(1), None, Some(4), Some(5), Some(2), None, Some(3), None]);
+        assert_eq!(seeded_bracket(&[1, 2, 3, 4, 5, 6]), vec![Some(1), None, Some(4), Some(5), Some(2), None, Some(3), Some(6)]);
+
+        for teams in [3, 5, 6] {
+            let seeds: Vec<usize> = (1..=teams).collect();
+            let bracket = seeded_bracket(&seeds);
+            let size = bracket.len();
+            let byes = size - teams;
+
+            assert_eq!(size, teams.next_power_of_two());
+            assert!(bracket[..size / 2].contains(&Some(1)));
+            assert!(bracket[size / 2..].contains(&Some(2)));
+
+            for pair in bracket.chunks(2) {
+                if let [Some(seed), None] | [None, Some(seed)] = pair {
+                    assert!(*seed <= byes, "{} teams: seed {} has a bye", teams, seed);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn knockout_bracket_seeds_teams_by_seed_or_rating() {
+        let teams = [team("Norway", 1600), team("Brazil", 2000), team("Chile", 1800)];
+        assert_eq!(Tournament::knockout_bracket(&teams), vec![Some(1), None, Some(2), Some(0)]);
+
+        let seeded = [
+            Team { seed: Some(2), ..team("Norway", 1600) },
+            Team { seed: Some(1), ..team("Brazil", 2000) },
+            Team { seed: Some(3), ..team("Chile", 1800) }
+        ];
+
+        assert_eq!(Tournament::knockout_bracket(&seeded), vec![Some(1), None, Some(0), Some(2)]);
+
+        // A full bracket without seeds keeps the order of the teams.
+        assert_eq!(knockout().bracket, [0, 1, 2, 3].map(Some));
+    }
+
+    #[test]
+    fn names_the_stages_of_the_bracket() {
+        let knockout_stages = |teams: u32| -> Vec<String> {
+            (0..teams).map(|i| team(&format!("Team {}", i), 1500 + i)).collect::<Tournament>().stages()
+        };
+        let group_stages = |groups: usize| -> Vec<String> {
+            (0..groups * 4).map(|i| grouped(&format!("Team {}", i), 1500, &(i % groups).to_string())).collect::<Tournament>().stages()
+        };
+
+        assert_eq!(knockout_stages(1), vec!["winner"]);
+        assert_eq!(knockout_stages(2), vec!["final", "winner"]);
+        assert_eq!(knockout_stages(6), vec!["quarterfinal", "semifinal", "final", "winner"]);
+        assert_eq!(knockout_stages(12), vec!["round of 16", "quarterfinal", "semifinal", "final", "winner"]);
+        assert_eq!(group_stages(3), vec!["quarterfinal", "semifinal", "final", "winner"]);
+        assert_eq!(group_stages(8), vec!["round of 16", "quarterfinal", "semifinal", "final", "winner"]);
+        assert_eq!(group_stages(10)[0], "round of 32");
+    }
+
+    #[test]
+    fn elo_updates_conserve_the_rating_sum() {
+        let mut ratings = vec![1500.0, 1800.0, 1650.0];
+
+        for (first, second, result) in [(0, 1, Ordering::Greater), (1, 2, Ordering::Equal), (2, 0, Ordering::Less), (0, 1, Ordering::Less)] {
+            let before = ratings[first] + ratings[second];
+            EloUpdatesModel::update(&mut ratings, first, second, result);
+
+            assert!((ratings[first] + ratings[second] - before).abs() < 1e-9);
+        }
+
+        assert!((ratings.iter().sum::<f64>() - 4950.0).abs() < 1e-9);
+
+        // An upset moves more points than an expected result.
+        let mut upset = vec![1500.0, 1800.0];
+        let mut expected = upset.clone();
+        EloUpdatesModel::update(&mut upset, 0, 1, Ordering::Greater);
+        EloUpdatesModel::update(&mut expected, 0, 1, Ordering::Less);
+        assert!(upset[0] - 1500.0 > 1500.0 - expected[0]);
+    }
+
+    #[test]
+    fn every_model_is_reproducible_with_a_seed() {
+        let tournament = knockout();
+        let check = |model: &dyn MatchModel, expected: Vec<(&str, Vec<u32>)>| {
+            let results = tournament.simulate(1000, model, &mut StdRng::seed_from_u64(42));
+            let parallel = tournament.simulate_parallel(1000, 1, 42, model);
+
+            assert_eq!(reached(&results), expected);
+            assert_eq!(reached(&parallel), expected);
+        };
+
+        check(&PoissonModel, vec![
+            ("Brazil", vec![1000, 893, 669]),
+            ("Chile", vec![1000, 620, 206]),
+            ("Japan", vec![1000, 380, 91]),
+            ("Norway", vec![1000, 107, 34])
+        ]);
+
+        check(&EloUpdatesModel, vec![
+            ("Brazil", vec![1000, 833, 596]),
+            ("Chile", vec![1000, 564, 220]),
+            ("Japan", vec![1000, 436, 136]),
+            ("Norway", vec![1000, 167, 48])
+        ]);
+    }
+}